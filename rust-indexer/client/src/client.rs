@@ -0,0 +1,187 @@
+use crate::config::{ClientConfig, RetryPolicy};
+use crate::error::{ClientError, Result};
+use crate::types::{
+    ChunkRequest, ChunkResponse, ExtractRequest, ExtractResponse, HashRequest, HashResponse, IndexJobRequest,
+    JobReport,
+};
+use reqwest::{Method, RequestBuilder, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// API version this client is written against, sent as `X-Api-Version` so a
+/// server that no longer speaks it answers 406 instead of an unreadable body.
+pub const API_VERSION: u32 = 1;
+
+const VERSION_HEADER: &str = "x-api-version";
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Shape of every error body the indexer sends.
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: Option<String>,
+    request_id: Option<String>,
+}
+
+/// Typed client for the indexer's `/v1` API. Cheap to clone; clones share
+/// the connection pool.
+#[derive(Debug, Clone)]
+pub struct SherlockClient {
+    base: Url,
+    api_key: Option<String>,
+    retry: RetryPolicy,
+    http: reqwest::Client,
+}
+
+impl SherlockClient {
+    pub fn new(config: ClientConfig) -> Result<Self> {
+        let mut base = Url::parse(&config.url).map_err(|e| ClientError::InvalidUrl(format!("{}: {}", config.url, e)))?;
+        base.path_segments_mut()
+            .map_err(|_| ClientError::InvalidUrl(config.url.clone()))?
+            .pop_if_empty()
+            .push(&format!("v{}", API_VERSION));
+
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .build()
+            .map_err(ClientError::Transport)?;
+
+        Ok(Self {
+            base,
+            api_key: config.api_key,
+            retry: config.retry,
+            http,
+        })
+    }
+
+    /// Client configured from `SHERLOCK_INDEXER_*` environment variables.
+    pub fn from_env() -> Result<Self> {
+        Self::new(ClientConfig::from_env())
+    }
+
+    /// `POST /v1/extract/:repo/*file`: symbols defined in a file.
+    pub async fn extract(&self, repo_path: &str, file_path: &str, request: &ExtractRequest) -> Result<ExtractResponse> {
+        let url = self.file_url("extract", repo_path, file_path);
+        self.call(Method::POST, url, Some(request), None, true).await
+    }
+
+    /// `POST /v1/extract-deps/:repo/*file`: imports of a file, one entry each.
+    pub async fn extract_dependencies(&self, repo_path: &str, file_path: &str) -> Result<ExtractResponse> {
+        let url = self.file_url("extract-deps", repo_path, file_path);
+        self.call(Method::POST, url, Some(&ExtractRequest::default()), None, true).await
+    }
+
+    /// `POST /v1/hash/:repo/*file`: content hash of a file or a line range of it.
+    pub async fn hash(&self, repo_path: &str, file_path: &str, request: &HashRequest) -> Result<HashResponse> {
+        let url = self.file_url("hash", repo_path, file_path);
+        self.call(Method::POST, url, Some(request), None, true).await
+    }
+
+    /// `POST /v1/chunk/:repo/*file`: a file cut into embedding-sized chunks.
+    pub async fn chunk(&self, repo_path: &str, file_path: &str, request: &ChunkRequest) -> Result<ChunkResponse> {
+        let url = self.file_url("chunk", repo_path, file_path);
+        self.call(Method::POST, url, Some(request), None, true).await
+    }
+
+    /// `POST /v1/jobs/index`: queues a repository index run. Without an
+    /// idempotency key a failed submission is not retried, since the server
+    /// may have queued the job before the connection dropped.
+    pub async fn submit_index_job(&self, request: &IndexJobRequest, idempotency_key: Option<&str>) -> Result<JobReport> {
+        let url = self.url(&["jobs", "index"]);
+        self.call(Method::POST, url, Some(request), idempotency_key, idempotency_key.is_some())
+            .await
+    }
+
+    /// `GET /v1/jobs/:id`: current state and progress of an index job.
+    pub async fn job(&self, job_id: &str) -> Result<JobReport> {
+        let url = self.url(&["jobs", job_id]);
+        self.call(Method::GET, url, None::<&()>, None, true).await
+    }
+
+    /// Polls a job every `interval` until it completes, fails or is superseded.
+    pub async fn wait_for_job(&self, job_id: &str, interval: Duration) -> Result<JobReport> {
+        loop {
+            let report = self.job(job_id).await?;
+            if report.state.is_finished() {
+                return Ok(report);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Base URL with `segments` appended, each percent-encoded on its own.
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .expect("base URL was checked in new()")
+            .extend(segments);
+        url
+    }
+
+    /// URL of a per-file route. The repository is a single path segment, so
+    /// slashes in it are encoded; those in the file path stay separators.
+    fn file_url(&self, route: &str, repo_path: &str, file_path: &str) -> Url {
+        let mut url = self.url(&[route, repo_path]);
+        url.path_segments_mut()
+            .expect("base URL was checked in new()")
+            .extend(file_path.split('/').filter(|part| !part.is_empty()));
+        url
+    }
+
+    /// Sends a request, retrying per the retry policy when `retryable`.
+    async fn call<B, T>(
+        &self,
+        method: Method,
+        url: Url,
+        body: Option<&B>,
+        idempotency_key: Option<&str>,
+        retryable: bool,
+    ) -> Result<T>
+    where
+        B: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        let mut attempt = 0;
+        loop {
+            let mut request = self
+                .http
+                .request(method.clone(), url.clone())
+                .header(VERSION_HEADER, API_VERSION);
+            if let Some(key) = &self.api_key {
+                request = request.bearer_auth(key);
+            }
+            if let Some(key) = idempotency_key {
+                request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+            }
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+
+            match send(request).await {
+                Ok(value) => return Ok(value),
+                Err(e) if retryable && e.is_retryable() && attempt < self.retry.max_retries => {
+                    attempt += 1;
+                    let backoff = self.retry.backoff(attempt);
+                    tracing::warn!("{} {} failed (attempt {}): {}; retrying in {:?}", method, url, attempt, e, backoff);
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+    let response = request.send().await.map_err(ClientError::Transport)?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.json::<ErrorBody>().await.ok();
+        return Err(ClientError::Status {
+            status,
+            message: body.as_ref().and_then(|b| b.error.clone()),
+            request_id: body.and_then(|b| b.request_id),
+        });
+    }
+    response.json().await.map_err(ClientError::Decode)
+}
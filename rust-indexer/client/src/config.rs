@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+pub const DEFAULT_URL: &str = "http://localhost:8081";
+
+/// How failed calls are retried. Only connection failures, timeouts and
+/// 429/502/503/504 answers are retried, and job submissions only when they
+/// carry an idempotency key.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each one after it.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retries.
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// Base URL of the indexer, without the `/v1` prefix.
+    pub url: String,
+    /// Sent as `Authorization: Bearer <key>`.
+    pub api_key: Option<String>,
+    /// Bound on each attempt, not on the call as a whole.
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    pub retry: RetryPolicy,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_URL.to_string(),
+            api_key: None,
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(5),
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+impl ClientConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Reads `SHERLOCK_INDEXER_*` environment variables, falling back to the
+    /// defaults for any that are unset.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            url: std::env::var("SHERLOCK_INDEXER_URL")
+                .ok()
+                .filter(|u| !u.is_empty())
+                .unwrap_or(defaults.url),
+            api_key: std::env::var("SHERLOCK_INDEXER_API_KEY").ok().filter(|k| !k.is_empty()),
+            timeout: env_parse("SHERLOCK_INDEXER_TIMEOUT_SECS")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.timeout),
+            connect_timeout: defaults.connect_timeout,
+            retry: RetryPolicy {
+                max_retries: env_parse("SHERLOCK_INDEXER_MAX_RETRIES").unwrap_or(defaults.retry.max_retries),
+                ..defaults.retry
+            },
+        }
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok().and_then(|v| v.parse().ok())
+}
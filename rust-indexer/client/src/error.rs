@@ -0,0 +1,48 @@
+use reqwest::StatusCode;
+
+/// Why a call to the indexer failed.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The indexer answered with a non-success status; `message` and
+    /// `request_id` come from the error body when it had them.
+    #[error("Indexer returned {status}{}", message.as_deref().map(|m| format!(": {}", m)).unwrap_or_default())]
+    Status {
+        status: StatusCode,
+        message: Option<String>,
+        request_id: Option<String>,
+    },
+    /// The request never got a response: connection failure or timeout.
+    #[error("Request to the indexer failed: {0}")]
+    Transport(#[source] reqwest::Error),
+    /// The response body did not match the expected shape.
+    #[error("Failed to decode indexer response: {0}")]
+    Decode(#[source] reqwest::Error),
+    #[error("Invalid indexer URL: {0}")]
+    InvalidUrl(String),
+}
+
+impl ClientError {
+    /// Whether sending the same request again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Status { status, .. } => matches!(
+                *status,
+                StatusCode::TOO_MANY_REQUESTS
+                    | StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            Self::Transport(_) => true,
+            Self::Decode(_) | Self::InvalidUrl(_) => false,
+        }
+    }
+
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
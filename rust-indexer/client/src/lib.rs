@@ -0,0 +1,13 @@
+mod client;
+mod config;
+mod error;
+mod types;
+
+pub use client::{SherlockClient, API_VERSION};
+pub use config::{ClientConfig, RetryPolicy, DEFAULT_URL};
+pub use error::{ClientError, Result};
+pub use types::*;
+
+/// Model types shared with the server.
+pub use sherlock_indexer_core::chunker::{ChunkSettings, ChunkStrategy, WindowUnit};
+pub use sherlock_indexer_core::symbol::{CodeChunk, CodeSymbol, ErrorRange};
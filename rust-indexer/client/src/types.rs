@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use sherlock_indexer_core::chunker::ChunkSettings;
+use sherlock_indexer_core::symbol::{CodeChunk, CodeSymbol, ErrorRange};
+
+/// Order of a symbol listing; see the server's `page` module.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolSort {
+    Position,
+    Name,
+    Kind,
+    Size,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Body of `POST /v1/extract/...`. Unset fields are left out so the server
+/// defaults apply.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ExtractRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<i32>,
+    pub include_metrics: bool,
+    pub include_halstead: bool,
+    pub include_tokens: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
+    pub enrich: bool,
+    /// Parses the file as this language instead of detecting it from the path.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Comma-separated symbol types to keep (`function,method`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kinds: Option<String>,
+    pub exported_only: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_lines: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SymbolSort>,
+    pub order: SortOrder,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExtractResponse {
+    pub symbols: Vec<CodeSymbol>,
+    /// The file has syntax errors, so `symbols` may be incomplete.
+    pub has_errors: bool,
+    pub error_count: usize,
+    pub error_ranges: Vec<ErrorRange>,
+    pub lossy_decoded: bool,
+    /// The file holds binary content and was not parsed.
+    pub binary_file: bool,
+    pub next_cursor: Option<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HashRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<i32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HashResponse {
+    pub hash: String,
+    pub success: bool,
+}
+
+/// Body of `POST /v1/chunk/...`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChunkRequest {
+    /// Overrides the configured settings for the file's language.
+    #[serde(flatten)]
+    pub settings: ChunkSettings,
+    pub include_tokens: bool,
+    /// Sends each chunk to the configured embedding endpoint.
+    pub embed: bool,
+    pub enrich: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChunkResponse {
+    pub chunks: Vec<CodeChunk>,
+    pub binary_file: bool,
+    pub tokenizer: Option<String>,
+    pub embedding_model: Option<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexJobRequest {
+    pub repo_path: String,
+    /// Receives the job report once the job completes or fails.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    /// Another job for the same repository is running first.
+    Waiting,
+    Running,
+    Completed,
+    Failed,
+    /// Skipped because a newer job for the same repository was queued behind it.
+    Superseded,
+}
+
+impl JobState {
+    /// Whether the job will not change state any more.
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Superseded)
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ProgressSnapshot {
+    pub files_discovered: usize,
+    /// Files handled so far, whether indexed or skipped.
+    pub files_done: usize,
+    pub files_parsed: usize,
+    pub symbols_extracted: usize,
+    pub errors: usize,
+    pub current_file: Option<String>,
+    pub elapsed_ms: u64,
+    pub eta_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexSummary {
+    pub files_indexed: usize,
+    pub chunks_upserted: usize,
+    pub sink: String,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct JobReport {
+    pub id: String,
+    pub repo_path: String,
+    pub state: JobState,
+    pub progress: ProgressSnapshot,
+    /// Unix timestamps (seconds).
+    pub created_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    pub summary: Option<IndexSummary>,
+    pub error: Option<String>,
+    pub success: bool,
+}
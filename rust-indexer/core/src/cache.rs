@@ -0,0 +1,181 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+pub const DEFAULT_PARSE_CACHE_ENTRIES: usize = 512;
+
+tokio::task_local! {
+    static PARTITION: Option<String>;
+}
+
+/// Runs `call` with the caches partitioned for `tenant`: entries it reads
+/// and writes are those of the tenant only, never another tenant's.
+pub async fn partitioned<F: Future>(tenant: Option<String>, call: F) -> F::Output {
+    PARTITION.scope(tenant, call).await
+}
+
+/// The tenant the current call's cache entries belong to; `None` outside
+/// [`partitioned`] or for callers without a tenant.
+pub fn partition() -> Option<String> {
+    PARTITION.try_with(Clone::clone).ok().flatten()
+}
+
+/// What a cached value was derived from; a different stamp means the file changed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    pub async fn of(path: &str) -> std::io::Result<Self> {
+        let metadata = crate::fs::metadata(path).await?;
+        Ok(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+
+    /// Compact form for keys of caches shared outside the process.
+    pub fn key(&self) -> String {
+        let modified = self
+            .modified
+            .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        format!("{}.{}", modified, self.len)
+    }
+}
+
+/// A cached file: the partition it belongs to and its path.
+type EntryKey = (Option<String>, String);
+
+struct Entry<T> {
+    value: Arc<T>,
+    stamp: FileStamp,
+    bytes: usize,
+    last_used: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheStats {
+    pub name: &'static str,
+    pub entries: usize,
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    /// Approximate: the size of the cached sources, not of derived structures.
+    pub memory_bytes: usize,
+}
+
+/// Per-file cache with least-recently-used eviction, partitioned by tenant.
+/// A capacity of zero disables caching.
+pub struct FileCache<T> {
+    name: &'static str,
+    capacity: usize,
+    entries: Mutex<HashMap<EntryKey, Entry<T>>>,
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<T> FileCache<T> {
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        Self {
+            name,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// The cached value for `path` in the current partition if it was built
+    /// from the same file version.
+    pub fn get(&self, path: &str, stamp: FileStamp) -> Option<Arc<T>> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get_mut(&(partition(), path.to_string())) {
+            Some(entry) if entry.stamp == stamp => {
+                entry.last_used = self.clock.fetch_add(1, Ordering::Relaxed);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.value.clone())
+            }
+            _ => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn insert(&self, path: &str, stamp: FileStamp, value: Arc<T>, bytes: usize) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = (partition(), path.to_string());
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        let last_used = self.clock.fetch_add(1, Ordering::Relaxed);
+        entries.insert(key, Entry { value, stamp, bytes, last_used });
+    }
+
+    /// Drops entries under `repo_path`, or everything when `None`, in every
+    /// partition; returns how many went.
+    pub fn purge(&self, repo_path: Option<&str>) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        match repo_path {
+            Some(repo_path) => {
+                let prefix = format!("{}/", repo_path.trim_end_matches('/'));
+                entries.retain(|(_, path), _| !path.starts_with(&prefix));
+            }
+            None => entries.clear(),
+        }
+        before - entries.len()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let entries = self.entries.lock().unwrap();
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        CacheStats {
+            name: self.name,
+            entries: entries.len(),
+            capacity: self.capacity,
+            hits,
+            misses,
+            hit_rate: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+            memory_bytes: entries.values().map(|entry| entry.bytes).sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tenants_do_not_see_each_others_entries() {
+        let cache = FileCache::new("test", 8);
+        let stamp = FileStamp { modified: None, len: 1 };
+        partitioned(Some("acme".to_string()), async { cache.insert("/repo/a.rs", stamp, Arc::new(1), 1) }).await;
+
+        assert!(partitioned(Some("acme".to_string()), async { cache.get("/repo/a.rs", stamp) }).await.is_some());
+        assert!(partitioned(Some("globex".to_string()), async { cache.get("/repo/a.rs", stamp) }).await.is_none());
+        assert!(cache.get("/repo/a.rs", stamp).is_none());
+        assert_eq!(cache.purge(Some("/repo")), 1);
+    }
+}
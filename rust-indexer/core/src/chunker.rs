@@ -0,0 +1,625 @@
+use crate::symbol::{ChunkManifestEntry, CodeChunk};
+use crate::tokens::{Tokenizer, TokenizerRegistry};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+/// How a file is cut into chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkStrategy {
+    /// Align chunks with top-level declarations, so a class or impl block
+    /// stays whole (needs a supported language).
+    #[default]
+    #[serde(alias = "class")]
+    Symbol,
+    /// Give every function and method its own chunk, splitting classes and
+    /// impl blocks apart (needs a supported language).
+    Function,
+    /// Fixed-size overlapping windows; works for any text file.
+    Window,
+    /// One chunk per Jupyter notebook cell; other files fall back to windows.
+    Cell,
+}
+
+/// Unit in which window size and overlap are measured.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowUnit {
+    #[default]
+    Lines,
+    Tokens,
+}
+
+pub const DEFAULT_WINDOW_SIZE: usize = 50;
+pub const DEFAULT_WINDOW_OVERLAP: usize = 10;
+
+/// Chunking behaviour requested by the caller.
+#[derive(Debug, Clone)]
+pub struct ChunkOptions {
+    pub strategy: ChunkStrategy,
+    /// Upper bound on tokens per chunk; requires `tokenizer`.
+    pub max_tokens: Option<usize>,
+    /// When set, every chunk reports its token count.
+    pub tokenizer: Option<Tokenizer>,
+    pub window_size: usize,
+    pub window_overlap: usize,
+    pub window_unit: WindowUnit,
+    /// Parses the file as this language instead of detecting it.
+    pub language: Option<String>,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            strategy: ChunkStrategy::Symbol,
+            max_tokens: None,
+            tokenizer: None,
+            window_size: DEFAULT_WINDOW_SIZE,
+            window_overlap: DEFAULT_WINDOW_OVERLAP,
+            window_unit: WindowUnit::Lines,
+            language: None,
+        }
+    }
+}
+
+/// Chunking settings as given by a request or the config file. Every field is
+/// optional so the two can be layered over the built-in defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChunkSettings {
+    pub strategy: Option<ChunkStrategy>,
+    pub max_tokens: Option<usize>,
+    /// Window size for the `window` strategy, in `unit`s.
+    pub window_size: Option<usize>,
+    /// Lines or tokens shared between consecutive windows.
+    pub overlap: Option<usize>,
+    pub unit: Option<WindowUnit>,
+    pub tokenizer: Option<String>,
+}
+
+impl ChunkSettings {
+    /// Fills every unset field from `fallback`.
+    pub fn or(self, fallback: &ChunkSettings) -> ChunkSettings {
+        ChunkSettings {
+            strategy: self.strategy.or(fallback.strategy),
+            max_tokens: self.max_tokens.or(fallback.max_tokens),
+            window_size: self.window_size.or(fallback.window_size),
+            overlap: self.overlap.or(fallback.overlap),
+            unit: self.unit.or(fallback.unit),
+            tokenizer: self.tokenizer.or_else(|| fallback.tokenizer.clone()),
+        }
+    }
+
+    /// Builds chunk options, loading a tokenizer when token counts or
+    /// token-based limits are needed. A `max_tokens` of zero is refused.
+    pub fn resolve(&self, tokenizers: &TokenizerRegistry, include_tokens: bool) -> Result<ChunkOptions> {
+        if self.max_tokens == Some(0) {
+            return Err(anyhow::anyhow!("max_tokens must be at least 1"));
+        }
+        let unit = self.unit.unwrap_or_default();
+        let tokenizer = if include_tokens || self.max_tokens.is_some() || unit == WindowUnit::Tokens {
+            Some(tokenizers.get(self.tokenizer.as_deref())?)
+        } else {
+            None
+        };
+
+        Ok(ChunkOptions {
+            strategy: self.strategy.unwrap_or_default(),
+            max_tokens: self.max_tokens,
+            tokenizer,
+            window_size: self.window_size.unwrap_or(DEFAULT_WINDOW_SIZE),
+            window_overlap: self.overlap.unwrap_or(DEFAULT_WINDOW_OVERLAP),
+            window_unit: unit,
+            language: None,
+        })
+    }
+}
+
+type ChunkRange = (usize, usize, Option<String>);
+
+/// Result of comparing fresh chunks against a previous manifest.
+#[derive(Debug, Default)]
+pub struct ChunkDiff {
+    pub changed: Vec<CodeChunk>,
+    pub removed: Vec<ChunkManifestEntry>,
+    pub unchanged: usize,
+}
+
+/// Splits `current` into chunks absent from `previous` and counts the rest;
+/// previous entries whose hash no longer occurs become tombstones.
+pub fn diff_chunks(current: Vec<CodeChunk>, previous: &[ChunkManifestEntry]) -> ChunkDiff {
+    let previous_hashes: HashSet<&str> = previous.iter().map(|p| p.hash.as_str()).collect();
+    let current_hashes: HashSet<String> = current.iter().map(|c| c.hash.clone()).collect();
+
+    let mut diff = ChunkDiff::default();
+    for chunk in current {
+        if previous_hashes.contains(chunk.hash.as_str()) {
+            diff.unchanged += 1;
+        } else {
+            diff.changed.push(chunk);
+        }
+    }
+    diff.removed = previous
+        .iter()
+        .filter(|p| !current_hashes.contains(&p.hash))
+        .cloned()
+        .collect();
+
+    diff
+}
+
+/// Stable content hash shared by chunking and the `/hash` endpoint: the
+/// first 64 bits of the SHA-256, so it is the same across builds and
+/// releases.
+pub fn content_hash(content: &str) -> String {
+    Sha256::digest(content.as_bytes())[..8].iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Hash that ignores indentation, trailing whitespace and blank lines, so
+/// copies that differ only in formatting collide.
+pub fn normalized_hash(content: &str) -> String {
+    let normalized: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    content_hash(&normalized.join("\n"))
+}
+
+/// Splits a file into chunks along top-level declaration boundaries. Without a
+/// token budget every declaration is its own chunk (leading comments attached);
+/// with one, adjacent declarations are packed together and oversized ones are
+/// split by line, so no chunk exceeds `max_tokens`. Under the `function`
+/// strategy, declarations containing any of the `functions` row ranges are cut
+/// at those functions first, and functions are never packed together.
+pub fn chunk_tree(
+    root: &tree_sitter::Node,
+    source: &str,
+    file_path: &str,
+    options: &ChunkOptions,
+    functions: &[(usize, usize)],
+) -> Result<Vec<CodeChunk>> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut segments = top_level_segments(root, lines.len());
+    let per_function = options.strategy == ChunkStrategy::Function;
+    if per_function {
+        segments = split_at_functions(&segments, functions, &lines);
+    }
+
+    let ranges: Vec<ChunkRange> = match (options.max_tokens, &options.tokenizer) {
+        (Some(max_tokens), Some(tokenizer)) if per_function => {
+            let mut ranges = Vec::new();
+            for &(start, end) in &segments {
+                ranges.extend(split_lines(start, end, &lines, max_tokens, tokenizer)?);
+            }
+            ranges
+        }
+        (Some(max_tokens), Some(tokenizer)) => pack_segments(&segments, &lines, max_tokens, tokenizer)?,
+        (Some(_), None) => return Err(anyhow::anyhow!("max_tokens requires a tokenizer")),
+        _ => segments.iter().map(|&(start, end)| (start, end, None)).collect(),
+    };
+
+    Ok(build_chunks(ranges, &lines, file_path, options))
+}
+
+/// Splits a Jupyter notebook into one chunk per non-empty cell. Line numbers
+/// count through the cell sources in order, as if the notebook were a script.
+/// Markdown cells are tagged `markdown`, code cells with the kernel language.
+pub fn chunk_notebook(source: &str, file_path: &str, options: &ChunkOptions) -> Result<Vec<CodeChunk>> {
+    let notebook: serde_json::Value = serde_json::from_str(source)?;
+    let language = notebook["metadata"]["language_info"]["name"]
+        .as_str()
+        .or_else(|| notebook["metadata"]["kernelspec"]["language"].as_str())
+        .map(str::to_string);
+
+    let mut text = String::new();
+    let mut cells = Vec::new();
+    let mut line_count = 0;
+    for cell in notebook["cells"].as_array().into_iter().flatten() {
+        let cell_source = match &cell["source"] {
+            serde_json::Value::Array(parts) => parts.iter().filter_map(|p| p.as_str()).collect::<String>(),
+            serde_json::Value::String(s) => s.clone(),
+            _ => continue,
+        };
+        let cell_text = cell_source.trim_end();
+        if cell_text.trim().is_empty() {
+            continue;
+        }
+        let cell_lines = cell_text.lines().count();
+        text.push_str(cell_text);
+        text.push('\n');
+        let is_markdown = cell["cell_type"].as_str() == Some("markdown");
+        cells.push((line_count, line_count + cell_lines - 1, is_markdown));
+        line_count += cell_lines;
+    }
+
+    let lines: Vec<&str> = text.lines().collect();
+    let mut ranges = Vec::new();
+    for &(start, end, _) in &cells {
+        match (options.max_tokens, &options.tokenizer) {
+            (Some(max_tokens), Some(tokenizer)) => ranges.extend(split_lines(start, end, &lines, max_tokens, tokenizer)?),
+            (Some(_), None) => return Err(anyhow::anyhow!("max_tokens requires a tokenizer")),
+            _ => ranges.push((start, end, None)),
+        }
+    }
+
+    let mut chunks = build_chunks(ranges, &lines, file_path, options);
+    for chunk in &mut chunks {
+        let row = chunk.line_start as usize - 1;
+        let is_markdown = cells.iter().any(|&(start, end, md)| md && start <= row && row <= end);
+        chunk.language = if is_markdown { Some("markdown".to_string()) } else { language.clone() };
+    }
+
+    Ok(chunks)
+}
+
+/// Splits any text into overlapping windows of `window_size` lines or tokens.
+/// Token windows still break on line boundaries so line numbers stay exact.
+pub fn chunk_text(source: &str, file_path: &str, options: &ChunkOptions) -> Result<Vec<CodeChunk>> {
+    let lines: Vec<&str> = source.lines().collect();
+    let size = options.window_size.max(1);
+    let overlap = options.window_overlap.min(size - 1);
+
+    let mut ranges: Vec<ChunkRange> = match (options.window_unit, &options.tokenizer) {
+        (WindowUnit::Lines, _) => line_windows(lines.len(), size, overlap),
+        (WindowUnit::Tokens, Some(tokenizer)) => token_windows(&lines, size, overlap, tokenizer)?,
+        (WindowUnit::Tokens, None) => return Err(anyhow::anyhow!("token windows require a tokenizer")),
+    };
+
+    if let Some(max_tokens) = options.max_tokens {
+        let tokenizer = options
+            .tokenizer
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("max_tokens requires a tokenizer"))?;
+        let mut bounded = Vec::with_capacity(ranges.len());
+        for (start, end, content) in ranges {
+            if content.is_some() || tokenizer.count(&lines[start..=end].join("\n")) <= max_tokens {
+                bounded.push((start, end, content));
+            } else {
+                bounded.extend(split_lines(start, end, &lines, max_tokens, tokenizer)?);
+            }
+        }
+        ranges = bounded;
+    }
+
+    Ok(build_chunks(ranges, &lines, file_path, options))
+}
+
+fn line_windows(line_count: usize, size: usize, overlap: usize) -> Vec<ChunkRange> {
+    let step = size - overlap;
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start < line_count {
+        let end = (start + size).min(line_count) - 1;
+        ranges.push((start, end, None));
+        if end + 1 >= line_count {
+            break;
+        }
+        start += step;
+    }
+    ranges
+}
+
+fn token_windows(lines: &[&str], size: usize, overlap: usize, tokenizer: &Tokenizer) -> Result<Vec<ChunkRange>> {
+    let count = |start: usize, end: usize| tokenizer.count(&lines[start..=end].join("\n"));
+    let mut ranges = Vec::new();
+    let mut start = 0;
+
+    while start < lines.len() {
+        if tokenizer.count(lines[start]) > size {
+            for piece in split_tokens(lines[start], size, tokenizer)? {
+                ranges.push((start, start, Some(piece)));
+            }
+            start += 1;
+            continue;
+        }
+
+        let mut end = start;
+        while end + 1 < lines.len() && count(start, end + 1) <= size {
+            end += 1;
+        }
+        ranges.push((start, end, None));
+        if end + 1 >= lines.len() {
+            break;
+        }
+
+        // Step back over trailing lines worth at most `overlap` tokens, always moving forward
+        let mut next = end + 1;
+        while next - 1 > start && count(next - 1, end) <= overlap {
+            next -= 1;
+        }
+        start = next;
+    }
+
+    Ok(ranges)
+}
+
+fn build_chunks(ranges: Vec<ChunkRange>, lines: &[&str], file_path: &str, options: &ChunkOptions) -> Vec<CodeChunk> {
+    ranges
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, end, content))| {
+            let content = content.unwrap_or_else(|| lines[start..=end].join("\n"));
+            CodeChunk {
+                id: format!("{}_chunk_{}", file_path, index),
+                file_path: file_path.to_string(),
+                line_start: start as i32 + 1,
+                line_end: end as i32 + 1,
+                hash: content_hash(&content),
+                language: None,
+                symbol: None,
+                imports: Vec::new(),
+                token_count: options.tokenizer.as_ref().map(|t| t.count(&content)),
+                embedding: None,
+                enrichment: None,
+                lossy_decoded: false,
+                content,
+            }
+        })
+        .collect()
+}
+
+/// Zero-based inclusive row ranges of top-level nodes, with comments folded into
+/// the declaration that follows them and nodes sharing a line merged.
+fn top_level_segments(root: &tree_sitter::Node, line_count: usize) -> Vec<(usize, usize)> {
+    let mut segments: Vec<(usize, usize)> = Vec::new();
+    let mut pending_comment: Option<usize> = None;
+    let mut cursor = root.walk();
+
+    for child in root.named_children(&mut cursor) {
+        let start = child.start_position().row;
+        let mut end = child.end_position().row;
+        // Nodes that swallow their trailing newline end at column 0 of the next row
+        if child.end_position().column == 0 && end > start {
+            end -= 1;
+        }
+        let end = end.min(line_count.saturating_sub(1));
+        if start >= line_count {
+            continue;
+        }
+
+        if child.kind().contains("comment") {
+            pending_comment.get_or_insert(start);
+            continue;
+        }
+
+        let start = pending_comment.take().unwrap_or(start);
+        match segments.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => segments.push((start, end)),
+        }
+    }
+
+    if let Some(start) = pending_comment {
+        segments.push((start, line_count.saturating_sub(1)));
+    }
+
+    segments
+}
+
+/// Cuts segments at the given function row ranges so each outermost function
+/// stands alone. Comments, attributes and decorators directly above a function
+/// stay with it; leftover container lines (headers, fields) form their own
+/// segments unless they hold nothing but punctuation.
+fn split_at_functions(segments: &[(usize, usize)], functions: &[(usize, usize)], lines: &[&str]) -> Vec<(usize, usize)> {
+    let mut functions = functions.to_vec();
+    functions.sort_by_key(|&(start, end)| (start, std::cmp::Reverse(end)));
+
+    let has_code = |start: usize, end: usize| {
+        lines[start..=end]
+            .iter()
+            .any(|line| line.chars().any(char::is_alphanumeric))
+    };
+    let is_preamble = |line: &str| {
+        let line = line.trim_start();
+        ["//", "/*", "*", "#", "@", "\"\"\""].iter().any(|p| line.starts_with(p))
+    };
+
+    let mut out = Vec::new();
+    for &(start, end) in segments {
+        let mut cursor = start;
+        for &(f_start, f_end) in functions.iter().filter(|&&(s, e)| s >= start && e <= end) {
+            // Nested functions are already covered by their parent
+            if f_start < cursor {
+                continue;
+            }
+            let mut head = f_start;
+            while head > cursor && is_preamble(lines[head - 1]) {
+                head -= 1;
+            }
+            if head > cursor && has_code(cursor, head - 1) {
+                out.push((cursor, head - 1));
+            }
+            out.push((head, f_end));
+            cursor = f_end + 1;
+        }
+        if cursor <= end && has_code(cursor, end) {
+            out.push((cursor, end));
+        }
+    }
+    out
+}
+
+/// Greedily packs segments into ranges that fit the token budget.
+fn pack_segments(
+    segments: &[(usize, usize)],
+    lines: &[&str],
+    max_tokens: usize,
+    tokenizer: &Tokenizer,
+) -> Result<Vec<ChunkRange>> {
+    let fits = |start: usize, end: usize| tokenizer.count(&lines[start..=end].join("\n")) <= max_tokens;
+
+    let mut ranges = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+
+    for &(start, end) in segments {
+        if let Some((cur_start, _)) = current {
+            if fits(cur_start, end) {
+                current = Some((cur_start, end));
+                continue;
+            }
+            let (s, e) = current.take().unwrap();
+            ranges.push((s, e, None));
+        }
+
+        if fits(start, end) {
+            current = Some((start, end));
+        } else {
+            ranges.extend(split_lines(start, end, lines, max_tokens, tokenizer)?);
+        }
+    }
+
+    if let Some((s, e)) = current {
+        ranges.push((s, e, None));
+    }
+
+    Ok(ranges)
+}
+
+/// Splits a range that exceeds the budget on line boundaries, falling back to
+/// token boundaries for single lines that are too long on their own.
+fn split_lines(
+    start: usize,
+    end: usize,
+    lines: &[&str],
+    max_tokens: usize,
+    tokenizer: &Tokenizer,
+) -> Result<Vec<ChunkRange>> {
+    let mut ranges = Vec::new();
+    let mut chunk_start = start;
+
+    while chunk_start <= end {
+        if tokenizer.count(lines[chunk_start]) > max_tokens {
+            for piece in split_tokens(lines[chunk_start], max_tokens, tokenizer)? {
+                ranges.push((chunk_start, chunk_start, Some(piece)));
+            }
+            chunk_start += 1;
+            continue;
+        }
+
+        let mut chunk_end = chunk_start;
+        while chunk_end < end
+            && tokenizer.count(&lines[chunk_start..=chunk_end + 1].join("\n")) <= max_tokens
+        {
+            chunk_end += 1;
+        }
+        ranges.push((chunk_start, chunk_end, None));
+        chunk_start = chunk_end + 1;
+    }
+
+    Ok(ranges)
+}
+
+fn split_tokens(line: &str, max_tokens: usize, tokenizer: &Tokenizer) -> Result<Vec<String>> {
+    debug_assert!(max_tokens > 0);
+    let tokens = tokenizer.encode(line);
+    let mut pieces = Vec::new();
+    let mut offset = 0;
+
+    while offset < tokens.len() {
+        // Every piece takes at least one token, so the loop always advances
+        let mut take = max_tokens.max(1).min(tokens.len() - offset);
+        // A cut inside a multi-byte character does not decode; back off until it does
+        loop {
+            match tokenizer.decode(tokens[offset..offset + take].to_vec()) {
+                Ok(piece) => {
+                    pieces.push(piece);
+                    break;
+                }
+                Err(_) if take > 1 => take -= 1,
+                Err(e) => return Err(e),
+            }
+        }
+        offset += take;
+    }
+
+    Ok(pieces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_max_tokens_is_refused() {
+        let settings = ChunkSettings {
+            max_tokens: Some(0),
+            ..Default::default()
+        };
+        assert!(settings.resolve(&TokenizerRegistry::new(), false).is_err());
+    }
+
+    fn windows(size: usize, overlap: usize, unit: WindowUnit) -> ChunkOptions {
+        ChunkOptions {
+            strategy: ChunkStrategy::Window,
+            tokenizer: Some(TokenizerRegistry::new().get(None).unwrap()),
+            window_size: size,
+            window_overlap: overlap,
+            window_unit: unit,
+            ..Default::default()
+        }
+    }
+
+    fn spans(chunks: &[CodeChunk]) -> Vec<(i32, i32)> {
+        chunks.iter().map(|chunk| (chunk.line_start, chunk.line_end)).collect()
+    }
+
+    #[test]
+    fn line_windows_overlap_by_the_requested_lines() {
+        assert_eq!(line_windows(5, 2, 1), vec![(0, 1, None), (1, 2, None), (2, 3, None), (3, 4, None)]);
+        assert_eq!(line_windows(5, 3, 0), vec![(0, 2, None), (3, 4, None)]);
+    }
+
+    #[test]
+    fn overlap_of_a_whole_window_still_advances() {
+        let source = (1..=6).map(|n| format!("line {}", n)).collect::<Vec<_>>().join("\n");
+        let chunks = chunk_text(&source, "a.txt", &windows(3, 5, WindowUnit::Lines)).unwrap();
+        assert_eq!(spans(&chunks), vec![(1, 3), (2, 4), (3, 5), (4, 6)]);
+    }
+
+    #[test]
+    fn empty_file_has_no_windows() {
+        for unit in [WindowUnit::Lines, WindowUnit::Tokens] {
+            assert!(chunk_text("", "a.txt", &windows(3, 1, unit)).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn over_long_line_is_cut_into_token_windows() {
+        let line = "let value = compute(first, second, third, fourth, fifth);";
+        let options = windows(4, 2, WindowUnit::Tokens);
+        let chunks = chunk_text(line, "a.rs", &options).unwrap();
+        let tokenizer = options.tokenizer.unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| (chunk.line_start, chunk.line_end) == (1, 1) && tokenizer.count(&chunk.content) <= 4));
+        assert_eq!(chunks.iter().map(|chunk| chunk.content.as_str()).collect::<String>(), line);
+    }
+
+    #[test]
+    fn token_windows_keep_moving_forward() {
+        let source = ["a", "bb cc", "d", "eee fff ggg", "h", "i j", "k"].join("\n");
+        for (size, overlap) in [(3, 2), (4, 3), (5, 4), (6, 1)] {
+            let chunks = chunk_text(&source, "a.txt", &windows(size, overlap, WindowUnit::Tokens)).unwrap();
+            let spans = spans(&chunks);
+            // Pieces of a line too long for the window share its span, but never repeat.
+            let advances = chunks.windows(2).all(|pair| {
+                let (a, b) = (&pair[0], &pair[1]);
+                a.line_start < b.line_start || (a.line_start == b.line_start && a.line_end == b.line_end && a.content != b.content)
+            });
+            assert!(advances, "{:?} for size {}", spans, size);
+            assert_eq!(spans.first().map(|span| span.0), Some(1));
+            assert_eq!(spans.last().map(|span| span.1), Some(7));
+        }
+    }
+
+    #[test]
+    fn long_lines_split_into_bounded_pieces() {
+        let tokenizer = TokenizerRegistry::new().get(None).unwrap();
+        let line = "let value = compute(first, second, third, fourth, fifth);";
+        let pieces = split_tokens(line, 3, &tokenizer).unwrap();
+        assert!(pieces.len() > 1);
+        assert!(pieces.iter().all(|piece| tokenizer.count(piece) <= 3));
+        assert_eq!(pieces.concat(), line);
+    }
+}
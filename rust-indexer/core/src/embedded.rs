@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use tree_sitter::{Language, Node, Parser, Range, Tree};
+
+/// Code of another language inside a document: the `<script>` or `<style>`
+/// blocks of an HTML file.
+pub struct Embedded {
+    pub language: &'static str,
+    /// Spans of the blocks in the document, in order.
+    pub ranges: Vec<Range>,
+}
+
+/// Non-empty script and style blocks of an HTML document, by language.
+/// Scripts of another `type` (templates, JSON data) and styles in a
+/// preprocessor language are left out.
+pub fn html_blocks(root: &Node, source: &str) -> Vec<Embedded> {
+    let mut blocks: Vec<Embedded> = Vec::new();
+    let mut cursor = root.walk();
+    let mut stack = vec![*root];
+    while let Some(node) = stack.pop() {
+        let language = match node.kind() {
+            "script_element" => script_language(&node, source),
+            "style_element" => style_language(&node, source),
+            _ => {
+                stack.extend(node.named_children(&mut cursor));
+                continue;
+            }
+        };
+        let Some(language) = language else {
+            continue;
+        };
+        let mut children = node.walk();
+        let Some(text) = node
+            .named_children(&mut children)
+            .find(|child| child.kind() == "raw_text")
+            .filter(|text| !source[text.byte_range()].trim().is_empty())
+        else {
+            continue;
+        };
+        match blocks.iter_mut().find(|block| block.language == language) {
+            Some(block) => block.ranges.push(text.range()),
+            None => blocks.push(Embedded {
+                language,
+                ranges: vec![text.range()],
+            }),
+        }
+    }
+    for block in &mut blocks {
+        block.ranges.sort_by_key(|range| range.start_byte);
+    }
+    blocks
+}
+
+/// Parses only `ranges` of `source`; positions in the tree are those in the
+/// whole document.
+pub fn parse(language: &Language, source: &str, ranges: &[Range]) -> Result<Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(language)?;
+    parser.set_included_ranges(ranges).ok().context("Embedded ranges overlap")?;
+    parser.parse(source, None).context("Failed to parse embedded code")
+}
+
+/// `lang` (Vue, Svelte) or `type` of a `<script>`.
+fn script_language(element: &Node, source: &str) -> Option<&'static str> {
+    if let Some(lang) = attribute(element, "lang", source) {
+        return match lang.to_lowercase().as_str() {
+            "js" | "javascript" | "jsx" => Some("javascript"),
+            "ts" | "typescript" => Some("typescript"),
+            "tsx" => Some("tsx"),
+            _ => None,
+        };
+    }
+    match attribute(element, "type", source).map(str::to_lowercase).as_deref() {
+        None | Some("" | "module" | "text/javascript" | "application/javascript" | "text/ecmascript" | "application/ecmascript") => {
+            Some("javascript")
+        }
+        Some("text/typescript" | "application/typescript") => Some("typescript"),
+        _ => None,
+    }
+}
+
+fn style_language(element: &Node, source: &str) -> Option<&'static str> {
+    match attribute(element, "lang", source).map(str::to_lowercase).as_deref() {
+        None | Some("css") => Some("css"),
+        _ => None,
+    }
+}
+
+/// Value of an attribute on an element's start tag; empty when it has none.
+fn attribute<'a>(element: &Node, name: &str, source: &'a str) -> Option<&'a str> {
+    let mut cursor = element.walk();
+    let start_tag = element.named_children(&mut cursor).find(|child| child.kind() == "start_tag")?;
+    let mut cursor = start_tag.walk();
+    let attribute = start_tag.named_children(&mut cursor).find(|child| {
+        child.kind() == "attribute"
+            && child
+                .named_child(0)
+                .is_some_and(|attribute_name| source[attribute_name.byte_range()].eq_ignore_ascii_case(name))
+    })?;
+    let Some(value) = attribute.named_child(1) else {
+        return Some("");
+    };
+    // `quoted_attribute_value` wraps the `attribute_value`
+    let value = match value.kind() {
+        "quoted_attribute_value" => match value.named_child(0) {
+            Some(inner) => inner,
+            None => return Some(""),
+        },
+        _ => value,
+    };
+    Some(&source[value.byte_range()])
+}
@@ -0,0 +1,71 @@
+use encoding_rs::{Encoding, UTF_8};
+
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Source text decoded from whatever encoding the file was written in.
+pub struct DecodedSource {
+    pub text: String,
+    /// Name of the encoding the bytes were decoded from, e.g. `windows-1252`.
+    pub encoding: &'static str,
+    /// Set when the file was not UTF-8, or held bytes that were replaced.
+    /// Byte offsets then refer to the decoded text, not to the file; line
+    /// numbers still match, since every supported encoding keeps newlines.
+    pub lossy_decoded: bool,
+}
+
+/// Decodes UTF-8 (with or without BOM) as is, UTF-16 by its BOM, and any
+/// other bytes by the most likely legacy encoding (Latin-1 for most older
+/// C/C++ sources), replacing undecodable sequences instead of failing.
+///
+/// The BOM is dropped and CRLF line endings become LF, so hashes and ranges
+/// match between Windows and Linux checkouts of the same file.
+pub fn decode(bytes: &[u8]) -> DecodedSource {
+    let mut decoded = decode_raw(bytes);
+    if decoded.text.contains("\r\n") {
+        decoded.text = decoded.text.replace("\r\n", "\n");
+    }
+    decoded
+}
+
+fn decode_raw(bytes: &[u8]) -> DecodedSource {
+    if let Some((encoding, bom)) = Encoding::for_bom(bytes) {
+        let (text, had_errors) = encoding.decode_without_bom_handling(&bytes[bom..]);
+        return DecodedSource {
+            text: text.into_owned(),
+            encoding: encoding.name(),
+            lossy_decoded: encoding != UTF_8 || had_errors,
+        };
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return DecodedSource {
+            text: text.to_string(),
+            encoding: UTF_8.name(),
+            lossy_decoded: false,
+        };
+    }
+
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, true);
+    let (text, _, _) = encoding.decode(bytes);
+    DecodedSource {
+        text: text.into_owned(),
+        encoding: encoding.name(),
+        lossy_decoded: true,
+    }
+}
+
+/// Whether `bytes` look like a binary file rather than text: a NUL byte in
+/// the first 8000 bytes, the same heuristic git uses. UTF-16 text (which is
+/// full of NULs) is recognised by its BOM first.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    if Encoding::for_bom(bytes).is_some() {
+        return false;
+    }
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+/// Reads and decodes a source file.
+pub async fn read(path: &str) -> std::io::Result<DecodedSource> {
+    Ok(decode(&crate::fs::read(path).await?))
+}
@@ -0,0 +1,35 @@
+/// Default cap on the size of a single source file (`MAX_FILE_BYTES`).
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Failures caused by the request rather than by the service.
+#[derive(Debug, thiserror::Error)]
+pub enum SourceError {
+    #[error("Repository path is not a directory")]
+    NotADirectory,
+    #[error("Unsupported file type")]
+    UnsupportedLanguage,
+    #[error("Unknown language {0:?}")]
+    UnknownLanguage(String),
+    #[error("The {language} grammar is disabled: {reason}")]
+    GrammarDisabled { language: String, reason: String },
+    #[error("No query pack {0:?} for this language")]
+    UnknownQueryPack(String),
+    #[error("Unknown git ref {0:?}")]
+    UnknownRevision(String),
+    #[error("Invalid line range: {reason} (the file has {line_count} lines)")]
+    InvalidLineRange { reason: &'static str, line_count: usize },
+    #[error("File is {size} bytes, over the {limit} byte limit")]
+    TooLarge { size: u64, limit: u64 },
+    #[error("File is binary")]
+    BinaryFile,
+}
+
+/// The request-side cause of `error`, if it has one.
+pub fn source_error(error: &anyhow::Error) -> Option<&SourceError> {
+    error.chain().find_map(|cause| cause.downcast_ref::<SourceError>())
+}
+
+/// Whether parsing failed because the file is binary.
+pub fn is_binary(error: &anyhow::Error) -> bool {
+    matches!(source_error(error), Some(SourceError::BinaryFile))
+}
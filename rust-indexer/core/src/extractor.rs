@@ -0,0 +1,627 @@
+use crate::metrics;
+use crate::symbol::CodeSymbol;
+use anyhow::Result;
+use std::sync::Arc;
+use tree_sitter::{Language, Node};
+
+/// Everything `ParserService` needs to know about one language. The built-in
+/// languages are implemented here; register more (or replace a built-in one
+/// under its name) with `ParserService::with_extractor`.
+pub trait LanguageExtractor: Send + Sync {
+    /// Name the language is registered and requested under (`rust`, `tsx`, ...).
+    fn name(&self) -> &str;
+
+    /// Grammar files of this language are parsed with.
+    fn grammar(&self) -> Language;
+
+    /// Whether this extractor claims a file the built-in detection does not
+    /// recognise, e.g. an org-specific extension. Checked before built-in
+    /// detection, so it can also take files over from a built-in language.
+    fn detect(&self, _file_path: &str) -> bool {
+        false
+    }
+
+    /// The symbol `node` declares, if it declares one. Called for every node
+    /// of the tree; nesting, filtering and metrics are handled by the caller.
+    fn extract_symbol(&self, node: &Node, source: &str, file_path: &str) -> Result<Option<CodeSymbol>>;
+
+    /// Node kinds of import, use and include statements.
+    fn import_kinds(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Node kinds of calls, each with the field holding what is called.
+    /// Their callees become symbol dependencies.
+    fn call_kinds(&self) -> &[(&str, &str)] {
+        &[]
+    }
+
+    /// Node kinds naming a type where it is used. They become symbol
+    /// dependencies.
+    fn type_kinds(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Types the declaration at `node` extends or implements, as written.
+    fn supertypes(&self, _node: &Node, _source: &str) -> Vec<String> {
+        vec![]
+    }
+
+    /// Documentation attached to the declaration at `node`.
+    fn docstring(&self, node: &Node, source: &str) -> Option<String> {
+        metrics::doc_comment(node, source, self.name())
+    }
+
+    /// Joins enclosing declaration names into a qualified name.
+    fn path_separator(&self) -> &str {
+        "."
+    }
+}
+
+/// Extractors for every built-in language whose feature is enabled.
+pub fn builtin() -> Vec<Arc<dyn LanguageExtractor>> {
+    vec![
+        #[cfg(feature = "rust")]
+        Arc::new(Rust),
+        #[cfg(feature = "javascript")]
+        Arc::new(JavaScript {
+            name: "javascript",
+            grammar: tree_sitter_javascript::language,
+        }),
+        #[cfg(feature = "typescript")]
+        Arc::new(JavaScript {
+            name: "typescript",
+            grammar: tree_sitter_typescript::language_typescript,
+        }),
+        #[cfg(feature = "typescript")]
+        Arc::new(JavaScript {
+            name: "tsx",
+            grammar: tree_sitter_typescript::language_tsx,
+        }),
+        #[cfg(feature = "go")]
+        Arc::new(Go),
+        #[cfg(feature = "python")]
+        Arc::new(Python),
+        #[cfg(feature = "java")]
+        Arc::new(Java),
+        #[cfg(feature = "cpp")]
+        Arc::new(Cpp),
+        #[cfg(feature = "css")]
+        Arc::new(Css),
+        #[cfg(feature = "html")]
+        Arc::new(Html),
+    ]
+}
+
+/// A symbol spanning `node`, named by its `name` field; `None` when the node
+/// has no name. Qualified name, metrics and token count are filled in later.
+pub fn named_symbol(
+    node: &Node,
+    source: &str,
+    file_path: &str,
+    symbol_type: &str,
+    exported: bool,
+    visibility: Option<&str>,
+) -> Result<Option<CodeSymbol>> {
+    let Some(name_node) = node.child_by_field_name("name") else {
+        return Ok(None);
+    };
+    let name = name_node.utf8_text(source.as_bytes())?.to_string();
+    Ok(Some(symbol_at(node, name, source, file_path, symbol_type, exported, visibility)))
+}
+
+/// A symbol called `name` spanning `node`.
+pub fn symbol_at(
+    node: &Node,
+    name: String,
+    source: &str,
+    file_path: &str,
+    symbol_type: &str,
+    exported: bool,
+    visibility: Option<&str>,
+) -> CodeSymbol {
+    CodeSymbol {
+        id: format!("{}_{}_{}", file_path, name, node.start_position().row),
+        symbol_name: name,
+        symbol_type: symbol_type.to_string(),
+        file_path: file_path.to_string(),
+        line_start: node.start_position().row as i32 + 1,
+        line_end: node.end_position().row as i32 + 1,
+        signature: Some(signature(node, source)),
+        dependencies: vec![],
+        exported,
+        visibility: visibility.map(str::to_string),
+        metrics: None,
+        token_count: None,
+        enrichment: None,
+        qualified_name: None,
+        supertypes: vec![],
+        reference_count: None,
+        owners: vec![],
+        external: false,
+    }
+}
+
+/// First line of the declaration (simplified).
+pub fn signature(node: &Node, source: &str) -> String {
+    let end_byte = node.end_byte().min(source.len());
+    let text = &source[node.start_byte()..end_byte];
+    text.lines().next().unwrap_or("").trim().to_string()
+}
+
+/// Types named in the inheritance clauses among `node`'s children
+/// (`extends`, `implements`, base lists, supertrait bounds), as written.
+pub fn heritage(node: &Node, source: &str) -> Vec<String> {
+    let mut types = Vec::new();
+    let mut cursor = node.walk();
+    let mut stack: Vec<_> = node
+        .named_children(&mut cursor)
+        .filter(|child| is_heritage_clause(child.kind()))
+        .collect();
+    stack.reverse();
+    while let Some(current) = stack.pop() {
+        if is_heritage_clause(current.kind()) {
+            let mut inner = current.walk();
+            let mut children: Vec<_> = current.named_children(&mut inner).collect();
+            children.reverse();
+            stack.extend(children);
+            continue;
+        }
+        // Generic arguments of an `extends` clause, keyword arguments such
+        // as `metaclass=`, access specifiers, lifetimes and `?Sized` name no
+        // supertype
+        if matches!(
+            current.kind(),
+            "type_arguments" | "keyword_argument" | "list_splat" | "dictionary_splat" | "access_specifier" | "virtual" | "lifetime" | "removed_trait_bound"
+                | "comment"
+        ) {
+            continue;
+        }
+        if let Ok(text) = current.utf8_text(source.as_bytes()) {
+            types.push(text.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+    }
+    types
+}
+
+fn is_heritage_clause(kind: &str) -> bool {
+    matches!(
+        kind,
+        "class_heritage"
+            | "extends_clause"
+            | "implements_clause"
+            | "extends_type_clause"
+            | "superclass"
+            | "super_interfaces"
+            | "extends_interfaces"
+            | "type_list"
+            | "argument_list"
+            | "trait_bounds"
+            | "base_class_clause"
+    )
+}
+
+#[cfg(feature = "rust")]
+struct Rust;
+
+#[cfg(feature = "rust")]
+impl LanguageExtractor for Rust {
+    fn name(&self) -> &str {
+        "rust"
+    }
+
+    fn grammar(&self) -> Language {
+        tree_sitter_rust::language()
+    }
+
+    fn extract_symbol(&self, node: &Node, source: &str, file_path: &str) -> Result<Option<CodeSymbol>> {
+        if node.kind() == "impl_item" {
+            // Named after the type it implements for; the trait, if any, is a supertype
+            let Some(name) = node.child_by_field_name("type").and_then(|n| rust_type_name(n, source)) else {
+                return Ok(None);
+            };
+            return Ok(Some(symbol_at(node, name.to_string(), source, file_path, "impl", false, None)));
+        }
+
+        let symbol_type = match node.kind() {
+            "function_item" => "function",
+            "struct_item" => "struct",
+            "enum_item" => "enum",
+            "trait_item" => "trait",
+            "type_item" => "type",
+            "const_item" => "const",
+            "static_item" => "static",
+            _ => return Ok(None),
+        };
+
+        // Exported when it carries a `pub` visibility modifier
+        let exported = node.child(0).map(|n| n.kind() == "visibility_modifier").unwrap_or(false);
+        let visibility = if exported { "public" } else { "private" };
+        named_symbol(node, source, file_path, symbol_type, exported, Some(visibility))
+    }
+
+    fn import_kinds(&self) -> &[&str] {
+        &["use_declaration", "extern_crate_declaration"]
+    }
+
+    fn call_kinds(&self) -> &[(&str, &str)] {
+        &[("call_expression", "function"), ("macro_invocation", "macro")]
+    }
+
+    fn type_kinds(&self) -> &[&str] {
+        &["type_identifier"]
+    }
+
+    fn supertypes(&self, node: &Node, source: &str) -> Vec<String> {
+        match node.kind() {
+            "trait_item" => heritage(node, source),
+            "impl_item" => node
+                .child_by_field_name("trait")
+                .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                .map(|text| vec![text.to_string()])
+                .unwrap_or_default(),
+            _ => vec![],
+        }
+    }
+
+    fn path_separator(&self) -> &str {
+        "::"
+    }
+}
+
+/// The type an `impl` block is for, without references, paths or generic
+/// arguments: `Parser` in `impl<'a> Display for &'a crate::Parser<'a>`.
+#[cfg(feature = "rust")]
+fn rust_type_name<'a>(node: Node, source: &'a str) -> Option<&'a str> {
+    let mut node = node;
+    while !node.kind().ends_with("identifier") && node.kind() != "primitive_type" {
+        node = node.child_by_field_name("type").or_else(|| node.child_by_field_name("name"))?;
+    }
+    node.utf8_text(source.as_bytes()).ok()
+}
+
+/// JavaScript and both TypeScript dialects, which share declaration kinds.
+#[cfg(any(feature = "javascript", feature = "typescript"))]
+struct JavaScript {
+    name: &'static str,
+    grammar: fn() -> Language,
+}
+
+#[cfg(any(feature = "javascript", feature = "typescript"))]
+impl LanguageExtractor for JavaScript {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn grammar(&self) -> Language {
+        (self.grammar)()
+    }
+
+    fn extract_symbol(&self, node: &Node, source: &str, file_path: &str) -> Result<Option<CodeSymbol>> {
+        let symbol_type = match node.kind() {
+            "function_declaration" | "function" => "function",
+            "method_definition" => "method",
+            "class_declaration" => "class",
+            "variable_declaration" => "variable",
+            // TypeScript only
+            "interface_declaration" => "interface",
+            "method_signature" => "method",
+            _ => return Ok(None),
+        };
+        let exported = js_exported(node, source);
+        let visibility = if exported { "public" } else { "private" };
+        named_symbol(node, source, file_path, symbol_type, exported, Some(visibility))
+    }
+
+    fn import_kinds(&self) -> &[&str] {
+        &["import_statement"]
+    }
+
+    fn call_kinds(&self) -> &[(&str, &str)] {
+        &[("call_expression", "function"), ("new_expression", "constructor")]
+    }
+
+    fn type_kinds(&self) -> &[&str] {
+        &["type_identifier"]
+    }
+
+    fn supertypes(&self, node: &Node, source: &str) -> Vec<String> {
+        match node.kind() {
+            "class_declaration" | "interface_declaration" => heritage(node, source),
+            _ => vec![],
+        }
+    }
+}
+
+/// Whether a declaration is exported: it sits in an `export` statement, or
+/// it is a member of an exported class or interface that is not `#private`,
+/// `private` or `protected`.
+#[cfg(any(feature = "javascript", feature = "typescript"))]
+fn js_exported(node: &Node, source: &str) -> bool {
+    let mut declaration = *node;
+    if matches!(node.kind(), "method_definition" | "method_signature") {
+        if node.child_by_field_name("name").is_some_and(|name| name.kind() == "private_property_identifier") {
+            return false;
+        }
+        let mut cursor = node.walk();
+        let hidden = node.children(&mut cursor).any(|child| {
+            child.kind() == "accessibility_modifier"
+                && matches!(child.utf8_text(source.as_bytes()), Ok("private" | "protected"))
+        });
+        if hidden {
+            return false;
+        }
+        let mut current = node.parent();
+        while let Some(ancestor) = current {
+            if matches!(ancestor.kind(), "class_declaration" | "abstract_class_declaration" | "interface_declaration") {
+                break;
+            }
+            current = ancestor.parent();
+        }
+        let Some(owner) = current else {
+            return false;
+        };
+        declaration = owner;
+    }
+    declaration.parent().is_some_and(|parent| parent.kind() == "export_statement")
+}
+
+#[cfg(feature = "go")]
+struct Go;
+
+#[cfg(feature = "go")]
+impl LanguageExtractor for Go {
+    fn name(&self) -> &str {
+        "go"
+    }
+
+    fn grammar(&self) -> Language {
+        tree_sitter_go::language()
+    }
+
+    fn extract_symbol(&self, node: &Node, source: &str, file_path: &str) -> Result<Option<CodeSymbol>> {
+        let symbol_type = match node.kind() {
+            "function_declaration" => "function",
+            // Methods of a type, and those an interface requires
+            "method_declaration" | "method_elem" | "method_spec" => "method",
+            "type_spec" | "type_alias" => match node.child_by_field_name("type").map(|n| n.kind()) {
+                Some("struct_type") => "struct",
+                Some("interface_type") => "interface",
+                _ => "type",
+            },
+            _ => return Ok(None),
+        };
+        let Some(mut symbol) = named_symbol(node, source, file_path, symbol_type, false, None)? else {
+            return Ok(None);
+        };
+        symbol.exported = symbol.symbol_name.chars().next().is_some_and(char::is_uppercase);
+        Ok(Some(symbol))
+    }
+
+    fn import_kinds(&self) -> &[&str] {
+        &["import_spec"]
+    }
+
+    fn call_kinds(&self) -> &[(&str, &str)] {
+        &[("call_expression", "function")]
+    }
+
+    fn type_kinds(&self) -> &[&str] {
+        &["type_identifier"]
+    }
+
+    /// Interfaces embedded in an interface. Go types satisfy interfaces
+    /// structurally, so types have none.
+    fn supertypes(&self, node: &Node, source: &str) -> Vec<String> {
+        let Some(interface) = node.child_by_field_name("type").filter(|n| n.kind() == "interface_type") else {
+            return vec![];
+        };
+        let mut cursor = interface.walk();
+        interface
+            .named_children(&mut cursor)
+            // A lone type names an embedded interface; unions are constraints
+            .filter(|element| matches!(element.kind(), "type_elem" | "constraint_elem") && element.named_child_count() == 1)
+            .filter_map(|element| element.named_child(0))
+            .filter(|embedded| matches!(embedded.kind(), "type_identifier" | "qualified_type" | "generic_type"))
+            .filter_map(|embedded| embedded.utf8_text(source.as_bytes()).ok())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[cfg(feature = "python")]
+struct Python;
+
+#[cfg(feature = "python")]
+impl LanguageExtractor for Python {
+    fn name(&self) -> &str {
+        "python"
+    }
+
+    fn grammar(&self) -> Language {
+        tree_sitter_python::language()
+    }
+
+    fn extract_symbol(&self, node: &Node, source: &str, file_path: &str) -> Result<Option<CodeSymbol>> {
+        let symbol_type = match node.kind() {
+            "function_definition" => "function",
+            "class_definition" => "class",
+            _ => return Ok(None),
+        };
+        let Some(mut symbol) = named_symbol(node, source, file_path, symbol_type, false, None)? else {
+            return Ok(None);
+        };
+        // Public by convention unless `_private`; dunder methods are public,
+        // functions local to another function are not
+        let name = symbol.symbol_name.as_str();
+        let private = name.starts_with('_') && !(name.starts_with("__") && name.ends_with("__"));
+        let mut local = false;
+        let mut current = node.parent();
+        while let Some(ancestor) = current {
+            local |= ancestor.kind() == "function_definition";
+            current = ancestor.parent();
+        }
+        symbol.exported = !private && !local;
+        symbol.visibility = Some(if symbol.exported { "public" } else { "private" }.to_string());
+        Ok(Some(symbol))
+    }
+
+    fn import_kinds(&self) -> &[&str] {
+        &["import_statement", "import_from_statement", "future_import_statement"]
+    }
+
+    fn call_kinds(&self) -> &[(&str, &str)] {
+        &[("call", "function")]
+    }
+
+    fn supertypes(&self, node: &Node, source: &str) -> Vec<String> {
+        match node.kind() {
+            "class_definition" => heritage(node, source),
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(feature = "java")]
+struct Java;
+
+#[cfg(feature = "java")]
+impl LanguageExtractor for Java {
+    fn name(&self) -> &str {
+        "java"
+    }
+
+    fn grammar(&self) -> Language {
+        tree_sitter_java::language()
+    }
+
+    fn extract_symbol(&self, node: &Node, source: &str, file_path: &str) -> Result<Option<CodeSymbol>> {
+        let symbol_type = match node.kind() {
+            "class_declaration" => "class",
+            "interface_declaration" => "interface",
+            "method_declaration" => "method",
+            _ => return Ok(None),
+        };
+        // Java methods are typically public
+        named_symbol(node, source, file_path, symbol_type, true, Some("public"))
+    }
+
+    fn import_kinds(&self) -> &[&str] {
+        &["import_declaration"]
+    }
+
+    fn call_kinds(&self) -> &[(&str, &str)] {
+        &[("method_invocation", "name")]
+    }
+
+    fn type_kinds(&self) -> &[&str] {
+        &["type_identifier"]
+    }
+
+    fn supertypes(&self, node: &Node, source: &str) -> Vec<String> {
+        match node.kind() {
+            "class_declaration" | "interface_declaration" => heritage(node, source),
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(feature = "cpp")]
+struct Cpp;
+
+#[cfg(feature = "cpp")]
+impl LanguageExtractor for Cpp {
+    fn name(&self) -> &str {
+        "cpp"
+    }
+
+    fn grammar(&self) -> Language {
+        tree_sitter_cpp::language()
+    }
+
+    fn extract_symbol(&self, node: &Node, source: &str, file_path: &str) -> Result<Option<CodeSymbol>> {
+        let symbol_type = match node.kind() {
+            "function_definition" => "function",
+            "class_specifier" => "class",
+            "namespace_definition" => "namespace",
+            _ => return Ok(None),
+        };
+        named_symbol(node, source, file_path, symbol_type, false, None)
+    }
+
+    fn import_kinds(&self) -> &[&str] {
+        &["preproc_include", "using_declaration"]
+    }
+
+    fn call_kinds(&self) -> &[(&str, &str)] {
+        &[("call_expression", "function")]
+    }
+
+    fn type_kinds(&self) -> &[&str] {
+        &["type_identifier"]
+    }
+
+    fn supertypes(&self, node: &Node, source: &str) -> Vec<String> {
+        match node.kind() {
+            "class_specifier" => heritage(node, source),
+            _ => vec![],
+        }
+    }
+
+    fn path_separator(&self) -> &str {
+        "::"
+    }
+}
+
+#[cfg(feature = "css")]
+struct Css;
+
+#[cfg(feature = "css")]
+impl LanguageExtractor for Css {
+    fn name(&self) -> &str {
+        "css"
+    }
+
+    fn grammar(&self) -> Language {
+        tree_sitter_css::language()
+    }
+
+    fn extract_symbol(&self, node: &Node, source: &str, file_path: &str) -> Result<Option<CodeSymbol>> {
+        let (symbol_type, name_kind) = match node.kind() {
+            "rule_set" => ("rule", "selectors"),
+            "keyframes_statement" => ("keyframes", "keyframes_name"),
+            // Custom properties (`--brand: #c00`)
+            "declaration" => ("variable", "property_name"),
+            _ => return Ok(None),
+        };
+        let mut cursor = node.walk();
+        let Some(name_node) = node.named_children(&mut cursor).find(|child| child.kind() == name_kind) else {
+            return Ok(None);
+        };
+        let name = name_node.utf8_text(source.as_bytes())?.split_whitespace().collect::<Vec<_>>().join(" ");
+        if symbol_type == "variable" && !name.starts_with("--") {
+            return Ok(None);
+        }
+        Ok(Some(symbol_at(node, name, source, file_path, symbol_type, false, None)))
+    }
+}
+
+/// Markup declares no symbols of its own; `ParserService` extracts those of
+/// its script and style blocks with their languages' extractors.
+#[cfg(feature = "html")]
+struct Html;
+
+#[cfg(feature = "html")]
+impl LanguageExtractor for Html {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn grammar(&self) -> Language {
+        tree_sitter_html::language()
+    }
+
+    fn extract_symbol(&self, _node: &Node, _source: &str, _file_path: &str) -> Result<Option<CodeSymbol>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,19 @@
+// The wasm32 build has no filesystem: every read fails there, and callers
+// hand the parser source text instead (`ParserService::extract_from_source`).
+#[cfg(not(target_arch = "wasm32"))]
+pub use tokio::fs::{metadata, read};
+
+#[cfg(target_arch = "wasm32")]
+pub async fn metadata(_path: impl AsRef<std::path::Path>) -> std::io::Result<std::fs::Metadata> {
+    Err(no_filesystem())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn read(_path: impl AsRef<std::path::Path>) -> std::io::Result<Vec<u8>> {
+    Err(no_filesystem())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn no_filesystem() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Unsupported, "No filesystem in the wasm32 build")
+}
@@ -0,0 +1,51 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes read from the start of a file to look for a generated-code header.
+const HEAD_BYTES: u64 = 4096;
+/// Bytes read from the end of a file to look for a source map reference.
+const TAIL_BYTES: u64 = 512;
+/// Lines of the head a header may sit in, after license blocks and pragmas.
+const HEADER_LINES: usize = 40;
+
+/// Why a file counts as generated, if it does:
+///
+/// - a header line saying it is generated and not to be edited (Go's
+///   `// Code generated ... DO NOT EDIT.`, protoc, Thrift and most other
+///   generators),
+/// - an `@generated` tag,
+/// - a trailing `sourceMappingURL` comment, as bundlers and compilers emit.
+pub fn reason(head: &str, tail: &str) -> Option<&'static str> {
+    for line in head.lines().take(HEADER_LINES) {
+        let lower = line.to_lowercase();
+        if lower.contains("do not edit") && lower.contains("generated") {
+            return Some("generated header");
+        }
+        if line.contains("@generated") {
+            return Some("@generated tag");
+        }
+    }
+    if tail.contains("sourceMappingURL=") {
+        return Some("source map reference");
+    }
+    None
+}
+
+/// Why the file at `path` counts as generated (see [`reason`]); `None` when
+/// it does not or cannot be read.
+pub fn check(path: &Path) -> Option<&'static str> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+
+    let mut head = Vec::new();
+    file.by_ref().take(HEAD_BYTES).read_to_end(&mut head).ok()?;
+    let mut tail = Vec::new();
+    if len > HEAD_BYTES {
+        file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES).max(HEAD_BYTES))).ok()?;
+        file.take(TAIL_BYTES).read_to_end(&mut tail).ok()?;
+    } else {
+        tail.clone_from(&head);
+    }
+    reason(&String::from_utf8_lossy(&head), &String::from_utf8_lossy(&tail))
+}
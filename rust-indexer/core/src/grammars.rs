@@ -0,0 +1,120 @@
+use crate::extractor::LanguageExtractor;
+use crate::language;
+use crate::symbol::CodeSymbol;
+use anyhow::{bail, Context, Result};
+use libloading::{Library, Symbol};
+use std::path::Path;
+use std::sync::Arc;
+use tree_sitter::{Language, Node, LANGUAGE_VERSION, MIN_COMPATIBLE_LANGUAGE_VERSION};
+
+/// A grammar compiled to a shared library and loaded at runtime. Files in it
+/// are parsed, chunked and checked for syntax errors like any other, but it
+/// has no symbol extraction of its own.
+struct LoadedGrammar {
+    name: String,
+    language: Language,
+    // The grammar's tables live in the library, so it is kept loaded as long as the language is in use
+    _library: Library,
+}
+
+impl LanguageExtractor for LoadedGrammar {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn grammar(&self) -> Language {
+        self.language.clone()
+    }
+
+    fn extract_symbol(&self, _node: &Node, _source: &str, _file_path: &str) -> Result<Option<CodeSymbol>> {
+        Ok(None)
+    }
+}
+
+/// Grammars in the directory named by `GRAMMAR_DIR`, none when it is unset.
+pub fn from_env() -> Result<Vec<Arc<dyn LanguageExtractor>>> {
+    match std::env::var("GRAMMAR_DIR").ok().filter(|dir| !dir.is_empty()) {
+        Some(dir) => load_dir(Path::new(&dir)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Loads every grammar library (`.so`, `.dylib`, `.dll`) in `dir`. The
+/// language is named after the file: `libtree-sitter-foo.so`,
+/// `tree-sitter-foo.so` and `foo.so` all load `tree_sitter_foo()` as `foo`.
+/// A library that fails to load is logged and skipped; an unreadable
+/// directory is an error.
+pub fn load_dir(dir: &Path) -> Result<Vec<Arc<dyn LanguageExtractor>>> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read grammar directory {}", dir.display()))?;
+    let mut paths: Vec<_> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+    paths.sort();
+
+    let mut grammars: Vec<Arc<dyn LanguageExtractor>> = Vec::new();
+    for path in paths {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("so" | "dylib" | "dll") => {}
+            Some("wasm") => {
+                tracing::warn!("Skipping {}: WASM grammars are not supported, build it as a shared library", path.display());
+                continue;
+            }
+            _ => continue,
+        }
+        match load(&path) {
+            Ok(grammar) => {
+                tracing::info!("Loaded {} grammar from {}", grammar.name, path.display());
+                language::register(&grammar.name);
+                grammars.push(Arc::new(grammar));
+            }
+            Err(e) => tracing::warn!("Skipping grammar {}: {:#}", path.display(), e),
+        }
+    }
+    Ok(grammars)
+}
+
+fn load(path: &Path) -> Result<LoadedGrammar> {
+    let name = grammar_name(path).context("Cannot tell the language from the file name")?;
+    if language::SUPPORTED.contains(&name.as_str()) {
+        bail!("{} is built in", name);
+    }
+
+    // Loading runs the library's initialisers, so only operator-controlled
+    // directories are read; a tree-sitter grammar's entry point takes no
+    // arguments and returns its language
+    let entry_point = format!("tree_sitter_{}", name);
+    let library = unsafe { Library::new(path) }?;
+    let language = unsafe {
+        let entry: Symbol<unsafe extern "C" fn() -> Language> = library
+            .get(entry_point.as_bytes())
+            .with_context(|| format!("No {} function", entry_point))?;
+        entry()
+    };
+
+    let version = language.version();
+    if !(MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION).contains(&version) {
+        bail!(
+            "Grammar ABI version {} is not between {} and {}",
+            version,
+            MIN_COMPATIBLE_LANGUAGE_VERSION,
+            LANGUAGE_VERSION
+        );
+    }
+
+    Ok(LoadedGrammar {
+        name,
+        language,
+        _library: library,
+    })
+}
+
+fn grammar_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let stem = stem.strip_prefix("lib").unwrap_or(stem);
+    let stem = stem
+        .strip_prefix("tree-sitter-")
+        .or_else(|| stem.strip_prefix("tree_sitter_"))
+        .unwrap_or(stem);
+    let name = stem.to_lowercase().replace('-', "_");
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some(name)
+}
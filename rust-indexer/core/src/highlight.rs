@@ -0,0 +1,95 @@
+use crate::symbol::{ErrorRange, HighlightSpan};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use tree_sitter::{Language, Node, Point, Query, QueryCursor};
+
+/// Highlight queries shipped with each grammar crate. TypeScript's extend
+/// JavaScript's, as in the tree-sitter highlighter.
+fn query_source(language: &str) -> Option<String> {
+    let parts: Option<&[&str]> = match language {
+        #[cfg(feature = "rust")]
+        "rust" => Some(&[tree_sitter_rust::HIGHLIGHTS_QUERY]),
+        #[cfg(feature = "javascript")]
+        "javascript" => Some(&[tree_sitter_javascript::HIGHLIGHT_QUERY, tree_sitter_javascript::JSX_HIGHLIGHT_QUERY]),
+        #[cfg(feature = "typescript")]
+        "typescript" => Some(&[tree_sitter_typescript::HIGHLIGHTS_QUERY, tree_sitter_javascript::HIGHLIGHT_QUERY]),
+        #[cfg(feature = "typescript")]
+        "tsx" => Some(&[
+            tree_sitter_typescript::HIGHLIGHTS_QUERY,
+            tree_sitter_javascript::HIGHLIGHT_QUERY,
+            tree_sitter_javascript::JSX_HIGHLIGHT_QUERY,
+        ]),
+        #[cfg(feature = "go")]
+        "go" => Some(&[tree_sitter_go::HIGHLIGHTS_QUERY]),
+        #[cfg(feature = "python")]
+        "python" => Some(&[tree_sitter_python::HIGHLIGHTS_QUERY]),
+        #[cfg(feature = "java")]
+        "java" => Some(&[tree_sitter_java::HIGHLIGHTS_QUERY]),
+        #[cfg(feature = "cpp")]
+        "cpp" => Some(&[tree_sitter_cpp::HIGHLIGHT_QUERY]),
+        _ => None,
+    };
+    parts.map(|parts| parts.join("\n"))
+}
+
+/// Compiled highlight queries per language.
+pub struct Highlights {
+    queries: HashMap<String, Query>,
+}
+
+impl Highlights {
+    /// Compiles the query of every language in `languages`; a query that does
+    /// not compile against its grammar is logged and that language left
+    /// without highlighting.
+    pub fn new(languages: &HashMap<String, Language>) -> Self {
+        let mut queries = HashMap::new();
+        for (name, language) in languages {
+            let Some(source) = query_source(name) else {
+                continue;
+            };
+            match Query::new(language, &source) {
+                Ok(query) => {
+                    queries.insert(name.clone(), query);
+                }
+                Err(e) => tracing::warn!("Highlight query for {} does not compile: {}", name, e),
+            }
+        }
+        Self { queries }
+    }
+
+    /// Spans of `root` captured by `language`'s query, optionally only those
+    /// within `rows` (zero-based, end exclusive), in source order. Where
+    /// several patterns capture the same node, the first one wins, so spans
+    /// never repeat; they may nest (an escape inside a string).
+    pub fn spans(&self, language: &str, root: Node, source: &str, rows: Option<Range<usize>>) -> Option<Vec<HighlightSpan>> {
+        let query = self.queries.get(language)?;
+        let mut cursor = QueryCursor::new();
+        if let Some(rows) = rows {
+            cursor.set_point_range(Point::new(rows.start, 0)..Point::new(rows.end, 0));
+        }
+
+        let names = query.capture_names();
+        let mut seen = HashSet::new();
+        let mut spans = Vec::new();
+        for (found, index) in cursor.captures(query, root, source.as_bytes()) {
+            let capture = found.captures[index];
+            let node = capture.node;
+            if !seen.insert(node.byte_range()) {
+                continue;
+            }
+            spans.push(HighlightSpan {
+                scope: names[capture.index as usize].to_string(),
+                start_byte: node.start_byte(),
+                end_byte: node.end_byte(),
+                range: ErrorRange {
+                    line_start: node.start_position().row as i32 + 1,
+                    line_end: node.end_position().row as i32 + 1,
+                    column_start: node.start_position().column,
+                    column_end: node.end_position().column,
+                },
+            });
+        }
+        spans.sort_by_key(|span| (span.start_byte, std::cmp::Reverse(span.end_byte)));
+        Some(spans)
+    }
+}
@@ -0,0 +1,245 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Bytes read from an extensionless file to look for a shebang or modeline.
+const SNIFF_BYTES: usize = 1024;
+/// Modelines are only honoured in the first few lines.
+const MODELINE_LINES: usize = 5;
+
+/// Languages we have a grammar for, as selected by the crate's features.
+pub const SUPPORTED: &[&str] = &[
+    #[cfg(feature = "rust")]
+    "rust",
+    #[cfg(feature = "javascript")]
+    "javascript",
+    #[cfg(feature = "typescript")]
+    "typescript",
+    #[cfg(feature = "typescript")]
+    "tsx",
+    #[cfg(feature = "go")]
+    "go",
+    #[cfg(feature = "python")]
+    "python",
+    #[cfg(feature = "java")]
+    "java",
+    #[cfg(feature = "cpp")]
+    "cpp",
+    #[cfg(feature = "css")]
+    "css",
+    #[cfg(feature = "html")]
+    "html",
+];
+
+/// Languages whose grammar was loaded at runtime, in addition to [`SUPPORTED`].
+static LOADED: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Records a language whose grammar was loaded at runtime, so extensions
+/// can be mapped to it.
+pub fn register(language: &str) {
+    let mut loaded = LOADED.write().unwrap();
+    if !loaded.iter().any(|name| name == language) {
+        loaded.push(language.to_string());
+    }
+}
+
+/// Whether there is a grammar for `language`, built in or loaded.
+pub fn is_supported(language: &str) -> bool {
+    SUPPORTED.contains(&language) || LOADED.read().unwrap().iter().any(|name| name == language)
+}
+
+/// Built-in extensions per language, lowercase and without the dot.
+const EXTENSION_TABLE: &[(&str, &[&str])] = &[
+    ("rust", &["rs"]),
+    ("javascript", &["js", "jsx", "mjs", "cjs"]),
+    ("typescript", &["ts", "mts", "cts"]),
+    ("tsx", &["tsx"]),
+    ("go", &["go"]),
+    ("python", &["py", "pyi", "pyw"]),
+    ("java", &["java"]),
+    ("cpp", &["cpp", "cc", "cxx", "c++", "c", "h", "hpp", "hh", "hxx", "h++", "inl", "ipp", "tpp"]),
+    ("css", &["css"]),
+    ("html", &["html", "htm", "xhtml", "vue", "svelte", "hbs", "handlebars", "ejs", "njk", "gohtml"]),
+];
+
+/// Well-known extensionless file names with a grammar we have. Build files
+/// without one (Makefile, Dockerfile, Rakefile, ...) stay unsupported and
+/// are chunked as plain text.
+const FILENAME_TABLE: &[(&str, &str)] = &[
+    ("BUILD", "python"),
+    ("BUILD.bazel", "python"),
+    ("WORKSPACE", "python"),
+    ("WORKSPACE.bazel", "python"),
+    ("SConstruct", "python"),
+    ("SConscript", "python"),
+    ("Snakefile", "python"),
+    ("Tiltfile", "python"),
+    ("Jakefile", "javascript"),
+];
+
+/// Language for a lowercase extension: the `configured` mapping (lowercase
+/// extension without the dot to a supported language) if there is one,
+/// otherwise the built-in table. Templates (`.gohtml`, `.hbs`, ...) are
+/// parsed as HTML for their script and style blocks.
+pub fn for_extension(ext: &str, configured: &HashMap<String, String>) -> Option<String> {
+    if let Some(language) = configured.get(ext) {
+        return Some(language.clone());
+    }
+    EXTENSION_TABLE
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&ext))
+        .map(|(language, _)| language.to_string())
+}
+
+/// Extensions that resolve to `language`, `configured` ones included.
+pub fn extensions(language: &str, configured: &HashMap<String, String>) -> Vec<String> {
+    let builtin = EXTENSION_TABLE
+        .iter()
+        .filter(|(name, _)| *name == language)
+        .flat_map(|(_, extensions)| extensions.iter().map(|ext| ext.to_string()));
+    let mut extensions: Vec<String> = builtin
+        .chain(configured.keys().cloned())
+        .filter(|ext| for_extension(ext, configured).as_deref() == Some(language))
+        .collect();
+    extensions.sort();
+    extensions.dedup();
+    extensions
+}
+
+pub fn for_filename(name: &str) -> Option<&'static str> {
+    FILENAME_TABLE.iter().find(|(file, _)| *file == name).map(|(_, language)| *language)
+}
+
+/// Extensionless file names recognised as `language`.
+pub fn filenames(language: &str) -> Vec<&'static str> {
+    FILENAME_TABLE.iter().filter(|(_, name)| *name == language).map(|(file, _)| *file).collect()
+}
+
+/// Language of an extensionless file from its shebang line or an
+/// emacs/vim modeline near the top.
+pub fn sniff(path: &Path) -> Option<&'static str> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    std::fs::File::open(path).ok()?.take(SNIFF_BYTES as u64).read_to_end(&mut head).ok()?;
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.lines();
+    let first = lines.next()?;
+    if let Some(interpreter) = first.strip_prefix("#!") {
+        return from_shebang(interpreter);
+    }
+    std::iter::once(first).chain(lines).take(MODELINE_LINES).find_map(from_modeline)
+}
+
+/// `#!/usr/bin/env python3`, `#!/usr/bin/node`, `#!/usr/bin/env -S deno run`, ...
+fn from_shebang(interpreter: &str) -> Option<&'static str> {
+    let mut words = interpreter.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    for_name(name)
+}
+
+/// `-*- mode: python -*-`, `-*- python -*-`, `vim: set ft=python:` or `vi: filetype=go`.
+fn from_modeline(line: &str) -> Option<&'static str> {
+    if let Some(start) = line.find("-*-") {
+        let rest = &line[start + 3..];
+        let body = &rest[..rest.find("-*-")?];
+        let mode = body
+            .split(';')
+            .find_map(|part| part.trim().strip_prefix("mode:"))
+            .unwrap_or(body);
+        return for_name(&mode.trim().to_lowercase());
+    }
+    let (_, settings) = line.split_once("vim:").or_else(|| line.split_once("vi:"))?;
+    settings
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|setting| setting.strip_prefix("ft=").or_else(|| setting.strip_prefix("filetype=")))
+        .and_then(|name| for_name(&name.to_lowercase()))
+}
+
+/// Interpreter, emacs mode or vim filetype name to one of our languages.
+fn for_name(name: &str) -> Option<&'static str> {
+    match name {
+        "python" | "pypy" => Some("python"),
+        "node" | "nodejs" | "javascript" | "js" | "js2" => Some("javascript"),
+        "deno" | "ts-node" | "tsx" | "bun" | "typescript" => Some("typescript"),
+        "rust" | "rust-script" => Some("rust"),
+        "go" => Some("go"),
+        "java" => Some("java"),
+        "c" | "c++" | "cpp" => Some("cpp"),
+        "css" => Some("css"),
+        "html" | "xhtml" | "vue" | "svelte" => Some("html"),
+        _ => None,
+    }
+}
+
+/// Which extraction features are implemented for a language.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LanguageFeatures {
+    pub symbols: bool,
+    /// Names each symbol calls or uses as a type (`dependencies`, `/extract-deps`).
+    pub dependencies: bool,
+    /// Types each class-like symbol extends or implements (`supertypes`, `/hierarchy`).
+    pub supertypes: bool,
+    /// Top-level import, use and include statements.
+    pub imports: bool,
+    pub docstrings: bool,
+    pub metrics: bool,
+    /// Whether `exported`/`visibility` reflect the source rather than a default.
+    pub visibility: bool,
+}
+
+/// A supported language, as listed by `GET /languages`.
+#[derive(Debug, Serialize)]
+pub struct LanguageInfo {
+    pub name: String,
+    pub extensions: Vec<String>,
+    pub filenames: Vec<&'static str>,
+    /// `symbol_type` values extraction produces for it.
+    pub symbol_kinds: &'static [&'static str],
+    pub features: LanguageFeatures,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguagesResponse {
+    pub languages: Vec<LanguageInfo>,
+    pub success: bool,
+}
+
+/// What the API reports for `language`, with extensions `configured` for it.
+pub fn info(language: &str, configured: &HashMap<String, String>) -> LanguageInfo {
+    let symbol_kinds: &'static [&'static str] = match language {
+        "rust" => &["function", "impl", "struct", "enum", "trait", "type", "const", "static"],
+        "javascript" => &["function", "method", "class", "variable"],
+        "typescript" | "tsx" => &["function", "method", "class", "interface", "variable"],
+        "go" => &["function", "method", "struct", "interface", "type"],
+        "python" => &["function", "class"],
+        "java" => &["class", "interface", "method"],
+        "cpp" => &["function", "class", "namespace"],
+        "css" => &["rule", "keyframes", "variable"],
+        // From script and style blocks
+        "html" => &["function", "method", "class", "variable", "rule", "keyframes"],
+        _ => &[],
+    };
+    let known = !symbol_kinds.is_empty();
+    // Stylesheets and markup only have symbols extracted
+    let code = known && !matches!(language, "css" | "html");
+    LanguageInfo {
+        name: language.to_string(),
+        extensions: extensions(language, configured),
+        filenames: filenames(language),
+        symbol_kinds,
+        features: LanguageFeatures {
+            symbols: known,
+            dependencies: code,
+            supertypes: code,
+            imports: code,
+            docstrings: code,
+            metrics: code,
+            visibility: matches!(language, "rust" | "go" | "javascript" | "typescript" | "tsx" | "python"),
+        },
+    }
+}
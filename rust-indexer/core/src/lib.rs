@@ -0,0 +1,29 @@
+pub mod cache;
+pub mod chunker;
+pub mod embedded;
+pub mod encoding;
+pub mod error;
+pub mod extractor;
+mod fs;
+pub mod generated;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod grammars;
+pub mod highlight;
+pub mod language;
+pub mod literals;
+pub mod metrics;
+pub mod owners;
+pub mod parser;
+pub mod query_pack;
+pub mod repo;
+pub mod routes;
+pub mod rules;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod shared_cache;
+pub mod stats;
+pub mod symbol;
+pub mod timing;
+pub mod tokens;
+
+pub use extractor::LanguageExtractor;
+pub use parser::{ExtractOptions, ParserService};
@@ -0,0 +1,285 @@
+use crate::symbol::CodeSymbol;
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+/// Node kinds of string literals across the built-in grammars.
+pub const STRING_KINDS: &[&str] = &[
+    "string",
+    "string_literal",
+    "raw_string_literal",
+    "interpreted_string_literal",
+    "template_string",
+    "text_block",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiteralKind {
+    String,
+    Constant,
+}
+
+/// A string literal, or a named constant and its value.
+#[derive(Debug, Serialize)]
+pub struct Literal {
+    pub kind: LiteralKind,
+    /// The constant's name; absent for string literals.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// A string's text between its quotes with escapes as written, or a
+    /// constant's value expression as written.
+    pub value: String,
+    pub file_path: String,
+    /// One-based line and zero-based byte column of the literal or the
+    /// constant's name.
+    pub line: i32,
+    pub column: usize,
+    /// Qualified name and id of the innermost symbol the literal is in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_id: Option<String>,
+}
+
+/// Which literals an extraction keeps.
+#[derive(Debug, Clone, Default)]
+pub struct LiteralFilter {
+    /// Only strings or only constants; both when `None`.
+    pub kind: Option<LiteralKind>,
+    /// Matched case-insensitively against values and constant names. With a
+    /// `*` (any run of characters) the pattern must cover the whole text,
+    /// without one containing it is enough.
+    pub pattern: Option<String>,
+}
+
+impl LiteralFilter {
+    pub fn matches(&self, literal: &Literal) -> bool {
+        let kind = self.kind.is_none() || self.kind == Some(literal.kind);
+        let pattern = match &self.pattern {
+            Some(pattern) => {
+                matches_pattern(pattern, &literal.value)
+                    || literal.name.as_deref().is_some_and(|name| matches_pattern(pattern, name))
+            }
+            None => true,
+        };
+        kind && pattern
+    }
+}
+
+/// String literals and named constants under `root`, in source order, each
+/// attributed to the innermost of `symbols` enclosing it.
+///
+/// Strings standing alone as statements (docstrings, `"use strict"`) and
+/// module paths of imports are skipped. Constants are Rust `const` and
+/// `static` items, Go `const` specs, Java `static final` fields and interface
+/// constants, C/C++ `#define`s, and, where any value is allowed, JavaScript
+/// `const` declarations and upper-case Python module or class attributes
+/// initialized with a literal.
+pub fn collect(
+    root: Node,
+    source: &str,
+    file_path: &str,
+    language: &str,
+    symbols: &[CodeSymbol],
+    filter: &LiteralFilter,
+) -> Vec<Literal> {
+    let mut literals = Vec::new();
+    visit(root, source, file_path, language, &mut literals);
+    literals.retain(|literal| filter.matches(literal));
+    for literal in &mut literals {
+        if let Some(symbol) = enclosing(symbols, literal) {
+            literal.symbol = Some(symbol.qualified_name.clone().unwrap_or_else(|| symbol.symbol_name.clone()));
+            literal.symbol_id = Some(symbol.id.clone());
+        }
+    }
+    literals
+}
+
+fn visit(node: Node, source: &str, file_path: &str, language: &str, literals: &mut Vec<Literal>) {
+    for (name, value) in constants(node, source, language) {
+        literals.push(Literal {
+            kind: LiteralKind::Constant,
+            name: Some(text(&name, source).to_string()),
+            value: text(&value, source).trim().to_string(),
+            file_path: file_path.to_string(),
+            line: name.start_position().row as i32 + 1,
+            column: name.start_position().column,
+            symbol: None,
+            symbol_id: None,
+        });
+    }
+    if STRING_KINDS.contains(&node.kind()) && !is_statement(&node) && !in_import(&node) {
+        literals.push(Literal {
+            kind: LiteralKind::String,
+            name: None,
+            value: unquote(text(&node, source), language).to_string(),
+            file_path: file_path.to_string(),
+            line: node.start_position().row as i32 + 1,
+            column: node.start_position().column,
+            symbol: None,
+            symbol_id: None,
+        });
+    }
+    // Interpolations can hold strings of their own
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        visit(child, source, file_path, language, literals);
+    }
+}
+
+/// Name and value nodes of the constants `node` declares.
+fn constants<'a>(node: Node<'a>, source: &str, language: &str) -> Vec<(Node<'a>, Node<'a>)> {
+    match (language, node.kind()) {
+        ("rust", "const_item" | "static_item") | ("cpp", "preproc_def") => named_value(&node, "name", "value"),
+        ("go", "const_spec") => {
+            // `const a, b = 1, 2`; specs repeating the previous value (iota) have none
+            let Some(values) = node.child_by_field_name("value") else {
+                return vec![];
+            };
+            let mut cursor = node.walk();
+            let names: Vec<Node> = node.children_by_field_name("name", &mut cursor).filter(|name| name.is_named()).collect();
+            let mut cursor = values.walk();
+            let values: Vec<Node> = values.named_children(&mut cursor).collect();
+            names.into_iter().zip(values).collect()
+        }
+        ("javascript" | "typescript" | "tsx", "lexical_declaration")
+            if node.child(0).is_some_and(|keyword| keyword.kind() == "const") =>
+        {
+            declarators(&node)
+                .filter(|(name, value)| name.kind() == "identifier" && is_literal_value(value))
+                .collect()
+        }
+        ("java", "field_declaration") if has_modifiers(&node, &["static", "final"]) => declarators(&node).collect(),
+        ("java", "constant_declaration") => declarators(&node).collect(),
+        ("python", "assignment") if is_python_attribute(&node) => named_value(&node, "left", "right")
+            .into_iter()
+            .filter(|(name, value)| name.kind() == "identifier" && is_upper_case(text(name, source)) && is_literal_value(value))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn named_value<'a>(node: &Node<'a>, name: &str, value: &str) -> Vec<(Node<'a>, Node<'a>)> {
+    match (node.child_by_field_name(name), node.child_by_field_name(value)) {
+        (Some(name), Some(value)) => vec![(name, value)],
+        _ => vec![],
+    }
+}
+
+/// Name and value of each initialized `variable_declarator` of a declaration.
+fn declarators<'a>(node: &Node<'a>) -> impl Iterator<Item = (Node<'a>, Node<'a>)> {
+    let mut cursor = node.walk();
+    let declarators: Vec<Node<'a>> = node
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "variable_declarator")
+        .collect();
+    declarators.into_iter().flat_map(|declarator| named_value(&declarator, "name", "value"))
+}
+
+fn has_modifiers(node: &Node, required: &[&str]) -> bool {
+    let mut cursor = node.walk();
+    let Some(modifiers) = node.named_children(&mut cursor).find(|child| child.kind() == "modifiers") else {
+        return false;
+    };
+    let mut cursor = modifiers.walk();
+    let present: Vec<&str> = modifiers.children(&mut cursor).map(|modifier| modifier.kind()).collect();
+    required.iter().all(|modifier| present.contains(modifier))
+}
+
+/// An assignment statement directly in a module or class body.
+fn is_python_attribute(node: &Node) -> bool {
+    let Some(scope) = node.parent().filter(|parent| parent.kind() == "expression_statement").and_then(|statement| statement.parent()) else {
+        return false;
+    };
+    scope.kind() == "module"
+        || (scope.kind() == "block" && scope.parent().is_some_and(|parent| parent.kind() == "class_definition"))
+}
+
+fn is_literal_value(node: &Node) -> bool {
+    STRING_KINDS.contains(&node.kind())
+        || matches!(node.kind(), "concatenated_string" | "number" | "integer" | "float" | "true" | "false")
+}
+
+fn is_upper_case(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_uppercase()) && !name.chars().any(|c| c.is_ascii_lowercase())
+}
+
+/// A string that is a statement of its own: a docstring or a directive.
+fn is_statement(node: &Node) -> bool {
+    node.parent()
+        .is_some_and(|parent| parent.kind() == "expression_statement" && parent.named_child_count() == 1)
+}
+
+/// Module paths in imports, includes and re-exports.
+fn in_import(node: &Node) -> bool {
+    if let Some(parent) = node.parent() {
+        if parent.kind() == "export_statement" && parent.child_by_field_name("source") == Some(*node) {
+            return true;
+        }
+    }
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        if ancestor.kind().contains("import") || ancestor.kind() == "preproc_include" {
+            return true;
+        }
+        current = ancestor.parent();
+    }
+    false
+}
+
+/// A string literal's text between its delimiters.
+pub fn unquote<'a>(literal: &'a str, language: &str) -> &'a str {
+    // Prefixes: r, b, f, u8, L, ...
+    let prefixed = literal.trim_start_matches(|c: char| c.is_ascii_alphanumeric());
+    let mut body = prefixed;
+    // Rust raw strings: r#"..."#
+    while let Some(inner) = body.strip_prefix('#').and_then(|inner| inner.strip_suffix('#')) {
+        body = inner;
+    }
+    for quote in ["\"\"\"", "'''", "\"", "'", "`"] {
+        let Some(inner) = body.strip_prefix(quote).and_then(|inner| inner.strip_suffix(quote)) else {
+            continue;
+        };
+        // C++ raw strings: R"delimiter(...)delimiter"
+        let raw = language == "cpp" && literal[..literal.len() - prefixed.len()].ends_with('R');
+        if let (true, Some(open), Some(close)) = (raw, inner.find('('), inner.rfind(')')) {
+            if open < close {
+                return &inner[open + 1..close];
+            }
+        }
+        return inner;
+    }
+    body
+}
+
+/// The innermost symbol around a literal, other than the constant it names.
+fn enclosing<'a>(symbols: &'a [CodeSymbol], literal: &Literal) -> Option<&'a CodeSymbol> {
+    symbols
+        .iter()
+        .filter(|symbol| symbol.file_path == literal.file_path)
+        .filter(|symbol| symbol.line_start <= literal.line && literal.line <= symbol.line_end)
+        .filter(|symbol| !(literal.name.as_deref() == Some(symbol.symbol_name.as_str()) && symbol.line_start == literal.line))
+        .min_by_key(|symbol| symbol.line_end - symbol.line_start)
+}
+
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let (pattern, text) = (pattern.to_lowercase(), text.to_lowercase());
+    if !pattern.contains('*') {
+        return text.contains(&pattern);
+    }
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let Some(mut rest) = text.strip_prefix(parts[0]) else {
+        return false;
+    };
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(parts[parts.len() - 1])
+}
+
+fn text<'a>(node: &Node, source: &'a str) -> &'a str {
+    &source[node.byte_range()]
+}
@@ -0,0 +1,493 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SymbolMetrics {
+    pub cyclomatic_complexity: u32,
+    pub statement_count: u32,
+    pub line_count: u32,
+    pub parameter_count: u32,
+    pub max_nesting_depth: u32,
+    pub code_lines: u32,
+    pub comment_lines: u32,
+    pub comment_ratio: f64,
+    pub documented: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halstead: Option<HalsteadMetrics>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HalsteadMetrics {
+    pub distinct_operators: u32,
+    pub distinct_operands: u32,
+    pub total_operators: u32,
+    pub total_operands: u32,
+    pub vocabulary: u32,
+    pub length: u32,
+    pub volume: f64,
+    pub difficulty: f64,
+    pub effort: f64,
+}
+
+/// Metric used to rank symbols in repository-level reports.
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricKind {
+    #[default]
+    CyclomaticComplexity,
+    StatementCount,
+    LineCount,
+    ParameterCount,
+    MaxNestingDepth,
+}
+
+impl MetricKind {
+    pub fn value(&self, metrics: &SymbolMetrics) -> u32 {
+        match self {
+            MetricKind::CyclomaticComplexity => metrics.cyclomatic_complexity,
+            MetricKind::StatementCount => metrics.statement_count,
+            MetricKind::LineCount => metrics.line_count,
+            MetricKind::ParameterCount => metrics.parameter_count,
+            MetricKind::MaxNestingDepth => metrics.max_nesting_depth,
+        }
+    }
+}
+
+/// Physical line classification for a span of source.
+#[derive(Debug, Serialize, Clone, Copy, Default)]
+pub struct LineCounts {
+    pub code_lines: u32,
+    pub comment_lines: u32,
+    pub blank_lines: u32,
+}
+
+impl LineCounts {
+    /// Comment-to-code ratio; zero when the span has no code.
+    pub fn comment_ratio(&self) -> f64 {
+        if self.code_lines == 0 {
+            0.0
+        } else {
+            self.comment_lines as f64 / self.code_lines as f64
+        }
+    }
+}
+
+/// Node kinds that open a new level of control-flow nesting.
+const NESTING_KINDS: &[&str] = &[
+    "if_expression",
+    "if_statement",
+    "while_expression",
+    "while_statement",
+    "for_expression",
+    "for_statement",
+    "for_in_statement",
+    "enhanced_for_statement",
+    "for_range_loop",
+    "loop_expression",
+    "do_statement",
+    "match_expression",
+    "match_statement",
+    "switch_statement",
+    "switch_expression",
+    "expression_switch_statement",
+    "type_switch_statement",
+    "select_statement",
+    "try_statement",
+    "with_statement",
+];
+
+/// Block nodes whose kind ends in `_statement` but only group other statements.
+const BLOCK_KINDS: &[&str] = &["compound_statement", "block_statement"];
+
+/// Nodes treated as a single operand even though the grammar gives them children.
+const COMPOSITE_OPERAND_KINDS: &[&str] = &[
+    "string",
+    "string_literal",
+    "raw_string_literal",
+    "interpreted_string_literal",
+    "template_string",
+    "char_literal",
+    "character_literal",
+    "concatenated_string",
+];
+
+/// Computes metrics for the subtree rooted at a symbol's node.
+pub fn compute(node: &tree_sitter::Node, source: &str, language: &str) -> SymbolMetrics {
+    let lines = line_counts(node, source);
+    SymbolMetrics {
+        cyclomatic_complexity: cyclomatic_complexity(node, source, language),
+        statement_count: statement_count(node),
+        line_count: (node.end_position().row - node.start_position().row) as u32 + 1,
+        parameter_count: parameter_count(node, language),
+        max_nesting_depth: max_nesting_depth(node, 0),
+        code_lines: lines.code_lines,
+        comment_lines: lines.comment_lines,
+        comment_ratio: lines.comment_ratio(),
+        documented: has_doc_comment(node, source, language),
+        halstead: None,
+    }
+}
+
+/// McCabe complexity: one plus the number of decision points (branches,
+/// loops, non-default cases, catch clauses and short-circuit operators).
+pub fn cyclomatic_complexity(node: &tree_sitter::Node, source: &str, language: &str) -> u32 {
+    let mut complexity = 1;
+    let mut cursor = node.walk();
+    let mut stack = vec![*node];
+
+    while let Some(current) = stack.pop() {
+        if is_decision_point(&current, source, language) {
+            complexity += 1;
+        }
+        for child in current.children(&mut cursor) {
+            stack.push(child);
+        }
+    }
+
+    complexity
+}
+
+fn is_decision_point(node: &tree_sitter::Node, source: &str, language: &str) -> bool {
+    match language {
+        "rust" => match node.kind() {
+            "if_expression" | "while_expression" | "for_expression" | "loop_expression" => true,
+            "match_arm" => node
+                .child_by_field_name("pattern")
+                .and_then(|p| p.utf8_text(source.as_bytes()).ok())
+                .map(|p| p.trim() != "_")
+                .unwrap_or(true),
+            "binary_expression" => is_short_circuit(node, source),
+            _ => false,
+        },
+        "javascript" | "typescript" | "tsx" => match node.kind() {
+            "if_statement" | "for_statement" | "for_in_statement" | "while_statement"
+            | "do_statement" | "switch_case" | "catch_clause" | "ternary_expression" => true,
+            "binary_expression" => is_short_circuit(node, source),
+            _ => false,
+        },
+        "go" => match node.kind() {
+            "if_statement" | "for_statement" | "expression_case" | "type_case"
+            | "communication_case" => true,
+            "binary_expression" => is_short_circuit(node, source),
+            _ => false,
+        },
+        "python" => matches!(
+            node.kind(),
+            "if_statement"
+                | "elif_clause"
+                | "for_statement"
+                | "while_statement"
+                | "except_clause"
+                | "conditional_expression"
+                | "boolean_operator"
+                | "for_in_clause"
+                | "if_clause"
+                | "case_clause"
+        ),
+        "java" => match node.kind() {
+            "if_statement" | "for_statement" | "enhanced_for_statement" | "while_statement"
+            | "do_statement" | "catch_clause" | "ternary_expression" => true,
+            "switch_label" => node
+                .utf8_text(source.as_bytes())
+                .map(|t| !t.trim_start().starts_with("default"))
+                .unwrap_or(false),
+            "binary_expression" => is_short_circuit(node, source),
+            _ => false,
+        },
+        "cpp" => match node.kind() {
+            "if_statement" | "for_statement" | "for_range_loop" | "while_statement"
+            | "do_statement" | "catch_clause" | "conditional_expression" => true,
+            "case_statement" => node.child_by_field_name("value").is_some(),
+            "binary_expression" => is_short_circuit(node, source),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+pub fn statement_count(node: &tree_sitter::Node) -> u32 {
+    let mut count = 0;
+    let mut cursor = node.walk();
+    let mut stack = vec![*node];
+
+    while let Some(current) = stack.pop() {
+        if is_statement(current.kind()) {
+            count += 1;
+        }
+        for child in current.children(&mut cursor) {
+            stack.push(child);
+        }
+    }
+
+    count
+}
+
+fn is_statement(kind: &str) -> bool {
+    if BLOCK_KINDS.contains(&kind) {
+        return false;
+    }
+    kind.ends_with("_statement")
+        || matches!(
+            kind,
+            "let_declaration"
+                | "local_variable_declaration"
+                | "lexical_declaration"
+                | "variable_declaration"
+                | "short_var_declaration"
+                | "declaration"
+        )
+}
+
+pub fn parameter_count(node: &tree_sitter::Node, language: &str) -> u32 {
+    let Some(params) = parameter_list(node) else {
+        return 0;
+    };
+
+    let mut count = 0;
+    let mut cursor = params.walk();
+    for param in params.named_children(&mut cursor) {
+        if param.kind() == "comment" {
+            continue;
+        }
+        // Go allows `a, b int` in a single declaration
+        if language == "go" && param.kind() == "parameter_declaration" {
+            let mut inner = param.walk();
+            let names = param
+                .children_by_field_name("name", &mut inner)
+                .count() as u32;
+            count += names.max(1);
+        } else {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Source text of each declared parameter, in order.
+pub fn parameters(node: &tree_sitter::Node, source: &str) -> Vec<String> {
+    let Some(params) = parameter_list(node) else {
+        return Vec::new();
+    };
+
+    let mut cursor = params.walk();
+    params
+        .named_children(&mut cursor)
+        .filter(|param| param.kind() != "comment")
+        .filter_map(|param| param.utf8_text(source.as_bytes()).ok())
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+/// Finds the parameter list, following C++ declarators down to the function declarator.
+fn parameter_list<'a>(node: &tree_sitter::Node<'a>) -> Option<tree_sitter::Node<'a>> {
+    if let Some(params) = node.child_by_field_name("parameters") {
+        return Some(params);
+    }
+    node.child_by_field_name("declarator")
+        .and_then(|declarator| parameter_list(&declarator))
+}
+
+pub fn max_nesting_depth(node: &tree_sitter::Node, depth: u32) -> u32 {
+    let mut max_depth = depth;
+    let mut cursor = node.walk();
+
+    for child in node.children(&mut cursor) {
+        let child_depth = if NESTING_KINDS.contains(&child.kind()) && !is_else_if(&child) {
+            depth + 1
+        } else {
+            depth
+        };
+        max_depth = max_depth.max(max_nesting_depth(&child, child_depth));
+    }
+
+    max_depth
+}
+
+/// `else if` continues the parent branch rather than nesting inside it.
+fn is_else_if(node: &tree_sitter::Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    if parent.kind() == "else_clause" {
+        return true;
+    }
+    parent
+        .child_by_field_name("alternative")
+        .map(|alt| alt == *node)
+        .unwrap_or(false)
+}
+
+/// Classifies each line spanned by `node` as blank, comment-only or code.
+/// A line mixing code and a trailing comment counts as code.
+pub fn line_counts(node: &tree_sitter::Node, source: &str) -> LineCounts {
+    let mut comments = Vec::new();
+    let mut cursor = node.walk();
+    let mut stack = vec![*node];
+    while let Some(current) = stack.pop() {
+        if current.kind().contains("comment") {
+            comments.push(current.start_byte()..current.end_byte());
+            continue;
+        }
+        for child in current.children(&mut cursor) {
+            stack.push(child);
+        }
+    }
+
+    let start = source[..node.start_byte()].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let end = node.end_byte().min(source.len());
+
+    let span = &source[start..end];
+    let span = span.strip_suffix('\n').unwrap_or(span);
+
+    let mut counts = LineCounts::default();
+    let mut offset = start;
+    for line in span.split('\n') {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            counts.blank_lines += 1;
+        } else {
+            let lead = offset + (line.len() - line.trim_start().len());
+            let tail = offset + line.trim_end().len();
+            if comments.iter().any(|c| c.start <= lead && c.end >= tail) {
+                counts.comment_lines += 1;
+            } else {
+                counts.code_lines += 1;
+            }
+        }
+        offset += line.len() + 1;
+    }
+
+    counts
+}
+
+/// Whether a symbol carries documentation: a comment directly above it
+/// (skipping attributes/decorators), or a leading docstring in Python.
+pub fn has_doc_comment(node: &tree_sitter::Node, source: &str, language: &str) -> bool {
+    doc_comment(node, source, language).is_some()
+}
+
+/// The documentation attached to a symbol, as written in the source.
+pub fn doc_comment(node: &tree_sitter::Node, source: &str, language: &str) -> Option<String> {
+    if language == "python" {
+        let docstring = node
+            .child_by_field_name("body")
+            .and_then(|body| body.named_child(0))
+            .filter(|first| first.kind() == "expression_statement")
+            .and_then(|first| first.named_child(0))
+            .filter(|expr| expr.kind() == "string");
+        if let Some(docstring) = docstring {
+            return docstring.utf8_text(source.as_bytes()).ok().map(str::to_string);
+        }
+    }
+
+    // Exports and decorators wrap the declaration, so the comment sits above the wrapper
+    let mut target = *node;
+    while let Some(parent) = target.parent() {
+        if matches!(parent.kind(), "export_statement" | "decorated_definition") {
+            target = parent;
+        } else {
+            break;
+        }
+    }
+
+    let mut line = target.start_position().row;
+    let mut sibling = target.prev_sibling();
+    let mut comments = Vec::new();
+    while let Some(prev) = sibling {
+        if prev.end_position().row + 1 < line {
+            break;
+        }
+        if prev.kind().contains("comment") {
+            let text = prev.utf8_text(source.as_bytes()).unwrap_or("");
+            if language == "rust" && !(text.starts_with("///") || text.starts_with("/**")) {
+                break;
+            }
+            comments.push(text.trim_end());
+        } else if !comments.is_empty()
+            || !matches!(prev.kind(), "attribute_item" | "decorator" | "annotation" | "marker_annotation")
+        {
+            break;
+        }
+        line = prev.start_position().row;
+        sibling = prev.prev_sibling();
+    }
+
+    if comments.is_empty() {
+        return None;
+    }
+    comments.reverse();
+    Some(comments.join("\n"))
+}
+
+/// Halstead measures derived from the operator and operand tokens of the subtree.
+pub fn halstead(node: &tree_sitter::Node, source: &str) -> HalsteadMetrics {
+    let mut operators = HashSet::new();
+    let mut operands = HashSet::new();
+    let mut total_operators = 0u32;
+    let mut total_operands = 0u32;
+
+    let mut cursor = node.walk();
+    let mut stack = vec![*node];
+    while let Some(current) = stack.pop() {
+        let kind = current.kind();
+        if kind.contains("comment") {
+            continue;
+        }
+
+        if COMPOSITE_OPERAND_KINDS.contains(&kind) || (current.child_count() == 0 && is_operand(kind)) {
+            let text = current.utf8_text(source.as_bytes()).unwrap_or(kind);
+            operands.insert(text);
+            total_operands += 1;
+        } else if current.child_count() == 0 {
+            operators.insert(kind);
+            total_operators += 1;
+        } else {
+            for child in current.children(&mut cursor) {
+                stack.push(child);
+            }
+        }
+    }
+
+    let n1 = operators.len() as u32;
+    let n2 = operands.len() as u32;
+    let vocabulary = n1 + n2;
+    let length = total_operators + total_operands;
+    let volume = if vocabulary > 0 {
+        length as f64 * (vocabulary as f64).log2()
+    } else {
+        0.0
+    };
+    let difficulty = if n2 > 0 {
+        (n1 as f64 / 2.0) * (total_operands as f64 / n2 as f64)
+    } else {
+        0.0
+    };
+
+    HalsteadMetrics {
+        distinct_operators: n1,
+        distinct_operands: n2,
+        total_operators,
+        total_operands,
+        vocabulary,
+        length,
+        volume,
+        difficulty,
+        effort: difficulty * volume,
+    }
+}
+
+fn is_operand(kind: &str) -> bool {
+    kind.ends_with("identifier")
+        || kind.ends_with("literal")
+        || matches!(
+            kind,
+            "integer" | "float" | "number" | "true" | "false" | "null" | "nil" | "none" | "undefined" | "iota"
+        )
+}
+
+fn is_short_circuit(node: &tree_sitter::Node, source: &str) -> bool {
+    node.child_by_field_name("operator")
+        .and_then(|op| op.utf8_text(source.as_bytes()).ok())
+        .map(|op| matches!(op, "&&" | "||" | "??"))
+        .unwrap_or(false)
+}
@@ -0,0 +1,96 @@
+use crate::symbol::CodeSymbol;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Where GitHub looks for a CODEOWNERS file, in order.
+const LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+struct Rule {
+    pattern: String,
+    matcher: Gitignore,
+    owners: Vec<String>,
+}
+
+/// The CODEOWNERS rules of a repository.
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// Reads the CODEOWNERS file GitHub would use for the repository at
+    /// `root`; `None` when it has none.
+    pub fn load(root: &Path) -> std::io::Result<Option<Self>> {
+        for location in LOCATIONS {
+            match std::fs::read_to_string(root.join(location)) {
+                Ok(text) => return Ok(Some(Self::parse(&text))),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parses CODEOWNERS rules: a gitignore-style pattern followed by owners
+    /// per line. Patterns that do not compile are logged and skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in text.lines() {
+            let line = line.split_once(" #").map_or(line, |(rule, _)| rule).trim();
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next().filter(|pattern| !pattern.starts_with('#')) else {
+                continue;
+            };
+            // CODEOWNERS has no negation
+            if pattern.starts_with('!') {
+                tracing::warn!("Skipping CODEOWNERS pattern {}: negation is not supported", pattern);
+                continue;
+            }
+            let mut builder = GitignoreBuilder::new("");
+            let matcher = match builder.add_line(None, pattern).and_then(|builder| builder.build()) {
+                Ok(matcher) => matcher,
+                Err(e) => {
+                    tracing::warn!("Skipping CODEOWNERS pattern {}: {}", pattern, e);
+                    continue;
+                }
+            };
+            rules.push(Rule {
+                pattern: pattern.to_string(),
+                matcher,
+                owners: fields.map(str::to_string).collect(),
+            });
+        }
+        Self { rules }
+    }
+
+    /// The rule that owns `relative_path` and its owners: the last matching
+    /// one, as GitHub applies them. A matching rule without owners leaves
+    /// the file unowned.
+    pub fn owners_of(&self, relative_path: &Path) -> Option<(&str, &[String])> {
+        // The matchers panic on paths that are not relative to the repository
+        if relative_path.has_root() {
+            return None;
+        }
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matcher.matched_path_or_any_parents(relative_path, false).is_ignore())
+            .map(|rule| (rule.pattern.as_str(), rule.owners.as_slice()))
+    }
+
+    /// Sets `owners` on symbols of the repository at `root`.
+    pub fn annotate(&self, root: &str, symbols: &mut [CodeSymbol]) {
+        let mut by_file: HashMap<String, Vec<String>> = HashMap::new();
+        for symbol in symbols {
+            let owners = by_file.entry(symbol.file_path.clone()).or_insert_with(|| {
+                Path::new(&symbol.file_path)
+                    .strip_prefix(root)
+                    .ok()
+                    .and_then(|relative| self.owners_of(relative))
+                    .map(|(_, owners)| owners.to_vec())
+                    .unwrap_or_default()
+            });
+            symbol.owners = owners.clone();
+        }
+    }
+}
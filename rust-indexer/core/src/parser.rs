@@ -0,0 +1,1486 @@
+use crate::cache::{CacheStats, FileCache, FileStamp};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::shared_cache::RedisCache;
+use crate::chunker::{self, ChunkOptions, ChunkStrategy};
+use crate::embedded;
+use crate::encoding::{self, DecodedSource};
+use crate::error::{SourceError, DEFAULT_MAX_FILE_BYTES};
+use crate::extractor::{self, LanguageExtractor};
+use crate::highlight::Highlights;
+use crate::language;
+use crate::literals::{self, Literal, LiteralFilter};
+use crate::metrics;
+use crate::query_pack::{QueryMatch, QueryPackInfo, QueryPacks};
+use crate::repo;
+use crate::routes::{self, Route};
+use crate::rules::{ExtractionRule, RuleExtractor};
+use crate::stats::{coverage, FileDocumentation};
+use crate::symbol::{AstFilter, AstNode, CodeChunk, HighlightSpan, SelectionRange, CodeSymbol, Diagnostic, DiagnosticKind, ErrorRange, SymbolFilter};
+use crate::timing;
+use crate::tokens::Tokenizer;
+use anyhow::{Context, Result};
+use std::path::Path;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+use tree_sitter::{Language, Parser, Point, Tree, LANGUAGE_VERSION, MIN_COMPATIBLE_LANGUAGE_VERSION};
+
+/// Grammar ABI versions the linked tree-sitter runtime can load.
+pub const ABI_VERSIONS: RangeInclusive<usize> = MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION;
+
+/// Optional extraction behaviour requested by the caller.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    pub include_metrics: bool,
+    /// Adds Halstead measures to the metrics block (implies `include_metrics`).
+    pub include_halstead: bool,
+    /// When set, every symbol reports its token count.
+    pub tokenizer: Option<Tokenizer>,
+    /// Parses the file as this language instead of detecting it.
+    pub language: Option<String>,
+    /// Drops symbols the caller did not ask for.
+    pub filter: SymbolFilter,
+}
+
+/// Source-level view of a single symbol used when assembling LLM context.
+#[derive(Debug, Clone)]
+pub struct SymbolDetails {
+    pub source: String,
+    pub docstring: Option<String>,
+    pub parameters: Vec<String>,
+    /// Distinct identifiers referenced inside the symbol's body.
+    pub references: Vec<String>,
+}
+
+struct ParsedFile {
+    language: String,
+    source: String,
+    tree: Tree,
+    lossy_decoded: bool,
+}
+
+pub struct ParserService {
+    parsers: std::collections::HashMap<String, Language>,
+    /// Grammars the runtime cannot load, with the reason; their languages
+    /// are refused instead of failing inside the parser.
+    disabled: BTreeMap<String, (Language, String)>,
+    /// In registration order, which is the order `detect` is asked in.
+    extractors: Vec<Arc<dyn LanguageExtractor>>,
+    cache: FileCache<ParsedFile>,
+    #[cfg(not(target_arch = "wasm32"))]
+    shared: Option<Arc<RedisCache>>,
+    max_file_bytes: u64,
+    highlights: Highlights,
+    packs: QueryPacks,
+    /// Operator-configured extension mappings, consulted before the built-in
+    /// ones; replaced on a config reload.
+    extensions: RwLock<HashMap<String, String>>,
+    /// Whether repository walks list generated files (see
+    /// [`crate::generated::check`]).
+    include_generated: AtomicBool,
+    /// Extractors and extraction rules added on top of the built-in ones, in
+    /// order, for [`Self::extraction_fingerprint`].
+    extraction: Vec<String>,
+}
+
+impl ParserService {
+    /// Keeps up to `capacity` parsed files in memory; zero disables the cache.
+    pub fn with_cache_capacity(capacity: usize) -> Self {
+        let mut service = Self {
+            parsers: Default::default(),
+            disabled: BTreeMap::new(),
+            extractors: Vec::new(),
+            cache: FileCache::new("parse", capacity),
+            #[cfg(not(target_arch = "wasm32"))]
+            shared: None,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            highlights: Highlights::new(&Default::default()),
+            packs: QueryPacks::default(),
+            extensions: RwLock::new(HashMap::new()),
+            include_generated: AtomicBool::new(false),
+            extraction: Vec::new(),
+        };
+        for extractor in extractor::builtin() {
+            service.register(extractor);
+        }
+        service.highlights = Highlights::new(&service.parsers);
+        service
+    }
+
+    /// Adds `extractor`, replacing one of the same name; its grammar is
+    /// disabled when the runtime cannot load it.
+    fn register(&mut self, extractor: Arc<dyn LanguageExtractor>) {
+        let name = extractor.name().to_string();
+        let grammar = extractor.grammar();
+        self.extractors.retain(|existing| existing.name() != name);
+        self.extractors.push(extractor);
+        self.parsers.remove(&name);
+        self.disabled.remove(&name);
+        match grammar_error(&grammar) {
+            None => {
+                self.parsers.insert(name, grammar);
+            }
+            Some(reason) => {
+                tracing::error!("Disabling the {} grammar: {}", name, reason);
+                self.disabled.insert(name, (grammar, reason));
+            }
+        }
+    }
+
+    /// Registers an extractor for another language, or replaces the one
+    /// registered under the same name.
+    pub fn with_extractor(mut self, extractor: Arc<dyn LanguageExtractor>) -> Self {
+        self.extraction.push(format!("extractor {} {}", extractor.name(), extractor.grammar().version()));
+        self.register(extractor);
+        // A replaced grammar needs its highlight query compiled against it
+        self.highlights = Highlights::new(&self.parsers);
+        self
+    }
+
+    /// Puts configured extraction rules in front of a language's extractor.
+    pub fn with_rules(self, language: &str, rules: &[ExtractionRule]) -> Result<Self> {
+        let base = self
+            .extractors
+            .iter()
+            .find(|extractor| extractor.name() == language)
+            .cloned()
+            .ok_or_else(|| SourceError::UnknownLanguage(language.to_string()))?;
+        let extractor = RuleExtractor::new(base, rules)?;
+        let mut service = self.with_extractor(Arc::new(extractor));
+        service.extraction.push(format!("rules {} {:?}", language, rules));
+        Ok(service)
+    }
+
+    /// Loads the query packs under `dir` (see `QueryPacks::load_dir`). They
+    /// are compiled against the grammars registered so far, so extractors
+    /// are registered first.
+    pub fn with_query_packs(mut self, dir: &Path) -> Result<Self> {
+        self.packs = QueryPacks::load_dir(dir, &self.parsers)
+            .with_context(|| format!("Failed to read query packs from {}", dir.display()))?;
+        Ok(self)
+    }
+
+    /// Refuses to read source files larger than `bytes`.
+    pub fn with_max_file_bytes(mut self, bytes: u64) -> Self {
+        self.max_file_bytes = bytes;
+        self
+    }
+
+    /// Maps lowercase extensions, without the dot, to supported languages
+    /// ahead of the built-in table.
+    pub fn with_extensions(self, extensions: HashMap<String, String>) -> Self {
+        self.set_extensions(extensions);
+        self
+    }
+
+    /// Replaces the configured extension mappings.
+    pub fn set_extensions(&self, extensions: HashMap<String, String>) {
+        *self.extensions.write().unwrap() = extensions;
+    }
+
+    /// Makes repository walks list generated files too; they are left out by
+    /// default.
+    pub fn with_include_generated(self, include: bool) -> Self {
+        self.set_include_generated(include);
+        self
+    }
+
+    pub fn set_include_generated(&self, include: bool) {
+        self.include_generated.store(include, Ordering::Relaxed);
+    }
+
+    pub fn include_generated(&self) -> bool {
+        self.include_generated.load(Ordering::Relaxed)
+    }
+
+    /// Short digest of everything that decides which symbols a file yields
+    /// as `language`: the extractors and rules added to this service, the
+    /// extension mappings and whether generated files are included. Shared
+    /// cache entries are keyed by it.
+    pub fn extraction_fingerprint(&self, language: &str) -> String {
+        let mut extensions: Vec<_> = self.extensions.read().unwrap().iter().map(|(ext, name)| format!("{}={}", ext, name)).collect();
+        extensions.sort();
+        let mut hasher = Sha256::new();
+        for part in [language.to_string(), self.include_generated().to_string(), extensions.join(",")].iter().chain(&self.extraction) {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hasher.finalize()[..8].iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Language for a lowercase extension, configured mappings first.
+    pub fn language_for_extension(&self, ext: &str) -> Option<String> {
+        language::for_extension(ext, &self.extensions.read().unwrap())
+    }
+
+    /// What the API reports for `language`, configured extensions included.
+    pub fn language_info(&self, language: &str) -> language::LanguageInfo {
+        language::info(language, &self.extensions.read().unwrap())
+    }
+
+    /// Shares default symbol extraction and chunk hashes with other replicas.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_shared_cache(mut self, shared: Arc<RedisCache>) -> Self {
+        self.shared = Some(shared);
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn shared_cache(&self) -> Option<&RedisCache> {
+        self.shared.as_deref()
+    }
+
+    /// Names of every usable language, sorted.
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = self.parsers.keys().cloned().collect();
+        languages.sort();
+        languages
+    }
+
+    /// Each grammar, disabled ones included, with the tree-sitter ABI
+    /// version it was generated for.
+    pub fn grammars(&self) -> Vec<(String, usize)> {
+        let mut grammars: Vec<_> = self
+            .parsers
+            .iter()
+            .chain(self.disabled.iter().map(|(name, (language, _))| (name, language)))
+            .map(|(name, language)| (name.clone(), language.version()))
+            .collect();
+        grammars.sort();
+        grammars
+    }
+
+    /// Why a language's grammar is disabled, if it is.
+    pub fn grammar_error(&self, language: &str) -> Option<&str> {
+        self.disabled.get(language).map(|(_, reason)| reason.as_str())
+    }
+
+    /// Parses a file into the cache ahead of the first request for it.
+    pub async fn preload(&self, file_path: &str) -> Result<()> {
+        self.parse_file(file_path).await.map(|_| ())
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
+    /// Forgets parsed files under `repo_path`, or all of them.
+    pub fn purge_cache(&self, repo_path: Option<&str>) -> usize {
+        self.cache.purge(repo_path)
+    }
+
+    /// Languages whose grammar cannot be loaded into a parser, e.g. after an
+    /// ABI mismatch with the tree-sitter runtime, sorted.
+    pub fn broken_grammars(&self) -> Vec<String> {
+        self.disabled.keys().cloned().collect()
+    }
+
+    /// Language by extension; files without one are recognised by well-known
+    /// names (`BUILD`, ...), then by a shebang or modeline in their first lines.
+    pub fn detect_language(file_path: &str) -> Option<String> {
+        Self::detect_with(file_path, &HashMap::new())
+    }
+
+    /// [`Self::detect_language`] with `configured` extension mappings
+    /// consulted first.
+    fn detect_with(file_path: &str, configured: &HashMap<String, String>) -> Option<String> {
+        let path = Path::new(file_path);
+        let detected = match path.extension() {
+            Some(ext) => language::for_extension(&ext.to_str()?.to_lowercase(), configured),
+            None => path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(language::for_filename)
+                .or_else(|| language::sniff(path))
+                .map(str::to_string),
+        };
+        // Languages whose grammar feature is off are as unsupported as unknown ones
+        detected.filter(|language| language::is_supported(language))
+    }
+
+    /// Language a file is parsed as: the first registered extractor claiming
+    /// it, else the built-in detection with the configured extensions.
+    pub fn language_for(&self, file_path: &str) -> Option<String> {
+        self.extractors
+            .iter()
+            .find(|extractor| extractor.detect(file_path))
+            .map(|extractor| extractor.name().to_string())
+            .or_else(|| Self::detect_with(file_path, &self.extensions.read().unwrap()))
+    }
+
+    /// The grammar to parse `language` with, refusing a disabled one.
+    fn grammar(&self, language: &str) -> Result<&Language> {
+        if let Some((_, reason)) = self.disabled.get(language) {
+            return Err(SourceError::GrammarDisabled {
+                language: language.to_string(),
+                reason: reason.clone(),
+            }
+            .into());
+        }
+        self.parsers.get(language).context("Language parser not available")
+    }
+
+    fn extractor(&self, language: &str) -> Result<&dyn LanguageExtractor> {
+        self.extractors
+            .iter()
+            .find(|extractor| extractor.name() == language)
+            .map(|extractor| extractor.as_ref())
+            .context("Language parser not available")
+    }
+
+    /// Reads and decodes a source file, refusing binary files and files over the size limit.
+    async fn read_source(&self, file_path: &str) -> Result<DecodedSource> {
+        let size = crate::fs::metadata(file_path).await
+            .context("Failed to read file")?
+            .len();
+        self.check_size(size)?;
+        let bytes = crate::fs::read(file_path).await
+            .context("Failed to read file")?;
+        decode_source(file_path, &bytes)
+    }
+
+    /// `read_source` with blocking reads, for callers without an async runtime.
+    fn read_source_sync(&self, file_path: &str) -> Result<DecodedSource> {
+        let size = std::fs::metadata(file_path)
+            .context("Failed to read file")?
+            .len();
+        self.check_size(size)?;
+        let bytes = std::fs::read(file_path)
+            .context("Failed to read file")?;
+        decode_source(file_path, &bytes)
+    }
+
+    fn check_size(&self, size: u64) -> Result<()> {
+        if size > self.max_file_bytes {
+            return Err(SourceError::TooLarge { size, limit: self.max_file_bytes }.into());
+        }
+        Ok(())
+    }
+
+    /// The requested language (`c` is parsed as C++), or the detected one.
+    fn resolve_language(&self, file_path: &str, requested: Option<&str>) -> Result<String> {
+        let Some(requested) = requested else {
+            return Ok(self.language_for(file_path).ok_or(SourceError::UnsupportedLanguage)?);
+        };
+        let name = match requested.to_lowercase().as_str() {
+            "c" | "c++" => "cpp".to_string(),
+            "js" => "javascript".to_string(),
+            "ts" => "typescript".to_string(),
+            "py" => "python".to_string(),
+            other => other.to_string(),
+        };
+        // A disabled language is refused with its reason once it is parsed
+        match self.parsers.contains_key(&name) || self.disabled.contains_key(&name) {
+            true => Ok(name),
+            false => Err(SourceError::UnknownLanguage(requested.to_string()).into()),
+        }
+    }
+
+    async fn parse_file(&self, file_path: &str) -> Result<Arc<ParsedFile>> {
+        self.parse_file_as(file_path, None).await
+    }
+
+    /// Parses a file as `language` (detected when `None`), reusing the cached
+    /// tree while the file is unchanged and was parsed as the same language.
+    async fn parse_file_as(&self, file_path: &str, language: Option<&str>) -> Result<Arc<ParsedFile>> {
+        let language_name = self.resolve_language(file_path, language)?;
+
+        let stamp = FileStamp::of(file_path).await
+            .context("Failed to read file")?;
+        if let Some(parsed) = self.cache.get(file_path, stamp).filter(|parsed| parsed.language == language_name) {
+            timing::cache_hit(Some(&language_name));
+            return Ok(parsed);
+        }
+
+        let parsed = Arc::new(self.parse_decoded(language_name, self.read_source(file_path).await?)?);
+        self.cache.insert(file_path, stamp, parsed.clone(), parsed.source.len());
+        Ok(parsed)
+    }
+
+    fn parse_decoded(&self, language_name: String, source: DecodedSource) -> Result<ParsedFile> {
+        let language = self.grammar(&language_name)?;
+        let tree = parse_source(language, &source.text)?;
+        timing::parsed(&language_name, source.text.len());
+
+        Ok(ParsedFile {
+            language: language_name,
+            source: source.text,
+            tree,
+            lossy_decoded: source.lossy_decoded,
+        })
+    }
+
+    /// Whether the file had to be decoded from a non-UTF-8 encoding or with replacements.
+    pub async fn lossy_decoded(&self, file_path: &str, language: Option<&str>) -> bool {
+        self.parse_file_as(file_path, language).await.is_ok_and(|parsed| parsed.lossy_decoded)
+    }
+
+    /// ERROR and MISSING nodes in the file's parse tree, in source order.
+    pub async fn syntax_errors(&self, file_path: &str, language: Option<&str>) -> Result<Vec<ErrorRange>> {
+        let diagnostics = self.diagnostics(file_path, language).await?;
+        Ok(diagnostics.into_iter().map(|diagnostic| diagnostic.range).collect())
+    }
+
+    /// Like `syntax_errors`, with what went wrong at each location.
+    pub async fn diagnostics(&self, file_path: &str, language: Option<&str>) -> Result<Vec<Diagnostic>> {
+        let parsed = self.parse_file_as(file_path, language).await?;
+        Ok(syntax_diagnostics(&parsed.tree.root_node(), &parsed.source))
+    }
+
+    /// tree-sitter's s-expression dump of the file, or of the smallest node
+    /// spanning the given lines, plus the language it was parsed as.
+    pub async fn sexp(
+        &self,
+        file_path: &str,
+        language: Option<&str>,
+        start_line: Option<i32>,
+        end_line: Option<i32>,
+    ) -> Result<(String, String)> {
+        self.with_node(file_path, language, start_line, end_line, |node, _| node.to_sexp())
+            .await
+    }
+
+    /// The parse tree as nested named nodes, from the same starting node as
+    /// [`Self::sexp`], plus the language it was parsed as.
+    pub async fn ast(
+        &self,
+        file_path: &str,
+        language: Option<&str>,
+        start_line: Option<i32>,
+        end_line: Option<i32>,
+        filter: &AstFilter,
+    ) -> Result<(Vec<AstNode>, String)> {
+        self.with_node(file_path, language, start_line, end_line, |node, source| {
+            ast_nodes(node, None, source, filter, 0)
+        })
+        .await
+    }
+
+    /// Highlight spans for the file, or for the given lines, from the cached
+    /// parse, plus the language it was parsed as.
+    pub async fn highlight(
+        &self,
+        file_path: &str,
+        language: Option<&str>,
+        start_line: Option<i32>,
+        end_line: Option<i32>,
+    ) -> Result<(Vec<HighlightSpan>, String)> {
+        let parsed = self.parse_file_as(file_path, language).await?;
+        let rows = match (start_line, end_line) {
+            (None, None) => None,
+            _ => {
+                let (start, end) = line_range(start_line, end_line, parsed.source.lines().count())?;
+                Some(start..end)
+            }
+        };
+        let spans = self
+            .highlights
+            .spans(&parsed.language, parsed.tree.root_node(), &parsed.source, rows)
+            .ok_or(SourceError::UnsupportedLanguage)?;
+        Ok((spans, parsed.language.clone()))
+    }
+
+    pub fn query_packs(&self) -> Vec<QueryPackInfo> {
+        self.packs.list()
+    }
+
+    /// Matches of a query pack in a file, optionally restricted to a line range.
+    pub async fn run_query_pack(
+        &self,
+        file_path: &str,
+        language: Option<&str>,
+        pack: &str,
+        start_line: Option<i32>,
+        end_line: Option<i32>,
+    ) -> Result<(Vec<QueryMatch>, String)> {
+        let parsed = self.parse_file_as(file_path, language).await?;
+        let rows = match (start_line, end_line) {
+            (None, None) => None,
+            _ => {
+                let (start, end) = line_range(start_line, end_line, parsed.source.lines().count())?;
+                Some(start..end)
+            }
+        };
+        let matches = self
+            .packs
+            .run(&parsed.language, pack, parsed.tree.root_node(), &parsed.source, rows)
+            .ok_or_else(|| SourceError::UnknownQueryPack(pack.to_string()))?;
+        Ok((matches, parsed.language.clone()))
+    }
+
+    /// The named nodes enclosing a position (one-based line, byte column),
+    /// innermost first, skipping parents that cover exactly the same text.
+    /// Powers "expand selection" in editors.
+    pub async fn selection_ranges(
+        &self,
+        file_path: &str,
+        language: Option<&str>,
+        line: i32,
+        column: usize,
+    ) -> Result<(Vec<SelectionRange>, String)> {
+        let parsed = self.parse_file_as(file_path, language).await?;
+        let (row, _) = line_range(Some(line), Some(line), parsed.source.lines().count())?;
+        let point = Point::new(row, column);
+        let root = parsed.tree.root_node();
+
+        let mut ranges: Vec<SelectionRange> = Vec::new();
+        let mut last_bytes = None;
+        let mut current = root.named_descendant_for_point_range(point, point);
+        while let Some(node) = current {
+            if node.is_named() && last_bytes != Some(node.byte_range()) {
+                last_bytes = Some(node.byte_range());
+                ranges.push(SelectionRange {
+                    kind: node.kind().to_string(),
+                    range: ErrorRange {
+                        line_start: node.start_position().row as i32 + 1,
+                        line_end: node.end_position().row as i32 + 1,
+                        column_start: node.start_position().column,
+                        column_end: node.end_position().column,
+                    },
+                });
+            }
+            current = node.parent();
+        }
+        Ok((ranges, parsed.language.clone()))
+    }
+
+    /// Runs `dump` on the root node, or on the smallest node spanning the lines.
+    async fn with_node<T>(
+        &self,
+        file_path: &str,
+        language: Option<&str>,
+        start_line: Option<i32>,
+        end_line: Option<i32>,
+        dump: impl FnOnce(tree_sitter::Node, &str) -> T,
+    ) -> Result<(T, String)> {
+        let parsed = self.parse_file_as(file_path, language).await?;
+        let root = parsed.tree.root_node();
+        if start_line.is_none() && end_line.is_none() {
+            return Ok((dump(root, &parsed.source), parsed.language.clone()));
+        }
+        let lines: Vec<&str> = parsed.source.lines().collect();
+        let (start, end) = line_range(start_line, end_line, lines.len())?;
+        let last = end - 1;
+        let node = root
+            .descendant_for_point_range(Point::new(start, 0), Point::new(last, lines[last].len()))
+            .unwrap_or(root);
+        Ok((dump(node, &parsed.source), parsed.language.clone()))
+    }
+
+    pub async fn extract_symbols(&self, file_path: &str, options: &ExtractOptions) -> Result<Vec<CodeSymbol>> {
+        // Only plain extraction (what indexing and context building use) is shared
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(shared) = self.shared.as_deref().filter(|_| {
+            !options.include_metrics
+                && !options.include_halstead
+                && options.tokenizer.is_none()
+                && options.language.is_none()
+                && options.filter.is_empty()
+        }) {
+            let stamp = FileStamp::of(file_path).await.context("Failed to read file")?;
+            let language = self.language_for(file_path).ok_or(SourceError::UnsupportedLanguage)?;
+            let fingerprint = self.extraction_fingerprint(&language);
+            if let Some(symbols) = shared.get_symbols(file_path, stamp, &fingerprint).await {
+                timing::cache_hit(Some(&language));
+                return Ok(symbols);
+            }
+            let parsed = self.parse_file(file_path).await?;
+            let symbols = self.extract_from_tree(&parsed.tree.root_node(), &parsed.source, file_path, &parsed.language, options)?;
+            shared.put_symbols(file_path, stamp, &fingerprint, &symbols).await;
+            return Ok(symbols);
+        }
+
+        let parsed = self.parse_file_as(file_path, options.language.as_deref()).await?;
+        self.extract_from_tree(&parsed.tree.root_node(), &parsed.source, file_path, &parsed.language, options)
+    }
+
+    /// Extracts symbols from source text the caller already holds, as if it
+    /// were the contents of `file_path`; nothing is read or cached. This is
+    /// the entry point where there is no filesystem, as in the wasm32 build.
+    pub fn extract_from_source(&self, source: &str, file_path: &str, options: &ExtractOptions) -> Result<Vec<CodeSymbol>> {
+        let language_name = self.resolve_language(file_path, options.language.as_deref())?;
+        let language = self.grammar(&language_name)?;
+        let tree = parse_source(language, source)?;
+        self.extract_from_tree(&tree.root_node(), source, file_path, &language_name, options)
+    }
+
+    /// `extract_symbols` for callers without an async runtime (CLI tools,
+    /// build plugins). Reads the file with blocking I/O and bypasses both
+    /// caches.
+    pub fn extract_symbols_sync(&self, file_path: &str, options: &ExtractOptions) -> Result<Vec<CodeSymbol>> {
+        let language_name = self.resolve_language(file_path, options.language.as_deref())?;
+        let parsed = self.parse_decoded(language_name, self.read_source_sync(file_path)?)?;
+        self.extract_from_tree(&parsed.tree.root_node(), &parsed.source, file_path, &parsed.language, options)
+    }
+
+    /// Re-parses a symbol's file to recover its source, documentation and references.
+    pub async fn symbol_details(&self, symbol: &CodeSymbol) -> Result<SymbolDetails> {
+        let parsed = self.parse_file(&symbol.file_path).await?;
+        self.details_in(&parsed, symbol)
+    }
+
+    /// Details for several symbols of the same file, parsing it only once.
+    pub async fn file_symbol_details(&self, file_path: &str, symbols: &[&CodeSymbol]) -> Result<Vec<SymbolDetails>> {
+        let parsed = self.parse_file(file_path).await?;
+        symbols.iter().map(|symbol| self.details_in(&parsed, symbol)).collect()
+    }
+
+    /// Details of a symbol of `parsed`, looked up in the script or style
+    /// block declaring it when `parsed` is an HTML file.
+    fn details_in(&self, parsed: &ParsedFile, symbol: &CodeSymbol) -> Result<SymbolDetails> {
+        if parsed.language == "html" {
+            for block in embedded::html_blocks(&parsed.tree.root_node(), &parsed.source) {
+                let (Ok(grammar), Ok(extractor)) = (self.grammar(block.language), self.extractor(block.language)) else {
+                    continue;
+                };
+                let tree = embedded::parse(grammar, &parsed.source, &block.ranges)?;
+                if let Some(node) = find_symbol_node(&tree.root_node(), &parsed.source, symbol) {
+                    return node_details(&node, &parsed.source, symbol, extractor);
+                }
+            }
+        }
+        symbol_details_in(parsed, symbol, self.extractor(&parsed.language)?)
+    }
+
+    /// Comment density for a whole file plus documentation coverage of its exported symbols.
+    pub async fn file_documentation(&self, file_path: &str) -> Result<FileDocumentation> {
+        let parsed = self.parse_file(file_path).await?;
+        let root_node = parsed.tree.root_node();
+
+        let options = ExtractOptions {
+            include_metrics: true,
+            ..Default::default()
+        };
+        let symbols = self.extract_from_tree(&root_node, &parsed.source, file_path, &parsed.language, &options)?;
+        let lines = metrics::line_counts(&root_node, &parsed.source);
+
+        let exported: Vec<_> = symbols.iter().filter(|s| s.exported).collect();
+        let documented = exported
+            .iter()
+            .filter(|s| s.metrics.as_ref().map(|m| m.documented).unwrap_or(false))
+            .count();
+
+        Ok(FileDocumentation {
+            file_path: file_path.to_string(),
+            code_lines: lines.code_lines,
+            comment_lines: lines.comment_lines,
+            blank_lines: lines.blank_lines,
+            comment_ratio: lines.comment_ratio(),
+            exported_symbols: exported.len(),
+            documented_exported_symbols: documented,
+            documentation_coverage: coverage(documented, exported.len()),
+        })
+    }
+
+    /// String literals and named constants in a file, each with its
+    /// enclosing symbol, plus the language it was parsed as.
+    pub async fn literals(
+        &self,
+        file_path: &str,
+        language: Option<&str>,
+        filter: &LiteralFilter,
+    ) -> Result<(Vec<Literal>, String)> {
+        let parsed = self.parse_file_as(file_path, language).await?;
+        let root = parsed.tree.root_node();
+        let symbols = self.extract_from_tree(&root, &parsed.source, file_path, &parsed.language, &ExtractOptions::default())?;
+        let literals = literals::collect(root, &parsed.source, file_path, &parsed.language, &symbols, filter);
+        Ok((literals, parsed.language.clone()))
+    }
+
+    /// `literals` of every supported file in a repository, skipping files that fail.
+    pub async fn repo_literals(&self, repo_path: &str, filter: &LiteralFilter) -> Result<Vec<Literal>> {
+        let root = Path::new(repo_path);
+        if !root.is_dir() {
+            return Err(SourceError::NotADirectory.into());
+        }
+
+        let mut literals = Vec::new();
+        for path in repo::source_files(root, self) {
+            let Some(path) = path.to_str() else {
+                continue;
+            };
+            match self.literals(path, None, filter).await {
+                Ok((file_literals, _)) => literals.extend(file_literals),
+                Err(e) => tracing::warn!("Skipping {}: {}", path, e),
+            }
+        }
+        Ok(literals)
+    }
+
+    /// HTTP routes a repository declares through web frameworks, with their
+    /// handlers found among the repository's symbols; files that fail are
+    /// skipped.
+    pub async fn repo_routes(&self, repo_path: &str) -> Result<Vec<Route>> {
+        let root = Path::new(repo_path);
+        if !root.is_dir() {
+            return Err(SourceError::NotADirectory.into());
+        }
+
+        let (mut found, mut symbols) = (Vec::new(), Vec::new());
+        for path in repo::source_files(root, self) {
+            let Some(path) = path.to_str() else {
+                continue;
+            };
+            let parsed = match self.parse_file(path).await {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    tracing::warn!("Skipping {}: {}", path, e);
+                    continue;
+                }
+            };
+            let root_node = parsed.tree.root_node();
+            match self.extract_from_tree(&root_node, &parsed.source, path, &parsed.language, &ExtractOptions::default()) {
+                Ok(file_symbols) => {
+                    found.extend(routes::collect(root_node, &parsed.source, path, &parsed.language, &file_symbols));
+                    symbols.extend(file_symbols);
+                }
+                Err(e) => tracing::warn!("Skipping {}: {}", path, e),
+            }
+        }
+        routes::resolve(&mut found, &symbols);
+        Ok(found)
+    }
+
+    /// Extracts symbols from every supported file in a repository, skipping files that fail.
+    pub async fn extract_repo_symbols(&self, repo_path: &str, options: &ExtractOptions) -> Result<Vec<CodeSymbol>> {
+        let root = Path::new(repo_path);
+        if !root.is_dir() {
+            return Err(SourceError::NotADirectory.into());
+        }
+
+        let mut symbols = Vec::new();
+        for path in repo::source_files(root, self) {
+            let external = path.strip_prefix(root).is_ok_and(repo::is_external);
+            let Some(path) = path.to_str() else {
+                continue;
+            };
+            match self.extract_symbols(path, options).await {
+                Ok(mut file_symbols) => {
+                    for symbol in &mut file_symbols {
+                        symbol.external = external;
+                    }
+                    symbols.extend(file_symbols);
+                }
+                Err(e) => tracing::warn!("Skipping {}: {}", path, e),
+            }
+        }
+
+        Ok(symbols)
+    }
+
+    /// The symbols of a file that depend on something, each with the names
+    /// its body calls or uses as a type.
+    pub async fn extract_dependencies(&self, file_path: &str) -> Result<Vec<CodeSymbol>> {
+        let mut symbols = self.extract_symbols(file_path, &ExtractOptions::default()).await?;
+        symbols.retain(|symbol| !symbol.dependencies.is_empty());
+        Ok(symbols)
+    }
+
+    pub async fn get_chunk_hash(&self, file_path: &str, start_line: Option<i32>, end_line: Option<i32>) -> Result<String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(shared) = &self.shared {
+            let stamp = FileStamp::of(file_path).await.context("Failed to read file")?;
+            if let Some(hash) = shared.get_hash(file_path, stamp, start_line, end_line).await {
+                return Ok(hash);
+            }
+            let hash = self.hash_lines(file_path, start_line, end_line).await?;
+            shared.put_hash(file_path, stamp, start_line, end_line, &hash).await;
+            return Ok(hash);
+        }
+
+        self.hash_lines(file_path, start_line, end_line).await
+    }
+
+    async fn hash_lines(&self, file_path: &str, start_line: Option<i32>, end_line: Option<i32>) -> Result<String> {
+        let source_code = self.read_source(file_path).await?.text;
+
+        let lines: Vec<&str> = source_code.lines().collect();
+        let (start, end) = line_range(start_line, end_line, lines.len())?;
+        let chunk: String = lines[start..end].join("\n");
+        Ok(chunker::content_hash(&chunk))
+    }
+
+    /// Chunks a file with the requested strategy. Files without a supported
+    /// grammar always fall back to windowed chunking. Chunks of supported files
+    /// carry the language, file imports and innermost enclosing symbol.
+    pub async fn chunk_file(&self, file_path: &str, options: &ChunkOptions) -> Result<Vec<CodeChunk>> {
+        if options.language.is_none() && self.language_for(file_path).is_none() {
+            return chunk_plain(&self.read_source(file_path).await?, file_path, options);
+        }
+
+        let parsed = self.parse_file_as(file_path, options.language.as_deref()).await?;
+        self.chunk_parsed(&parsed, file_path, options)
+    }
+
+    /// `chunk_file` for callers without an async runtime. Reads the file with
+    /// blocking I/O and bypasses the parse cache.
+    pub fn chunk_sync(&self, file_path: &str, options: &ChunkOptions) -> Result<Vec<CodeChunk>> {
+        if options.language.is_none() && self.language_for(file_path).is_none() {
+            return chunk_plain(&self.read_source_sync(file_path)?, file_path, options);
+        }
+
+        let language_name = self.resolve_language(file_path, options.language.as_deref())?;
+        let parsed = self.parse_decoded(language_name, self.read_source_sync(file_path)?)?;
+        self.chunk_parsed(&parsed, file_path, options)
+    }
+
+    fn chunk_parsed(&self, parsed: &ParsedFile, file_path: &str, options: &ChunkOptions) -> Result<Vec<CodeChunk>> {
+        let root_node = parsed.tree.root_node();
+        let symbols = self.extract_from_tree(&root_node, &parsed.source, file_path, &parsed.language, &ExtractOptions::default())?;
+        let mut chunks = match options.strategy {
+            ChunkStrategy::Window | ChunkStrategy::Cell => chunker::chunk_text(&parsed.source, file_path, options)?,
+            ChunkStrategy::Symbol | ChunkStrategy::Function => {
+                let functions: Vec<(usize, usize)> = symbols
+                    .iter()
+                    .filter(|s| s.symbol_type == "function" || s.symbol_type == "method")
+                    .map(|s| (s.line_start as usize - 1, s.line_end as usize - 1))
+                    .collect();
+                chunker::chunk_tree(&root_node, &parsed.source, file_path, options, &functions)?
+            }
+        };
+
+        let imports = file_imports(&root_node, &parsed.source, self.extractor(&parsed.language)?.import_kinds());
+        for chunk in &mut chunks {
+            chunk.language = Some(parsed.language.clone());
+            chunk.imports = imports.clone();
+            chunk.lossy_decoded = parsed.lossy_decoded;
+            // A chunk may open with comments or decorators above its symbol,
+            // as long as no other symbol starts in between
+            let leads_into = |s: &CodeSymbol| {
+                s.line_end == chunk.line_end
+                    && !symbols
+                        .iter()
+                        .any(|o| o.line_start >= chunk.line_start && o.line_start < s.line_start)
+            };
+            chunk.symbol = symbols
+                .iter()
+                .filter(|s| s.line_end >= chunk.line_end && (s.line_start <= chunk.line_start || leads_into(s)))
+                .min_by_key(|s| s.line_end - s.line_start)
+                .and_then(|s| s.qualified_name.clone());
+        }
+
+        Ok(chunks)
+    }
+
+    fn extract_from_tree(
+        &self,
+        node: &tree_sitter::Node,
+        source: &str,
+        file_path: &str,
+        language: &str,
+        options: &ExtractOptions,
+    ) -> Result<Vec<CodeSymbol>> {
+        let extractor = self.extractor(language)?;
+        let mut symbols = Vec::new();
+        self.walk_tree(node, source, file_path, extractor, options, &mut symbols)?;
+        // Script and style blocks are parsed in place, so their symbols keep document positions
+        if language == "html" {
+            for block in embedded::html_blocks(node, source) {
+                let (Ok(grammar), Ok(block_extractor)) = (self.grammar(block.language), self.extractor(block.language)) else {
+                    continue;
+                };
+                let tree = embedded::parse(grammar, source, &block.ranges)?;
+                self.walk_tree(&tree.root_node(), source, file_path, block_extractor, options, &mut symbols)?;
+            }
+        }
+        Ok(dedup_symbols(symbols))
+    }
+
+    fn walk_tree(
+        &self,
+        node: &tree_sitter::Node,
+        source: &str,
+        file_path: &str,
+        extractor: &dyn LanguageExtractor,
+        options: &ExtractOptions,
+        symbols: &mut Vec<CodeSymbol>,
+    ) -> Result<()> {
+        let before = symbols.len();
+
+        symbols.extend(extractor.extract_symbol(node, source, file_path)?);
+
+        if !options.filter.is_empty() {
+            let kept: Vec<_> = symbols.drain(before..).filter(|symbol| options.filter.matches(symbol)).collect();
+            symbols.extend(kept);
+        }
+
+        for symbol in &mut symbols[before..] {
+            symbol.qualified_name = Some(qualified_name(node, &symbol.symbol_name, source, extractor.path_separator()));
+            symbol.dependencies = dependencies(node, source, extractor, &symbol.symbol_name);
+            symbol.supertypes = extractor.supertypes(node, source);
+        }
+
+        if options.include_metrics || options.include_halstead {
+            for symbol in &mut symbols[before..] {
+                let mut symbol_metrics = metrics::compute(node, source, extractor.name());
+                if options.include_halstead {
+                    symbol_metrics.halstead = Some(metrics::halstead(node, source));
+                }
+                symbol.metrics = Some(symbol_metrics);
+            }
+        }
+
+        if let Some(tokenizer) = &options.tokenizer {
+            let text = node.utf8_text(source.as_bytes())?;
+            for symbol in &mut symbols[before..] {
+                symbol.token_count = Some(tokenizer.count(text));
+            }
+        }
+
+        // Recursively process children
+        for i in 0..node.child_count() {
+            if let Some(child) = node.child(i) {
+                self.walk_tree(&child, source, file_path, extractor, options, symbols)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes a file's bytes, refusing binary content.
+fn decode_source(file_path: &str, bytes: &[u8]) -> Result<DecodedSource> {
+    if encoding::is_binary(bytes) {
+        return Err(SourceError::BinaryFile.into());
+    }
+    let decoded = encoding::decode(bytes);
+    if decoded.lossy_decoded {
+        tracing::debug!("Decoded {} as {}", file_path, decoded.encoding);
+    }
+    Ok(decoded)
+}
+
+/// Chunks a file without a supported grammar: notebooks by cell when asked,
+/// anything else in windows.
+fn chunk_plain(source: &DecodedSource, file_path: &str, options: &ChunkOptions) -> Result<Vec<CodeChunk>> {
+    let mut chunks = if options.strategy == ChunkStrategy::Cell && file_path.ends_with(".ipynb") {
+        chunker::chunk_notebook(&source.text, file_path, options)
+            .context("Failed to read notebook")?
+    } else {
+        chunker::chunk_text(&source.text, file_path, options)?
+    };
+    for chunk in &mut chunks {
+        chunk.lossy_decoded = source.lossy_decoded;
+    }
+    Ok(chunks)
+}
+
+fn parse_source(language: &Language, source: &str) -> Result<Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(language)?;
+    parser.parse(source, None).context("Failed to parse file")
+}
+
+/// Why the runtime cannot parse with `language`, if it cannot.
+fn grammar_error(language: &Language) -> Option<String> {
+    let version = language.version();
+    if !ABI_VERSIONS.contains(&version) {
+        return Some(format!(
+            "ABI version {} is outside the {}-{} the tree-sitter runtime supports",
+            version,
+            ABI_VERSIONS.start(),
+            ABI_VERSIONS.end()
+        ));
+    }
+    Parser::new().set_language(language).err().map(|e| e.to_string())
+}
+
+/// The outermost ERROR nodes and every MISSING node under `root`, those
+/// inside ERROR nodes included, in source order.
+fn syntax_diagnostics(root: &tree_sitter::Node, source: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if !root.has_error() {
+        return diagnostics;
+    }
+    let mut cursor = root.walk();
+    // Each node with whether it is inside an ERROR node already reported
+    let mut stack = vec![(*root, false)];
+    while let Some((node, in_error)) = stack.pop() {
+        let (kind, message) = if node.is_missing() {
+            (DiagnosticKind::Missing, format!("Missing `{}`", node.kind()))
+        } else if node.is_error() && !in_error {
+            stack.extend(node.children(&mut cursor).filter(|child| child.has_error()).map(|child| (child, true)));
+            let text = source.get(node.start_byte()..node.end_byte()).unwrap_or_default();
+            let first_line = text.lines().next().unwrap_or_default().trim();
+            let snippet: String = first_line.chars().take(40).collect();
+            (DiagnosticKind::Error, format!("Unexpected `{}`", snippet))
+        } else {
+            stack.extend(node.children(&mut cursor).filter(|child| child.has_error()).map(|child| (child, in_error)));
+            continue;
+        };
+        diagnostics.push(Diagnostic {
+            kind,
+            message,
+            range: ErrorRange {
+                line_start: node.start_position().row as i32 + 1,
+                line_end: node.end_position().row as i32 + 1,
+                column_start: node.start_position().column,
+                column_end: node.end_position().column,
+            },
+        });
+    }
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.range.line_start, diagnostic.range.column_start));
+    diagnostics
+}
+
+/// Zero-based `start..end` for one-based inclusive `start_line`/`end_line`.
+/// A start before line 1 or an end past the last line is clamped; ranges
+/// that are reversed or lie outside the file are rejected.
+fn line_range(start_line: Option<i32>, end_line: Option<i32>, line_count: usize) -> Result<(usize, usize)> {
+    let invalid = |reason| SourceError::InvalidLineRange { reason, line_count };
+    if line_count == 0 {
+        return Err(invalid("the file is empty").into());
+    }
+    let start = start_line.unwrap_or(1).max(1) as usize;
+    let end = match end_line {
+        Some(end) if end < 1 => return Err(invalid("end_line must be at least 1").into()),
+        Some(end) => (end as usize).min(line_count),
+        None => line_count,
+    };
+    if start > line_count {
+        return Err(invalid("start_line is past the end of the file").into());
+    }
+    if start > end {
+        return Err(invalid("start_line is after end_line").into());
+    }
+    Ok((start - 1, end))
+}
+
+fn symbol_details_in(parsed: &ParsedFile, symbol: &CodeSymbol, extractor: &dyn LanguageExtractor) -> Result<SymbolDetails> {
+    let root_node = parsed.tree.root_node();
+    let node = find_symbol_node(&root_node, &parsed.source, symbol)
+        .context("Symbol not found in file")?;
+    node_details(&node, &parsed.source, symbol, extractor)
+}
+
+fn node_details(node: &tree_sitter::Node, source: &str, symbol: &CodeSymbol, extractor: &dyn LanguageExtractor) -> Result<SymbolDetails> {
+    let mut references = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut cursor = node.walk();
+    let mut stack = vec![*node];
+    while let Some(current) = stack.pop() {
+        if current.child_count() == 0 && current.kind().ends_with("identifier") {
+            let text = current.utf8_text(source.as_bytes())?;
+            if text != symbol.symbol_name && seen.insert(text) {
+                references.push(text.to_string());
+            }
+        }
+        // Children are pushed in reverse so references come out in source order
+        let children: Vec<_> = current.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+
+    Ok(SymbolDetails {
+        source: node.utf8_text(source.as_bytes())?.to_string(),
+        docstring: extractor.docstring(node, source),
+        parameters: metrics::parameters(node, source),
+        references,
+    })
+}
+
+/// Names a symbol's body calls or uses as a type, in source order without
+/// repeats; the symbol's own name (recursion) is left out.
+fn dependencies(node: &tree_sitter::Node, source: &str, extractor: &dyn LanguageExtractor, own_name: &str) -> Vec<String> {
+    let (call_kinds, type_kinds) = (extractor.call_kinds(), extractor.type_kinds());
+    if call_kinds.is_empty() && type_kinds.is_empty() {
+        return Vec::new();
+    }
+
+    let mut names = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut cursor = node.walk();
+    let mut stack = vec![*node];
+    while let Some(current) = stack.pop() {
+        let name = match call_kinds.iter().find(|(kind, _)| *kind == current.kind()) {
+            Some((_, field)) => current.child_by_field_name(field).and_then(|callee| callee_name(callee, source)),
+            None if type_kinds.contains(&current.kind()) => current.utf8_text(source.as_bytes()).ok(),
+            None => None,
+        };
+        if let Some(name) = name.filter(|name| *name != own_name) {
+            if seen.insert(name) {
+                names.push(name.to_string());
+            }
+        }
+        let children: Vec<_> = current.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    names
+}
+
+/// The identifier naming what a callee expression refers to: `c` in
+/// `a.b.c`, `a::b::c`, `a->c` or `c::<T>`.
+fn callee_name<'a>(node: tree_sitter::Node, source: &'a str) -> Option<&'a str> {
+    let mut node = node;
+    while node.named_child_count() > 0 {
+        node = ["name", "function", "field", "property", "attribute"]
+            .iter()
+            .find_map(|field| node.child_by_field_name(field))
+            .or_else(|| node.named_child(node.named_child_count() - 1))?;
+    }
+    if !node.kind().ends_with("identifier") {
+        return None;
+    }
+    node.utf8_text(source.as_bytes()).ok()
+}
+
+/// Locates the node a symbol was extracted from by its line span and name.
+fn find_symbol_node<'a>(
+    root: &tree_sitter::Node<'a>,
+    source: &str,
+    symbol: &CodeSymbol,
+) -> Option<tree_sitter::Node<'a>> {
+    let start_row = (symbol.line_start - 1).max(0) as usize;
+    let end_row = (symbol.line_end - 1).max(0) as usize;
+
+    let mut cursor = root.walk();
+    let mut stack = vec![*root];
+    while let Some(current) = stack.pop() {
+        if current.start_position().row > end_row || current.end_position().row < start_row {
+            continue;
+        }
+        if current.start_position().row == start_row && current.end_position().row == end_row {
+            let name = current
+                .child_by_field_name("name")
+                .and_then(|n| n.utf8_text(source.as_bytes()).ok());
+            if name == Some(symbol.symbol_name.as_str()) {
+                return Some(current);
+            }
+        }
+        for child in current.children(&mut cursor) {
+            stack.push(child);
+        }
+    }
+
+    None
+}
+
+/// `node` (the `field` of its parent) and its named descendants as JSON nodes. Nodes deeper than
+/// `max_depth` are left out; nodes whose kind is filtered out are replaced
+/// by their (filtered) children, so matches keep their nesting.
+fn ast_nodes(
+    node: tree_sitter::Node,
+    field: Option<&str>,
+    source: &str,
+    filter: &AstFilter,
+    depth: usize,
+) -> Vec<AstNode> {
+    let mut children = Vec::new();
+    if !matches!(filter.max_depth, Some(max) if depth >= max) {
+        let mut cursor = node.walk();
+        if cursor.goto_first_child() {
+            loop {
+                let child = cursor.node();
+                if child.is_named() {
+                    children.extend(ast_nodes(child, cursor.field_name(), source, filter, depth + 1));
+                }
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+    }
+
+    if !filter.kinds.is_empty() && !filter.kinds.iter().any(|kind| kind == node.kind()) {
+        return children;
+    }
+    let text = (filter.include_text && node.named_child_count() == 0)
+        .then(|| node.utf8_text(source.as_bytes()).ok().map(str::to_string))
+        .flatten();
+    vec![AstNode {
+        kind: node.kind().to_string(),
+        field: field.map(str::to_string),
+        range: ErrorRange {
+            line_start: node.start_position().row as i32 + 1,
+            line_end: node.end_position().row as i32 + 1,
+            column_start: node.start_position().column,
+            column_end: node.end_position().column,
+        },
+        is_error: node.is_error(),
+        is_missing: node.is_missing(),
+        text,
+        children,
+    }]
+}
+
+/// Drops symbols that repeat one already reported: a construct matched both
+/// as a declaration and as its wrapper (`export class X`, a declarator inside
+/// its definition, ...) yields the same qualified name over nested ranges
+/// that share a first or last line. The outermost match is kept, since it
+/// spans the whole construct. Genuinely nested symbols (a method in an impl
+/// in a mod) have different qualified names and are all kept, in source order.
+fn dedup_symbols(symbols: Vec<CodeSymbol>) -> Vec<CodeSymbol> {
+    let mut kept: Vec<CodeSymbol> = Vec::with_capacity(symbols.len());
+    // Positions in `kept` by name and first line, and by name and last line:
+    // only symbols sharing one can repeat each other. Entries left behind
+    // when a symbol is replaced are weeded out by the full comparison.
+    let mut by_start: HashMap<SymbolLineKey, Vec<usize>> = HashMap::new();
+    let mut by_end: HashMap<SymbolLineKey, Vec<usize>> = HashMap::new();
+    for symbol in symbols {
+        let key = |line: i32| (symbol.qualified_name.clone(), symbol.symbol_name.clone(), line);
+        let duplicate = by_start
+            .get(&key(symbol.line_start))
+            .into_iter()
+            .chain(by_end.get(&key(symbol.line_end)))
+            .flatten()
+            .copied()
+            .filter(|&index| {
+                let other = &kept[index];
+                other.qualified_name == symbol.qualified_name
+                    && other.symbol_name == symbol.symbol_name
+                    && (other.line_start == symbol.line_start || other.line_end == symbol.line_end)
+                    && (contains(other, &symbol) || contains(&symbol, other))
+            })
+            .min();
+        let index = match duplicate {
+            Some(index) if contains(&symbol, &kept[index]) && !contains(&kept[index], &symbol) => {
+                kept[index] = symbol;
+                index
+            }
+            Some(_) => continue,
+            None => {
+                kept.push(symbol);
+                kept.len() - 1
+            }
+        };
+        let symbol = &kept[index];
+        let key = |line: i32| (symbol.qualified_name.clone(), symbol.symbol_name.clone(), line);
+        by_start.entry(key(symbol.line_start)).or_default().push(index);
+        by_end.entry(key(symbol.line_end)).or_default().push(index);
+    }
+    kept.sort_by_key(|symbol| (symbol.line_start, std::cmp::Reverse(symbol.line_end)));
+    kept
+}
+
+/// Qualified name, name and a line of a symbol.
+type SymbolLineKey = (Option<String>, String, i32);
+
+fn contains(outer: &CodeSymbol, inner: &CodeSymbol) -> bool {
+    outer.line_start <= inner.line_start && inner.line_end <= outer.line_end
+}
+
+/// Builds `Outer::inner` (Rust, C++) or `Outer.inner` names from the
+/// declarations enclosing `node`.
+fn qualified_name(node: &tree_sitter::Node, name: &str, source: &str, separator: &str) -> String {
+    let mut parts = vec![name.to_string()];
+    // A Go method belongs to its receiver's type
+    if let Some(receiver) = node.child_by_field_name("receiver").and_then(|n| receiver_type(n, source)) {
+        parts.push(receiver.to_string());
+    }
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        let container_name = match ancestor.kind() {
+            "impl_item" => ancestor.child_by_field_name("type"),
+            "mod_item" | "trait_item" | "class_declaration" | "class_definition" | "class_specifier"
+            | "struct_specifier" | "interface_declaration" | "enum_declaration" | "namespace_definition"
+            | "abstract_class_declaration" | "type_spec" => ancestor.child_by_field_name("name"),
+            _ => None,
+        };
+        if let Some(text) = container_name.and_then(|n| n.utf8_text(source.as_bytes()).ok()) {
+            parts.push(text.to_string());
+        }
+        current = ancestor.parent();
+    }
+
+    parts.reverse();
+    parts.join(separator)
+}
+
+/// The type named by a Go receiver list: `Server` in `(s *Server)` or
+/// `(l List[T])`.
+fn receiver_type<'a>(receiver: tree_sitter::Node, source: &'a str) -> Option<&'a str> {
+    let mut node = receiver;
+    while node.kind() != "type_identifier" {
+        node = node.child_by_field_name("type").or_else(|| node.named_child(0))?;
+    }
+    node.utf8_text(source.as_bytes()).ok()
+}
+
+/// Top-level statements of the given import kinds (see
+/// `LanguageExtractor::import_kinds`), one per entry.
+pub fn file_imports(root: &tree_sitter::Node, source: &str, import_kinds: &[&str]) -> Vec<String> {
+    let mut imports = Vec::new();
+    let mut cursor = root.walk();
+    let mut stack: Vec<_> = root.children(&mut cursor).collect();
+    stack.reverse();
+    while let Some(node) = stack.pop() {
+        if import_kinds.contains(&node.kind()) {
+            if let Ok(text) = node.utf8_text(source.as_bytes()) {
+                imports.push(text.split_whitespace().collect::<Vec<_>>().join(" "));
+            }
+        } else if matches!(node.kind(), "import_declaration" | "import_spec_list") {
+            // Go groups specs in `import ( ... )` blocks
+            let mut inner = node.walk();
+            let mut children: Vec<_> = node.children(&mut inner).collect();
+            children.reverse();
+            stack.extend(children);
+        }
+    }
+
+    imports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, line_start: i32, line_end: i32) -> CodeSymbol {
+        CodeSymbol {
+            id: format!("a.ts:{}:{}", name, line_start),
+            symbol_name: name.to_string(),
+            symbol_type: "class".to_string(),
+            file_path: "a.ts".to_string(),
+            line_start,
+            line_end,
+            signature: None,
+            dependencies: Vec::new(),
+            exported: false,
+            visibility: None,
+            metrics: None,
+            token_count: None,
+            enrichment: None,
+            qualified_name: Some(name.to_string()),
+            supertypes: Vec::new(),
+            reference_count: None,
+            owners: Vec::new(),
+            external: false,
+        }
+    }
+
+    fn spans(symbols: &[CodeSymbol]) -> Vec<(&str, i32, i32)> {
+        symbols.iter().map(|s| (s.symbol_name.as_str(), s.line_start, s.line_end)).collect()
+    }
+
+    #[test]
+    fn dedup_keeps_the_outermost_of_nested_matches() {
+        let symbols = vec![symbol("Widget", 2, 9), symbol("Widget", 1, 9), symbol("render", 3, 5), symbol("Widget", 1, 4)];
+        assert_eq!(spans(&dedup_symbols(symbols)), vec![("Widget", 1, 9), ("render", 3, 5)]);
+    }
+
+    #[test]
+    fn dedup_keeps_same_names_over_separate_ranges() {
+        let symbols = vec![symbol("overload", 1, 2), symbol("overload", 3, 4), symbol("overload", 3, 4)];
+        assert_eq!(spans(&dedup_symbols(symbols)), vec![("overload", 1, 2), ("overload", 3, 4)]);
+    }
+
+    #[test]
+    fn missing_nodes_inside_errors_are_reported() {
+        let parser = ParserService::with_cache_capacity(0);
+        // Recovery wraps everything in one ERROR node holding a `let` that lacks its `;`
+        let source = "let 1 { { def = class class { ] ) ]\n";
+        let tree = parse_source(parser.grammar("rust").unwrap(), source).unwrap();
+        let diagnostics = syntax_diagnostics(&tree.root_node(), source);
+        let kinds: Vec<_> = diagnostics.iter().map(|d| (d.kind, d.message.as_str())).collect();
+        assert!(kinds.contains(&(DiagnosticKind::Missing, "Missing `;`")), "{:?}", kinds);
+        assert_eq!(kinds.iter().filter(|(kind, _)| *kind == DiagnosticKind::Error).count(), 1, "{:?}", kinds);
+    }
+
+    #[test]
+    fn exported_class_is_reported_once() {
+        let parser = ParserService::with_cache_capacity(0);
+        let source = "export class Widget {\n  render() {}\n}\n";
+        let symbols = parser.extract_from_source(source, "widget.ts", &ExtractOptions::default()).unwrap();
+        let widgets = symbols.iter().filter(|s| s.symbol_name == "Widget").count();
+        assert_eq!(widgets, 1);
+    }
+
+    /// Name, type, first and last line, exported.
+    type Outline<'a> = (&'a str, &'a str, i32, i32, bool);
+
+    fn outline(symbols: &[CodeSymbol]) -> Vec<Outline<'_>> {
+        symbols.iter().map(|s| (s.symbol_name.as_str(), s.symbol_type.as_str(), s.line_start, s.line_end, s.exported)).collect()
+    }
+
+    /// Pins what every registered extractor reports, so moving language
+    /// handling around cannot silently add, drop or reshape symbols.
+    #[test]
+    fn extractors_keep_their_symbols() {
+        let parser = ParserService::with_cache_capacity(0);
+        let cases: &[(&str, &str, &[Outline])] = &[
+            (
+                "lib.rs",
+                "use std::fmt;\n\npub struct Point {\n    x: i32,\n}\n\nenum Shape {\n    Dot,\n}\n\npub trait Area {\n    fn area(&self) -> f64;\n}\n\nimpl Point {\n    pub fn new(x: i32) -> Self {\n        Point { x }\n    }\n}\n\nfn helper() {}\n\nmod inner {\n    pub(crate) fn nested() {}\n}\n",
+                &[
+                    ("Point", "struct", 3, 5, true),
+                    ("Shape", "enum", 7, 9, false),
+                    ("Area", "trait", 11, 13, true),
+                    ("Point", "impl", 15, 19, false),
+                    ("new", "function", 16, 18, true),
+                    ("helper", "function", 21, 21, false),
+                    ("nested", "function", 24, 24, true),
+                ],
+            ),
+            (
+                "app.ts",
+                "import { a } from './a';\n\nexport class Widget extends Base {\n  render(): void {}\n}\n\ninterface Props {\n  name: string;\n}\n\nexport function build(x: number) {\n  return x;\n}\n\nconst arrow = () => 1;\n",
+                &[
+                    ("Widget", "class", 3, 5, true),
+                    ("render", "method", 4, 4, true),
+                    ("Props", "interface", 7, 9, false),
+                    ("build", "function", 11, 13, true),
+                ],
+            ),
+            (
+                "app.js",
+                "class Thing {\n  go() {}\n}\n\nfunction run() {}\n\nexport const handler = async (req) => req;\n",
+                &[
+                    ("Thing", "class", 1, 3, false),
+                    ("go", "method", 2, 2, false),
+                    ("run", "function", 5, 5, false),
+                ],
+            ),
+            (
+                "main.go",
+                "package main\n\nimport \"fmt\"\n\ntype Server struct {\n\tport int\n}\n\ntype Handler interface {\n\tServe()\n}\n\nfunc (s *Server) Start() error {\n\treturn nil\n}\n\nfunc helper() {}\n",
+                &[
+                    ("Server", "struct", 5, 7, true),
+                    ("Handler", "interface", 9, 11, true),
+                    ("Serve", "method", 10, 10, true),
+                    ("Start", "method", 13, 15, true),
+                    ("helper", "function", 17, 17, false),
+                ],
+            ),
+            (
+                "app.py",
+                "import os\n\nclass Service(Base):\n    def run(self):\n        pass\n\n    def _hidden(self):\n        pass\n\ndef main():\n    pass\n",
+                &[
+                    ("Service", "class", 3, 8, true),
+                    ("run", "function", 4, 5, true),
+                    ("_hidden", "function", 7, 8, false),
+                    ("main", "function", 10, 11, true),
+                ],
+            ),
+            (
+                "App.java",
+                "package app;\n\npublic class App {\n    private int count;\n\n    public void run() {}\n\n    static int helper() { return 1; }\n}\n\ninterface Runner {\n    void go();\n}\n",
+                &[
+                    ("App", "class", 3, 9, true),
+                    ("run", "method", 6, 6, true),
+                    ("helper", "method", 8, 8, true),
+                    ("Runner", "interface", 11, 13, true),
+                    ("go", "method", 12, 12, true),
+                ],
+            ),
+            (
+                "shape.cpp",
+                "#include <vector>\n\nclass Shape {\npublic:\n    virtual double area() const;\n};\n\nstruct Point {\n    int x;\n};\n\nint add(int a, int b) {\n    return a + b;\n}\n",
+                &[
+                    ("Shape", "class", 3, 6, false),
+                ],
+            ),
+            (
+                "site.css",
+                ".button {\n  color: red;\n}\n\n#main .title {\n  margin: 0;\n}\n",
+                &[
+                    (".button", "rule", 1, 3, false),
+                    ("#main .title", "rule", 5, 7, false),
+                ],
+            ),
+        ];
+        for (path, source, expected) in cases {
+            let symbols = parser.extract_from_source(source, path, &ExtractOptions::default()).unwrap();
+            assert_eq!(outline(&symbols), *expected, "{}", path);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::symbol::ErrorRange;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::Path;
+use tree_sitter::{Language, Node, Point, Query, QueryCursor};
+
+/// A loaded query pack, as listed by `GET /query-packs`.
+#[derive(Debug, Serialize)]
+pub struct QueryPackInfo {
+    pub language: String,
+    pub name: String,
+    pub patterns: usize,
+    pub captures: Vec<String>,
+}
+
+/// One match of a pack's query: the captures of a single pattern.
+#[derive(Debug, Serialize)]
+pub struct QueryMatch {
+    /// Index of the pattern in the pack's `.scm` file.
+    pub pattern: usize,
+    pub captures: Vec<QueryCapture>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryCapture {
+    /// Capture name without the `@`.
+    pub name: String,
+    pub text: String,
+    /// Offsets into the decoded text (see `CodeChunk::lossy_decoded`).
+    pub start_byte: usize,
+    pub end_byte: usize,
+    #[serde(flatten)]
+    pub range: ErrorRange,
+}
+
+/// Named queries per language, read from `<dir>/<language>/<name>.scm`.
+#[derive(Default)]
+pub struct QueryPacks {
+    packs: HashMap<String, BTreeMap<String, Query>>,
+}
+
+impl QueryPacks {
+    /// Compiles every `.scm` file under a subdirectory of `dir` named after
+    /// a language in `languages`. Unknown languages and queries that do not
+    /// compile against their grammar are logged and skipped.
+    pub fn load_dir(dir: &Path, languages: &HashMap<String, Language>) -> std::io::Result<Self> {
+        let mut packs: HashMap<String, BTreeMap<String, Query>> = HashMap::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let language_name = path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+            let Some(language) = languages.get(&language_name) else {
+                tracing::warn!("Skipping query packs in {}: no {} grammar", path.display(), language_name);
+                continue;
+            };
+
+            for file in std::fs::read_dir(&path)? {
+                let file = file?.path();
+                if file.extension().and_then(|ext| ext.to_str()) != Some("scm") {
+                    continue;
+                }
+                let name = file.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+                let source = std::fs::read_to_string(&file)?;
+                match Query::new(language, &source) {
+                    Ok(query) => {
+                        packs.entry(language_name.clone()).or_default().insert(name, query);
+                    }
+                    Err(e) => tracing::warn!("Query pack {} does not compile: {}", file.display(), e),
+                }
+            }
+        }
+        Ok(Self { packs })
+    }
+
+    /// Every loaded pack, by language and then name.
+    pub fn list(&self) -> Vec<QueryPackInfo> {
+        let mut infos: Vec<QueryPackInfo> = self
+            .packs
+            .iter()
+            .flat_map(|(language, packs)| {
+                packs.iter().map(move |(name, query)| QueryPackInfo {
+                    language: language.clone(),
+                    name: name.clone(),
+                    patterns: query.pattern_count(),
+                    captures: query.capture_names().iter().map(|name| name.to_string()).collect(),
+                })
+            })
+            .collect();
+        infos.sort_by(|a, b| a.language.cmp(&b.language).then_with(|| a.name.cmp(&b.name)));
+        infos
+    }
+
+    /// Matches of `language`'s pack `name` in `root`, optionally only those
+    /// within `rows` (zero-based, end exclusive). `None` when there is no
+    /// such pack.
+    pub fn run(
+        &self,
+        language: &str,
+        name: &str,
+        root: Node,
+        source: &str,
+        rows: Option<Range<usize>>,
+    ) -> Option<Vec<QueryMatch>> {
+        let query = self.packs.get(language)?.get(name)?;
+        let mut cursor = QueryCursor::new();
+        if let Some(rows) = rows {
+            cursor.set_point_range(Point::new(rows.start, 0)..Point::new(rows.end, 0));
+        }
+
+        let names = query.capture_names();
+        let matches = cursor
+            .matches(query, root, source.as_bytes())
+            .map(|found| QueryMatch {
+                pattern: found.pattern_index,
+                captures: found
+                    .captures
+                    .iter()
+                    .map(|capture| {
+                        let node = capture.node;
+                        QueryCapture {
+                            name: names[capture.index as usize].to_string(),
+                            text: source[node.byte_range()].to_string(),
+                            start_byte: node.start_byte(),
+                            end_byte: node.end_byte(),
+                            range: ErrorRange {
+                                line_start: node.start_position().row as i32 + 1,
+                                line_end: node.end_position().row as i32 + 1,
+                                column_start: node.start_position().column,
+                                column_end: node.end_position().column,
+                            },
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        Some(matches)
+    }
+}
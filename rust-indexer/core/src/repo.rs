@@ -0,0 +1,146 @@
+use crate::generated;
+use crate::parser::ParserService;
+use ignore::{DirEntry, WalkBuilder};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Directories that never contain first-party source worth indexing.
+const SKIPPED_DIRS: &[&str] = &[
+    "target",
+    "dist",
+    "build",
+    "__pycache__",
+];
+
+/// Directories of vendored and third-party code. Unless gitignored they are
+/// walked like any other, and what is under them is marked external.
+const EXTERNAL_DIRS: &[&str] = &["vendor", "third_party", "third-party", "node_modules"];
+
+/// Whether a path, relative to its repository, is vendored or third-party
+/// code.
+pub fn is_external(relative_path: &Path) -> bool {
+    relative_path
+        .parent()
+        .is_some_and(|dir| dir.iter().any(|part| EXTERNAL_DIRS.iter().any(|external| part == *external)))
+}
+
+fn is_skipped(entry: &DirEntry) -> bool {
+    if entry.depth() == 0 {
+        return false;
+    }
+    let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+    let name = entry.file_name().to_string_lossy();
+    is_dir && SKIPPED_DIRS.contains(&name.as_ref())
+}
+
+/// Whether `entry` is a symlinked directory pointing outside `root`, so the
+/// walk never descends into it.
+fn escapes(entry: &DirEntry, root: &Path) -> bool {
+    if !entry.path_is_symlink() || !entry.file_type().is_some_and(|t| t.is_dir()) {
+        return false;
+    }
+    let escapes = !entry.path().canonicalize().is_ok_and(|target| target.starts_with(root));
+    if escapes {
+        tracing::warn!("Not following {}: symlink resolves outside the repository", entry.path().display());
+    }
+    escapes
+}
+
+/// Lists every file under `root`, honouring `.gitignore` and skipping hidden entries.
+///
+/// Symlinks are followed only while they resolve inside `root`; links that
+/// escape it are skipped, directory cycles (such as a `node_modules` package
+/// linking back to its workspace) are cut, and a file reachable through
+/// several links is listed once.
+pub fn files(root: &Path) -> Vec<PathBuf> {
+    let Ok(canonical_root) = root.canonicalize() else {
+        return Vec::new();
+    };
+    let mut seen = HashSet::new();
+    let jail = canonical_root.clone();
+    WalkBuilder::new(root)
+        .require_git(false)
+        .follow_links(true)
+        .filter_entry(move |e| !is_skipped(e) && !escapes(e, &jail))
+        .build()
+        .filter_map(|e| match e {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                tracing::warn!("Skipping entry under {}: {}", root.display(), e);
+                None
+            }
+        })
+        .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .filter(|e| match e.path().canonicalize() {
+            Ok(target) if target.starts_with(&canonical_root) => seen.insert(target),
+            _ => {
+                tracing::warn!("Skipping {}: resolves outside the repository", e.path().display());
+                false
+            }
+        })
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// Lists every file under `root` that `parser` has a language for, leaving
+/// out generated files unless it includes them (see
+/// [`ParserService::with_include_generated`]).
+pub fn source_files(root: &Path, parser: &ParserService) -> Vec<PathBuf> {
+    let include_generated = parser.include_generated();
+    files(root)
+        .into_iter()
+        .filter(|path| {
+            path.to_str()
+                .and_then(|path| parser.language_for(path))
+                .is_some()
+        })
+        .filter(|path| {
+            if include_generated {
+                return true;
+            }
+            match generated::check(path) {
+                Some(reason) => {
+                    tracing::debug!("Skipping generated file {} ({})", path.display(), reason);
+                    false
+                }
+                None => true,
+            }
+        })
+        .collect()
+}
+
+/// Separators accepted in request paths: a Windows checkout or client may
+/// send `\` where we expect `/`, or a mix of both.
+fn parts(path: &str) -> impl Iterator<Item = &str> {
+    path.split(['/', '\\']).filter(|part| !part.is_empty() && *part != ".")
+}
+
+/// Joins a request's file path onto its repository path. `.` components and
+/// repeated or leading separators are dropped, and either separator is
+/// accepted in the file path. The repository path is kept as given, so
+/// drive letters (`C:\repos\app`) and UNC shares (`\\server\share\app`)
+/// resolve as usual on Windows hosts.
+pub fn join(repo_path: &str, file_path: &str) -> String {
+    let mut path = PathBuf::from(repo_path);
+    path.extend(parts(file_path));
+    path.to_string_lossy().into_owned()
+}
+
+/// Whether `path` has a `..` component under either separator.
+pub fn has_parent_dir(path: &str) -> bool {
+    parts(path).any(|part| part == "..")
+}
+
+/// Whether `path` is absolute on any platform: a leading separator, a drive
+/// letter (`C:`) or a UNC prefix.
+pub fn is_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with(['/', '\\']) || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+/// Whether `path` is `root` or inside it, comparing components so that
+/// separators and trailing slashes do not matter.
+pub fn is_within(path: &str, root: &str) -> bool {
+    let mut path = parts(path);
+    parts(root).all(|part| path.next() == Some(part))
+}
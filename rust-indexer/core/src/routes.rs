@@ -0,0 +1,473 @@
+use crate::literals::{unquote, STRING_KINDS};
+use crate::symbol::CodeSymbol;
+use serde::Serialize;
+use tree_sitter::Node;
+
+/// Route helpers named after the HTTP method they register.
+const METHODS: &[&str] = &["get", "post", "put", "delete", "patch", "head", "options"];
+
+/// Spring's mapping annotations and the method each implies.
+const SPRING_MAPPINGS: &[(&str, Option<&str>)] = &[
+    ("RequestMapping", None),
+    ("GetMapping", Some("GET")),
+    ("PostMapping", Some("POST")),
+    ("PutMapping", Some("PUT")),
+    ("DeleteMapping", Some("DELETE")),
+    ("PatchMapping", Some("PATCH")),
+];
+
+/// An HTTP endpoint declared through a web framework.
+#[derive(Debug, Serialize, Clone)]
+pub struct Route {
+    /// Upper-case HTTP method; `ANY` when every method is accepted.
+    pub method: String,
+    /// The path as declared, with the framework's parameter syntax
+    /// (`/users/<id>`, `/users/:id`, `/users/{id}`).
+    pub path: String,
+    /// `flask`, `fastapi`, `spring`, `express` or `axum`.
+    pub framework: &'static str,
+    /// The handler as written: a function name or path, absent for inline
+    /// handlers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handler: Option<String>,
+    /// Id of the handler's symbol, when it could be found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handler_id: Option<String>,
+    pub file_path: String,
+    /// One-based line of the declaration.
+    pub line: i32,
+}
+
+/// Routes declared in a file through Flask or FastAPI decorators, Spring
+/// mapping annotations, Express `app.get(...)`-style calls or axum
+/// `Router::route`, in source order. Decorated and annotated handlers are
+/// attributed to their symbol among `symbols`; see [`resolve`] for the rest.
+///
+/// Paths are as declared: prefixes added by blueprints, routers mounted
+/// elsewhere or `nest` are not applied, Spring's class-level
+/// `@RequestMapping` is. Python and Express files are only considered when
+/// they import the framework.
+pub fn collect(root: Node, source: &str, file_path: &str, language: &str, symbols: &[CodeSymbol]) -> Vec<Route> {
+    let framework = match language {
+        "python" => python_framework(&root, source),
+        "javascript" | "typescript" | "tsx" => imports_express(&root, source).then_some("express"),
+        "java" => Some("spring"),
+        "rust" => Some("axum"),
+        _ => None,
+    };
+    let Some(framework) = framework else {
+        return vec![];
+    };
+    let mut routes = Vec::new();
+    visit(root, source, file_path, framework, &mut routes);
+    // Elsewhere handlers are referenced by name and left to `resolve`
+    if matches!(framework, "flask" | "fastapi" | "spring") {
+        for route in &mut routes {
+            route.handler_id = route
+                .handler
+                .as_deref()
+                .and_then(|handler| declared_handler(symbols, file_path, handler, route.line))
+                .map(|symbol| symbol.id.clone());
+        }
+    }
+    routes
+}
+
+/// Attributes handlers referenced by name (Express, axum) to a function or
+/// method of that name in `symbols`, preferring the route's own file, then
+/// a name only one symbol has.
+pub fn resolve(routes: &mut [Route], symbols: &[CodeSymbol]) {
+    for route in routes.iter_mut().filter(|route| route.handler_id.is_none()) {
+        let Some(handler) = route.handler.as_deref() else {
+            continue;
+        };
+        let name = handler.rsplit([':', '.']).next().unwrap_or(handler);
+        let candidates: Vec<&CodeSymbol> = symbols
+            .iter()
+            .filter(|symbol| matches!(symbol.symbol_type.as_str(), "function" | "method") && symbol.symbol_name == name)
+            .collect();
+        let found = candidates
+            .iter()
+            .find(|symbol| symbol.file_path == route.file_path)
+            .or_else(|| if candidates.len() == 1 { candidates.first() } else { None });
+        route.handler_id = found.map(|symbol| symbol.id.clone());
+    }
+}
+
+fn visit(node: Node, source: &str, file_path: &str, framework: &'static str, routes: &mut Vec<Route>) {
+    let found = match (framework, node.kind()) {
+        ("flask" | "fastapi", "decorated_definition") => python_routes(&node, source),
+        ("spring", "method_declaration") => spring_routes(&node, source),
+        ("express", "call_expression") => express_routes(&node, source),
+        ("axum", "call_expression") => axum_routes(&node, source),
+        _ => vec![],
+    };
+    routes.extend(found.into_iter().map(|(method, path, handler)| Route {
+        method,
+        path,
+        framework,
+        handler,
+        handler_id: None,
+        file_path: file_path.to_string(),
+        line: node.start_position().row as i32 + 1,
+    }));
+
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        visit(child, source, file_path, framework, routes);
+    }
+}
+
+type Found = (String, String, Option<String>);
+
+/// `@app.route(path, methods=[...])` (Flask) and `@router.get(path)`
+/// (FastAPI, Flask 2).
+fn python_routes(node: &Node, source: &str) -> Vec<Found> {
+    let Some(name) = node
+        .child_by_field_name("definition")
+        .filter(|definition| definition.kind() == "function_definition")
+        .and_then(|definition| definition.child_by_field_name("name"))
+    else {
+        return vec![];
+    };
+    let handler = text(&name, source).to_string();
+
+    let mut found = Vec::new();
+    let mut cursor = node.walk();
+    for decorator in node.named_children(&mut cursor).filter(|child| child.kind() == "decorator") {
+        let Some(call) = decorator.named_child(0).filter(|call| call.kind() == "call") else {
+            continue;
+        };
+        let Some(helper) = call
+            .child_by_field_name("function")
+            .filter(|function| function.kind() == "attribute")
+            .and_then(|function| function.child_by_field_name("attribute"))
+            .map(|attribute| text(&attribute, source))
+        else {
+            continue;
+        };
+        let Some(arguments) = call.child_by_field_name("arguments") else {
+            continue;
+        };
+        let mut cursor = arguments.walk();
+        let arguments: Vec<Node> = arguments.named_children(&mut cursor).collect();
+        let path = arguments
+            .iter()
+            .find(|argument| argument.kind() != "keyword_argument")
+            .copied()
+            .or_else(|| keyword_argument(&arguments, "path", source))
+            .and_then(|path| string_value(&path, source, "python"));
+        let Some(path) = path else {
+            continue;
+        };
+
+        let methods = match helper {
+            "route" | "api_route" => {
+                let listed: Vec<String> = keyword_argument(&arguments, "methods", source)
+                    .map(|methods| {
+                        let mut cursor = methods.walk();
+                        let listed: Vec<String> = methods
+                            .named_children(&mut cursor)
+                            .filter_map(|method| string_value(&method, source, "python"))
+                            .map(|method| method.to_uppercase())
+                            .collect();
+                        listed
+                    })
+                    .unwrap_or_default();
+                if listed.is_empty() {
+                    vec!["GET".to_string()]
+                } else {
+                    listed
+                }
+            }
+            helper if METHODS.contains(&helper) => vec![helper.to_uppercase()],
+            _ => continue,
+        };
+        found.extend(methods.into_iter().map(|method| (method, path.clone(), Some(handler.clone()))));
+    }
+    found
+}
+
+fn keyword_argument<'a>(arguments: &[Node<'a>], name: &str, source: &str) -> Option<Node<'a>> {
+    arguments
+        .iter()
+        .filter(|argument| argument.kind() == "keyword_argument")
+        .find(|argument| argument.child_by_field_name("name").is_some_and(|key| text(&key, source) == name))
+        .and_then(|argument| argument.child_by_field_name("value"))
+}
+
+/// Whether the module imports Flask or FastAPI, and which.
+fn python_framework(root: &Node, source: &str) -> Option<&'static str> {
+    let mut cursor = root.walk();
+    let mut framework = None;
+    for statement in root.named_children(&mut cursor) {
+        let module = match statement.kind() {
+            "import_from_statement" => statement.child_by_field_name("module_name").map(|module| text(&module, source)),
+            "import_statement" => statement.child_by_field_name("name").map(|name| text(&name, source)),
+            _ => None,
+        };
+        match module.and_then(|module| module.split('.').next()) {
+            Some("fastapi") => return Some("fastapi"),
+            Some("flask") => framework = Some("flask"),
+            _ => {}
+        }
+    }
+    framework
+}
+
+/// Spring `@GetMapping`-style and `@RequestMapping` annotations on a method,
+/// under the class's `@RequestMapping` prefix.
+fn spring_routes(node: &Node, source: &str) -> Vec<Found> {
+    let Some(name) = node.child_by_field_name("name") else {
+        return vec![];
+    };
+    let handler = text(&name, source).to_string();
+    let prefixes = node
+        .parent()
+        .filter(|body| body.kind() == "class_body")
+        .and_then(|body| body.parent())
+        .and_then(|class| spring_mapping(&class, source))
+        .map(|(_, paths, _)| paths)
+        .unwrap_or_default();
+    let Some((default_method, paths, methods)) = spring_mapping(node, source) else {
+        return vec![];
+    };
+    let methods = if !methods.is_empty() {
+        methods
+    } else {
+        vec![default_method.unwrap_or("ANY").to_string()]
+    };
+
+    let mut found = Vec::new();
+    for prefix in if prefixes.is_empty() { vec![String::new()] } else { prefixes } {
+        for path in if paths.is_empty() { vec![String::new()] } else { paths.clone() } {
+            for method in &methods {
+                found.push((method.clone(), join_paths(&prefix, &path), Some(handler.clone())));
+            }
+        }
+    }
+    found
+}
+
+/// The mapping annotation of a declaration: the method its name implies,
+/// its paths and its `method` attribute.
+fn spring_mapping(node: &Node, source: &str) -> Option<(Option<&'static str>, Vec<String>, Vec<String>)> {
+    let mut cursor = node.walk();
+    let modifiers = node.named_children(&mut cursor).find(|child| child.kind() == "modifiers")?;
+    let mut cursor = modifiers.walk();
+    for annotation in modifiers.named_children(&mut cursor) {
+        if !matches!(annotation.kind(), "annotation" | "marker_annotation") {
+            continue;
+        }
+        let Some(name) = annotation.child_by_field_name("name").map(|name| text(&name, source)) else {
+            continue;
+        };
+        // `@org.springframework...GetMapping` is written with a scoped name
+        let name = name.rsplit('.').next().unwrap_or(name);
+        let Some((_, default_method)) = SPRING_MAPPINGS.iter().find(|(mapping, _)| *mapping == name) else {
+            continue;
+        };
+
+        let (mut paths, mut methods) = (Vec::new(), Vec::new());
+        if let Some(arguments) = annotation.child_by_field_name("arguments") {
+            let mut cursor = arguments.walk();
+            for argument in arguments.named_children(&mut cursor) {
+                if argument.kind() != "element_value_pair" {
+                    paths.extend(spring_values(&argument, |value| string_value(value, source, "java")));
+                    continue;
+                }
+                let (Some(key), Some(value)) = (argument.child_by_field_name("key"), argument.child_by_field_name("value")) else {
+                    continue;
+                };
+                match text(&key, source) {
+                    "value" | "path" => paths.extend(spring_values(&value, |value| string_value(value, source, "java"))),
+                    "method" => methods.extend(spring_values(&value, |value| {
+                        // `RequestMethod.GET`
+                        let written = text(value, source);
+                        Some(written.rsplit('.').next().unwrap_or(written).to_string())
+                    })),
+                    _ => {}
+                }
+            }
+        }
+        return Some((*default_method, paths, methods));
+    }
+    None
+}
+
+/// An annotation value, or each element of an array of them.
+fn spring_values(value: &Node, read: impl Fn(&Node) -> Option<String>) -> Vec<String> {
+    if value.kind() != "element_value_array_initializer" {
+        return read(value).into_iter().collect();
+    }
+    let mut cursor = value.walk();
+    let values: Vec<String> = value.named_children(&mut cursor).filter_map(|element| read(&element)).collect();
+    values
+}
+
+/// `app.get(path, ...handlers)` and the like on any object; the last
+/// argument is the handler.
+fn express_routes(node: &Node, source: &str) -> Vec<Found> {
+    let Some(helper) = node
+        .child_by_field_name("function")
+        .filter(|function| function.kind() == "member_expression")
+        .and_then(|function| function.child_by_field_name("property"))
+        .map(|property| text(&property, source))
+    else {
+        return vec![];
+    };
+    let method = match helper {
+        "all" => "ANY".to_string(),
+        helper if METHODS.contains(&helper) => helper.to_uppercase(),
+        _ => return vec![],
+    };
+    let Some(arguments) = node.child_by_field_name("arguments") else {
+        return vec![];
+    };
+    let mut cursor = arguments.walk();
+    let arguments: Vec<Node> = arguments.named_children(&mut cursor).collect();
+    let (Some(path), Some(handler)) = (arguments.first(), arguments.last()) else {
+        return vec![];
+    };
+    // `map.get(key)` and `client.get(url)` pass no handler
+    if arguments.len() < 2 {
+        return vec![];
+    }
+    let Some(path) = string_value(path, source, "javascript") else {
+        return vec![];
+    };
+    let handler = matches!(handler.kind(), "identifier" | "member_expression").then(|| text(handler, source).to_string());
+    vec![(method, path, handler)]
+}
+
+/// Whether the file imports or requires `express`.
+fn imports_express(root: &Node, source: &str) -> bool {
+    let mut cursor = root.walk();
+    let imports = root.named_children(&mut cursor).any(|statement| match statement.kind() {
+        "import_statement" => statement
+            .child_by_field_name("source")
+            .and_then(|module| string_value(&module, source, "javascript"))
+            .is_some_and(|module| module == "express"),
+        "lexical_declaration" | "variable_declaration" => requires_express(&statement, source),
+        _ => false,
+    });
+    imports
+}
+
+fn requires_express(node: &Node, source: &str) -> bool {
+    if node.kind() == "call_expression"
+        && node.child_by_field_name("function").is_some_and(|function| text(&function, source) == "require")
+    {
+        return node
+            .child_by_field_name("arguments")
+            .and_then(|arguments| arguments.named_child(0))
+            .and_then(|module| string_value(&module, source, "javascript"))
+            .is_some_and(|module| module == "express");
+    }
+    let mut cursor = node.walk();
+    let found = node.named_children(&mut cursor).any(|child| requires_express(&child, source));
+    found
+}
+
+/// `.route(path, get(handler).post(other))` on an axum `Router`.
+fn axum_routes(node: &Node, source: &str) -> Vec<Found> {
+    let is_route = node
+        .child_by_field_name("function")
+        .filter(|function| function.kind() == "field_expression")
+        .and_then(|function| function.child_by_field_name("field"))
+        .is_some_and(|field| text(&field, source) == "route");
+    if !is_route {
+        return vec![];
+    }
+    let Some(arguments) = node.child_by_field_name("arguments") else {
+        return vec![];
+    };
+    let (Some(path), Some(router)) = (arguments.named_child(0), arguments.named_child(1)) else {
+        return vec![];
+    };
+    let Some(path) = string_value(&path, source, "rust") else {
+        return vec![];
+    };
+    method_router(&router, source)
+        .into_iter()
+        .map(|(method, handler)| (method, path.clone(), handler))
+        .collect()
+}
+
+/// Method and handler of each `get(handler)` in a method router chain such
+/// as `get(list).post(create)`.
+fn method_router(node: &Node, source: &str) -> Vec<(String, Option<String>)> {
+    if node.kind() != "call_expression" {
+        return vec![];
+    }
+    let (Some(function), Some(arguments)) = (node.child_by_field_name("function"), node.child_by_field_name("arguments")) else {
+        return vec![];
+    };
+    let (mut routes, helper) = match function.kind() {
+        // `get(list).post(create)`: the chain so far, then this method
+        "field_expression" => {
+            let (Some(inner), Some(field)) = (function.child_by_field_name("value"), function.child_by_field_name("field")) else {
+                return vec![];
+            };
+            let routes = method_router(&inner, source);
+            if routes.is_empty() {
+                return vec![];
+            }
+            (routes, text(&field, source))
+        }
+        // `get(list)`, `routing::get(list)`
+        "identifier" => (vec![], text(&function, source)),
+        "scoped_identifier" => match function.child_by_field_name("name") {
+            Some(name) => (vec![], text(&name, source)),
+            None => return vec![],
+        },
+        _ => return vec![],
+    };
+    let method = match helper {
+        "any" => "ANY".to_string(),
+        helper if METHODS.contains(&helper) => helper.to_uppercase(),
+        _ => return vec![],
+    };
+    let handler = arguments.named_child(0).map(|handler| text(&handler, source).to_string());
+    routes.push((method, handler));
+    routes
+}
+
+/// The text of a string literal, unless it interpolates.
+fn string_value(node: &Node, source: &str, language: &str) -> Option<String> {
+    if !STRING_KINDS.contains(&node.kind()) || has_interpolation(node) {
+        return None;
+    }
+    Some(unquote(text(node, source), language).to_string())
+}
+
+fn has_interpolation(node: &Node) -> bool {
+    let mut cursor = node.walk();
+    let interpolates = node
+        .named_children(&mut cursor)
+        .any(|child| matches!(child.kind(), "interpolation" | "template_substitution"));
+    interpolates
+}
+
+/// The symbol of a decorated or annotated handler: the nearest one of its
+/// name that ends after the route's first decorator or annotation.
+fn declared_handler<'a>(symbols: &'a [CodeSymbol], file_path: &str, handler: &str, line: i32) -> Option<&'a CodeSymbol> {
+    symbols
+        .iter()
+        .filter(|symbol| symbol.file_path == file_path && symbol.symbol_name == handler && symbol.line_end >= line)
+        .min_by_key(|symbol| (symbol.line_start - line).abs())
+}
+
+fn join_paths(prefix: &str, path: &str) -> String {
+    let joined = format!("{}/{}", prefix.trim_end_matches('/'), path.trim_start_matches('/'));
+    let joined = if joined.len() > 1 { joined.trim_end_matches('/').to_string() } else { joined };
+    if joined.starts_with('/') {
+        joined
+    } else {
+        format!("/{}", joined)
+    }
+}
+
+fn text<'a>(node: &Node, source: &'a str) -> &'a str {
+    &source[node.byte_range()]
+}
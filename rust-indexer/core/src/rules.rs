@@ -0,0 +1,147 @@
+use crate::extractor::{self, LanguageExtractor};
+use crate::symbol::CodeSymbol;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::sync::Arc;
+use tree_sitter::{Language, Node, Query, QueryCursor};
+
+/// One operator-defined mapping from a node kind to a symbol.
+///
+/// ```toml
+/// [[rules.java]]
+/// node = "enum_declaration"
+/// kind = "enum"
+/// exported = '(modifiers "public" @public)'
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExtractionRule {
+    /// Node kind the rule applies to (`enum_declaration`).
+    pub node: String,
+    /// `symbol_type` of the symbols it produces.
+    pub kind: String,
+    /// Field of the node holding the symbol's name.
+    #[serde(default = "default_name_field")]
+    pub name_field: String,
+    /// Query run against the node: the symbol is exported when one of its
+    /// captures lands on the node or a direct child. Without one, symbols
+    /// are unexported and have no visibility.
+    #[serde(default)]
+    pub exported: Option<String>,
+}
+
+fn default_name_field() -> String {
+    "name".to_string()
+}
+
+struct CompiledRule {
+    node: String,
+    kind: String,
+    name_field: String,
+    exported: Option<Query>,
+}
+
+/// A language's extractor with configured rules in front of it: nodes a
+/// rule covers become symbols as the rule says, everything else is left to
+/// the wrapped extractor.
+pub struct RuleExtractor {
+    base: Arc<dyn LanguageExtractor>,
+    rules: Vec<CompiledRule>,
+}
+
+impl RuleExtractor {
+    /// Compiles `rules` against the grammar of `base`, failing on a node
+    /// kind, field or query the grammar does not have.
+    pub fn new(base: Arc<dyn LanguageExtractor>, rules: &[ExtractionRule]) -> Result<Self> {
+        let grammar = base.grammar();
+        let rules = rules
+            .iter()
+            .map(|rule| compile(&grammar, rule).with_context(|| format!("Invalid {} rule for {}", base.name(), rule.node)))
+            .collect::<Result<_>>()?;
+        Ok(Self { base, rules })
+    }
+}
+
+fn compile(grammar: &Language, rule: &ExtractionRule) -> Result<CompiledRule> {
+    // The rule as a whole, so a node kind or field the grammar lacks is caught here
+    Query::new(grammar, &format!("({} {}: (_)) @symbol", rule.node, rule.name_field))?;
+
+    let exported = match &rule.exported {
+        Some(source) => {
+            let query = Query::new(grammar, source)?;
+            if query.capture_names().is_empty() {
+                bail!("The exported query captures nothing");
+            }
+            Some(query)
+        }
+        None => None,
+    };
+    Ok(CompiledRule {
+        node: rule.node.clone(),
+        kind: rule.kind.clone(),
+        name_field: rule.name_field.clone(),
+        exported,
+    })
+}
+
+impl LanguageExtractor for RuleExtractor {
+    fn name(&self) -> &str {
+        self.base.name()
+    }
+
+    fn grammar(&self) -> Language {
+        self.base.grammar()
+    }
+
+    fn detect(&self, file_path: &str) -> bool {
+        self.base.detect(file_path)
+    }
+
+    fn extract_symbol(&self, node: &Node, source: &str, file_path: &str) -> Result<Option<CodeSymbol>> {
+        let Some(rule) = self.rules.iter().find(|rule| rule.node == node.kind()) else {
+            return self.base.extract_symbol(node, source, file_path);
+        };
+        let Some(name_node) = node.child_by_field_name(&rule.name_field) else {
+            return Ok(None);
+        };
+        let name = name_node.utf8_text(source.as_bytes())?.to_string();
+
+        let exported = rule.exported.as_ref().is_some_and(|query| {
+            QueryCursor::new().matches(query, *node, source.as_bytes()).any(|found| {
+                found
+                    .captures
+                    .iter()
+                    .any(|capture| capture.node == *node || capture.node.parent() == Some(*node))
+            })
+        });
+        let visibility = rule.exported.as_ref().map(|_| if exported { "public" } else { "private" });
+        Ok(Some(extractor::symbol_at(node, name, source, file_path, &rule.kind, exported, visibility)))
+    }
+
+    fn import_kinds(&self) -> &[&str] {
+        self.base.import_kinds()
+    }
+
+    fn call_kinds(&self) -> &[(&str, &str)] {
+        self.base.call_kinds()
+    }
+
+    fn type_kinds(&self) -> &[&str] {
+        self.base.type_kinds()
+    }
+
+    fn supertypes(&self, node: &Node, source: &str) -> Vec<String> {
+        if self.rules.iter().any(|rule| rule.node == node.kind()) {
+            return extractor::heritage(node, source);
+        }
+        self.base.supertypes(node, source)
+    }
+
+        fn docstring(&self, node: &Node, source: &str) -> Option<String> {
+        self.base.docstring(node, source)
+    }
+
+    fn path_separator(&self) -> &str {
+        self.base.path_separator()
+    }
+}
@@ -0,0 +1,194 @@
+use crate::cache::{self, FileStamp};
+use crate::symbol::CodeSymbol;
+use anyhow::{Context, Result};
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub const DEFAULT_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Bumped whenever what is stored under a key changes meaning (such as the
+/// chunk hash function), so entries written by older builds are never read.
+const KEY_VERSION: &str = "v2";
+
+#[derive(Debug, Serialize)]
+pub struct SharedCacheStats {
+    pub name: &'static str,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    /// Lookups or writes that failed and fell back to the local path.
+    pub errors: u64,
+}
+
+/// Extracted symbols and chunk hashes shared through Redis, so replicas behind
+/// a load balancer reuse each other's work. Entries are keyed by file path and
+/// `FileStamp`, so an edited file never serves stale results, and partitioned
+/// by the current call's tenant. Symbols are also keyed by a fingerprint of
+/// the extraction config, so replicas configured differently, or a reload,
+/// never share them. Redis errors only cost a cache miss.
+pub struct RedisCache {
+    connection: ConnectionManager,
+    prefix: String,
+    ttl_secs: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    errors: AtomicU64,
+}
+
+impl RedisCache {
+    /// Reads `REDIS_CACHE_URL`; `None` when unset.
+    pub async fn from_env() -> Result<Option<Self>> {
+        let Some(url) = std::env::var("REDIS_CACHE_URL").ok().filter(|u| !u.is_empty()) else {
+            return Ok(None);
+        };
+        let client = redis::Client::open(url.as_str()).context("Invalid REDIS_CACHE_URL")?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .with_context(|| format!("Failed to connect to Redis at {}", url))?;
+        Ok(Some(Self {
+            connection,
+            prefix: std::env::var("REDIS_CACHE_PREFIX").unwrap_or_else(|_| "sherlock:indexer".to_string()),
+            ttl_secs: std::env::var("REDIS_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_TTL_SECS),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+        }))
+    }
+
+    /// `prefix:<version>`, or `prefix:tenant:<tenant>:<version>` inside a
+    /// tenant's partition.
+    fn partition_prefix(&self) -> String {
+        match cache::partition() {
+            Some(tenant) => format!("{}:tenant:{}:{}", self.prefix, tenant, KEY_VERSION),
+            None => format!("{}:{}", self.prefix, KEY_VERSION),
+        }
+    }
+
+    fn symbols_key(&self, file_path: &str, stamp: FileStamp, fingerprint: &str) -> String {
+        format!("{}:symbols:{}:{}:{}", self.partition_prefix(), fingerprint, stamp.key(), file_path)
+    }
+
+    fn hash_key(&self, file_path: &str, stamp: FileStamp, start: Option<i32>, end: Option<i32>) -> String {
+        let bound = |line: Option<i32>| line.map(|l| l.to_string()).unwrap_or_default();
+        format!("{}:hash:{}:{}-{}:{}", self.partition_prefix(), stamp.key(), bound(start), bound(end), file_path)
+    }
+
+    async fn get(&self, key: &str) -> Option<String> {
+        let mut connection = self.connection.clone();
+        match connection.get::<_, Option<String>>(key).await {
+            Ok(Some(value)) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(value)
+            }
+            Ok(None) => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            Err(e) => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+                tracing::warn!("Redis cache lookup failed: {}", e);
+                None
+            }
+        }
+    }
+
+    async fn set(&self, key: &str, value: String) {
+        let mut connection = self.connection.clone();
+        if let Err(e) = connection.set_ex::<_, _, ()>(key, value, self.ttl_secs).await {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!("Redis cache write failed: {}", e);
+        }
+    }
+
+    /// Symbols extracted under the config `fingerprint` stands for.
+    pub async fn get_symbols(&self, file_path: &str, stamp: FileStamp, fingerprint: &str) -> Option<Vec<CodeSymbol>> {
+        let value = self.get(&self.symbols_key(file_path, stamp, fingerprint)).await?;
+        serde_json::from_str(&value).ok()
+    }
+
+    pub async fn put_symbols(&self, file_path: &str, stamp: FileStamp, fingerprint: &str, symbols: &[CodeSymbol]) {
+        if let Ok(value) = serde_json::to_string(symbols) {
+            self.set(&self.symbols_key(file_path, stamp, fingerprint), value).await;
+        }
+    }
+
+    pub async fn get_hash(&self, file_path: &str, stamp: FileStamp, start: Option<i32>, end: Option<i32>) -> Option<String> {
+        self.get(&self.hash_key(file_path, stamp, start, end)).await
+    }
+
+    pub async fn put_hash(&self, file_path: &str, stamp: FileStamp, start: Option<i32>, end: Option<i32>, hash: &str) {
+        self.set(&self.hash_key(file_path, stamp, start, end), hash.to_string()).await;
+    }
+
+    /// Deletes entries for files under `repo_path`, or every entry, of every
+    /// tenant; returns how many went.
+    pub async fn purge(&self, repo_path: Option<&str>) -> Result<usize> {
+        let pattern = match repo_path {
+            Some(repo_path) => format!("{}:*:{}/*", escape_glob(&self.prefix), escape_glob(repo_path.trim_end_matches('/'))),
+            None => format!("{}:*", escape_glob(&self.prefix)),
+        };
+        let mut connection = self.connection.clone();
+        let keys: Vec<String> = {
+            let mut iter = connection.scan_match::<_, String>(&pattern).await?;
+            let mut keys = Vec::new();
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+            keys
+        };
+        for batch in keys.chunks(500) {
+            connection.del::<_, ()>(batch).await?;
+        }
+        Ok(keys.len())
+    }
+
+    /// Round trip to Redis, for readiness checks.
+    pub async fn ping(&self) -> Result<()> {
+        let mut connection = self.connection.clone();
+        redis::cmd("PING").query_async::<_, ()>(&mut connection).await?;
+        Ok(())
+    }
+
+    pub fn stats(&self) -> SharedCacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+        SharedCacheStats {
+            name: "redis",
+            hits,
+            misses,
+            hit_rate: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// `text` with the glob metacharacters of a Redis `MATCH` pattern escaped,
+/// so it only matches itself.
+fn escape_glob(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_metacharacters_are_escaped() {
+        assert_eq!(escape_glob("/repos/app"), "/repos/app");
+        assert_eq!(escape_glob("/repos/[a]*?"), "/repos/\\[a\\]\\*\\?");
+        assert_eq!(escape_glob("C:\\repos"), "C:\\\\repos");
+    }
+}
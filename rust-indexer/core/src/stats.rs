@@ -0,0 +1,225 @@
+use crate::parser::ParserService;
+use crate::repo;
+use crate::symbol::CodeSymbol;
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Extension to display-name mapping for programming and markup languages,
+/// mirroring what linguist counts towards a repository's language stats.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("js", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("ts", "TypeScript"),
+    ("tsx", "TSX"),
+    ("go", "Go"),
+    ("py", "Python"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("kts", "Kotlin"),
+    ("scala", "Scala"),
+    ("c", "C"),
+    ("h", "C"),
+    ("cpp", "C++"),
+    ("cc", "C++"),
+    ("cxx", "C++"),
+    ("hpp", "C++"),
+    ("cs", "C#"),
+    ("rb", "Ruby"),
+    ("php", "PHP"),
+    ("swift", "Swift"),
+    ("m", "Objective-C"),
+    ("sh", "Shell"),
+    ("bash", "Shell"),
+    ("sql", "SQL"),
+    ("html", "HTML"),
+    ("htm", "HTML"),
+    ("css", "CSS"),
+    ("scss", "SCSS"),
+    ("sass", "Sass"),
+    ("less", "Less"),
+    ("vue", "Vue"),
+    ("svelte", "Svelte"),
+    ("dart", "Dart"),
+    ("ex", "Elixir"),
+    ("exs", "Elixir"),
+    ("hs", "Haskell"),
+    ("lua", "Lua"),
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LanguageStat {
+    pub language: String,
+    pub files: usize,
+    pub bytes: u64,
+    pub lines: u64,
+    pub byte_percentage: f64,
+    pub line_percentage: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguageBreakdown {
+    pub languages: Vec<LanguageStat>,
+    pub total_bytes: u64,
+    pub total_lines: u64,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FileDocumentation {
+    pub file_path: String,
+    pub code_lines: u32,
+    pub comment_lines: u32,
+    pub blank_lines: u32,
+    pub comment_ratio: f64,
+    pub exported_symbols: usize,
+    pub documented_exported_symbols: usize,
+    pub documentation_coverage: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DocumentationReport {
+    pub files: Vec<FileDocumentation>,
+    pub code_lines: u64,
+    pub comment_lines: u64,
+    pub comment_ratio: f64,
+    pub exported_symbols: usize,
+    pub documented_exported_symbols: usize,
+    pub documentation_coverage: f64,
+    pub success: bool,
+}
+
+/// Fraction of `total` that is documented; a file with nothing exported is fully covered.
+pub fn coverage(documented: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        documented as f64 / total as f64
+    }
+}
+
+fn language_for(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, name)| *name)
+}
+
+/// Per-language byte and line totals for a repository, largest first.
+pub async fn language_breakdown(repo_path: &str) -> Result<LanguageBreakdown> {
+    let root = Path::new(repo_path);
+    if !root.is_dir() {
+        return Err(anyhow::anyhow!("Repository path is not a directory"));
+    }
+
+    let mut totals: HashMap<&'static str, (usize, u64, u64)> = HashMap::new();
+    for path in repo::files(root) {
+        let Some(language) = language_for(&path) else {
+            continue;
+        };
+        let Ok(content) = crate::fs::read(&path).await else {
+            continue;
+        };
+
+        let lines = content.iter().filter(|b| **b == b'\n').count() as u64
+            + u64::from(!content.is_empty() && !content.ends_with(b"\n"));
+        let entry = totals.entry(language).or_default();
+        entry.0 += 1;
+        entry.1 += content.len() as u64;
+        entry.2 += lines;
+    }
+
+    let total_bytes: u64 = totals.values().map(|t| t.1).sum();
+    let total_lines: u64 = totals.values().map(|t| t.2).sum();
+    let percentage = |part: u64, total: u64| {
+        if total == 0 {
+            0.0
+        } else {
+            (part as f64 / total as f64 * 10000.0).round() / 100.0
+        }
+    };
+
+    let mut languages: Vec<LanguageStat> = totals
+        .into_iter()
+        .map(|(language, (files, bytes, lines))| LanguageStat {
+            language: language.to_string(),
+            files,
+            bytes,
+            lines,
+            byte_percentage: percentage(bytes, total_bytes),
+            line_percentage: percentage(lines, total_lines),
+        })
+        .collect();
+    languages.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.language.cmp(&b.language)));
+
+    Ok(LanguageBreakdown {
+        languages,
+        total_bytes,
+        total_lines,
+        success: true,
+    })
+}
+
+/// Comment density and exported-symbol documentation coverage across a repository.
+pub async fn documentation_report(parser: &ParserService, repo_path: &str) -> Result<DocumentationReport> {
+    let root = Path::new(repo_path);
+    if !root.is_dir() {
+        return Err(anyhow::anyhow!("Repository path is not a directory"));
+    }
+
+    let mut files = Vec::new();
+    for path in repo::source_files(root, parser) {
+        let Some(path) = path.to_str() else {
+            continue;
+        };
+        match parser.file_documentation(path).await {
+            Ok(doc) => files.push(doc),
+            Err(e) => tracing::warn!("Skipping {}: {}", path, e),
+        }
+    }
+
+    let code_lines: u64 = files.iter().map(|f| f.code_lines as u64).sum();
+    let comment_lines: u64 = files.iter().map(|f| f.comment_lines as u64).sum();
+    let exported_symbols: usize = files.iter().map(|f| f.exported_symbols).sum();
+    let documented_exported_symbols: usize = files.iter().map(|f| f.documented_exported_symbols).sum();
+
+    Ok(DocumentationReport {
+        files,
+        code_lines,
+        comment_lines,
+        comment_ratio: if code_lines == 0 { 0.0 } else { comment_lines as f64 / code_lines as f64 },
+        exported_symbols,
+        documented_exported_symbols,
+        documentation_coverage: coverage(documented_exported_symbols, exported_symbols),
+        success: true,
+    })
+}
+
+/// How many symbols of a repository depend on each name, from their
+/// `dependencies`. Names are not resolved, so same-named symbols share a
+/// count.
+#[derive(Debug, Default)]
+pub struct ReferenceCounts(HashMap<String, usize>);
+
+impl ReferenceCounts {
+    pub fn from_symbols(symbols: &[CodeSymbol]) -> Self {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for name in symbols.iter().flat_map(|symbol| &symbol.dependencies) {
+            *counts.entry(name.clone()).or_default() += 1;
+        }
+        Self(counts)
+    }
+
+    /// Sets `reference_count` on `symbols`, which must still carry their
+    /// names as extracted.
+    pub fn annotate(&self, symbols: &mut [CodeSymbol]) {
+        for symbol in symbols {
+            symbol.reference_count = Some(self.0.get(&symbol.symbol_name).copied().unwrap_or(0));
+        }
+    }
+}
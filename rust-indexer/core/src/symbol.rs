@@ -0,0 +1,173 @@
+use crate::metrics::SymbolMetrics;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodeSymbol {
+    pub id: String,
+    pub symbol_name: String,
+    pub symbol_type: String, // "function", "class", "method", "struct", "enum", etc.
+    pub file_path: String,
+    pub line_start: i32,
+    pub line_end: i32,
+    pub signature: Option<String>,
+    pub dependencies: Vec<String>,
+    pub exported: bool,
+    pub visibility: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<SymbolMetrics>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_count: Option<usize>,
+    /// Metadata merged in from enrichment hooks (summary, labels, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrichment: Option<serde_json::Map<String, serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    /// Types this one extends or implements, as written (`Base<T>`,
+    /// `serde::Serialize`). For a Rust `impl Trait for Type` block, the trait.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub supertypes: Vec<String>,
+    /// Other symbols of the repository that call or use this one by name.
+    /// Only set where the whole repository is known (repository index runs,
+    /// usage reports).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference_count: Option<usize>,
+    /// Owners of the symbol's file under the repository's CODEOWNERS, where
+    /// the repository is known.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
+    /// Declared in vendored or third-party code (`vendor/`, `third_party/`,
+    /// `node_modules/`), where the repository is known.
+    #[serde(default)]
+    pub external: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodeChunk {
+    pub id: String,
+    pub file_path: String,
+    pub line_start: i32,
+    pub line_end: i32,
+    pub content: String,
+    pub hash: String,
+    pub language: Option<String>,
+    /// Qualified name of the innermost symbol enclosing the whole chunk.
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub imports: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_count: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enrichment: Option<serde_json::Map<String, serde_json::Value>>,
+    /// The file was not valid UTF-8 and was decoded with replacements or from
+    /// a legacy encoding.
+    #[serde(default)]
+    pub lossy_decoded: bool,
+}
+
+/// Which extracted symbols a caller wants; applied while walking the tree,
+/// before metrics and token counts are computed for them.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolFilter {
+    /// Symbol types to keep; any type when empty.
+    pub kinds: Vec<String>,
+    pub exported_only: bool,
+    pub min_lines: Option<i32>,
+}
+
+impl SymbolFilter {
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty() && !self.exported_only && self.min_lines.is_none()
+    }
+
+    pub fn matches(&self, symbol: &CodeSymbol) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(&symbol.symbol_type))
+            && (!self.exported_only || symbol.exported)
+            && !matches!(self.min_lines, Some(min) if symbol.line_end - symbol.line_start + 1 < min)
+    }
+}
+
+/// Span of an ERROR or MISSING node in a parse tree; lines are one-based,
+/// columns zero-based byte offsets within the line.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ErrorRange {
+    pub line_start: i32,
+    pub line_end: i32,
+    pub column_start: usize,
+    pub column_end: usize,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// Source the grammar could not make sense of (an ERROR node).
+    Error,
+    /// A token the parser had to assume, such as a missing `;` (a MISSING node).
+    Missing,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub message: String,
+    #[serde(flatten)]
+    pub range: ErrorRange,
+}
+
+/// Which parse tree nodes an AST dump includes.
+#[derive(Debug, Clone, Default)]
+pub struct AstFilter {
+    pub max_depth: Option<usize>,
+    /// Node kinds to keep; all when empty.
+    pub kinds: Vec<String>,
+    pub include_text: bool,
+}
+
+/// A named node of a parse tree.
+#[derive(Debug, Serialize)]
+pub struct AstNode {
+    pub kind: String,
+    /// Field of the parent this node fills (`name`, `body`, ...).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub range: ErrorRange,
+    pub is_error: bool,
+    pub is_missing: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    pub children: Vec<AstNode>,
+}
+
+/// A token the grammar's highlight query captured.
+#[derive(Debug, Serialize)]
+pub struct HighlightSpan {
+    /// Capture name from the query: `keyword`, `string`, `function.method`, ...
+    pub scope: String,
+    /// Offsets into the decoded text (see `CodeChunk::lossy_decoded`).
+    pub start_byte: usize,
+    pub end_byte: usize,
+    #[serde(flatten)]
+    pub range: ErrorRange,
+}
+
+/// A syntactic range enclosing the cursor.
+#[derive(Debug, Serialize)]
+pub struct SelectionRange {
+    /// Node kind: `call_expression`, `block`, `function_item`, ...
+    pub kind: String,
+    #[serde(flatten)]
+    pub range: ErrorRange,
+}
+
+/// A chunk as remembered by the caller from a previous chunking run.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChunkManifestEntry {
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_start: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_end: Option<i32>,
+}
@@ -0,0 +1,66 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Parsing done while serving one call, for callers that report it (the
+/// server puts it in response headers).
+#[derive(Debug, Default)]
+pub struct CallStats {
+    bytes_parsed: AtomicU64,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    language: Mutex<Option<String>>,
+}
+
+impl CallStats {
+    pub fn bytes_parsed(&self) -> u64 {
+        self.bytes_parsed.load(Ordering::Relaxed)
+    }
+
+    /// Parses (or extractions) answered from a cache.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Files parsed from disk.
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Language of the last file parsed or served from cache.
+    pub fn language(&self) -> Option<String> {
+        self.language.lock().unwrap().clone()
+    }
+}
+
+tokio::task_local! {
+    static CALL: Arc<CallStats>;
+}
+
+/// Runs `call`, recording the parsing it does into `stats`.
+pub async fn scope<F: Future>(stats: Arc<CallStats>, call: F) -> F::Output {
+    CALL.scope(stats, call).await
+}
+
+fn with_call(record: impl FnOnce(&CallStats)) {
+    let _ = CALL.try_with(|call| record(call));
+}
+
+/// Records a file parsed from disk for the current call, if any.
+pub fn parsed(language: &str, bytes: usize) {
+    with_call(|call| {
+        call.bytes_parsed.fetch_add(bytes as u64, Ordering::Relaxed);
+        call.misses.fetch_add(1, Ordering::Relaxed);
+        *call.language.lock().unwrap() = Some(language.to_string());
+    });
+}
+
+/// Records a parse (or extraction) answered from a cache.
+pub fn cache_hit(language: Option<&str>) {
+    with_call(|call| {
+        call.hits.fetch_add(1, Ordering::Relaxed);
+        if let Some(language) = language {
+            *call.language.lock().unwrap() = Some(language.to_string());
+        }
+    });
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tiktoken_rs::CoreBPE;
+
+/// Encoding used when a request does not name one.
+pub const DEFAULT_TOKENIZER: &str = "cl100k_base";
+
+/// A loaded BPE encoding, cheap to clone and share across requests.
+#[derive(Clone)]
+pub struct Tokenizer {
+    name: String,
+    bpe: Arc<CoreBPE>,
+}
+
+impl std::fmt::Debug for Tokenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tokenizer").field("name", &self.name).finish()
+    }
+}
+
+impl Tokenizer {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn count(&self, text: &str) -> usize {
+        self.bpe.encode_ordinary(text).len()
+    }
+
+    pub fn encode(&self, text: &str) -> Vec<usize> {
+        self.bpe.encode_ordinary(text)
+    }
+
+    pub fn decode(&self, tokens: Vec<usize>) -> Result<String> {
+        self.bpe.decode(tokens)
+    }
+}
+
+/// Lazily loads encodings by name; the BPE tables are large, so each is built once.
+#[derive(Default)]
+pub struct TokenizerRegistry {
+    loaded: Mutex<HashMap<String, Tokenizer>>,
+}
+
+impl TokenizerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves an encoding name (or an OpenAI model name) to a tokenizer.
+    pub fn get(&self, name: Option<&str>) -> Result<Tokenizer> {
+        let name = name.unwrap_or(DEFAULT_TOKENIZER);
+        let mut loaded = self.loaded.lock().unwrap();
+        if let Some(tokenizer) = loaded.get(name) {
+            return Ok(tokenizer.clone());
+        }
+
+        let bpe = match name {
+            "cl100k_base" => tiktoken_rs::cl100k_base()?,
+            "o200k_base" => tiktoken_rs::o200k_base()?,
+            "p50k_base" => tiktoken_rs::p50k_base()?,
+            "p50k_edit" => tiktoken_rs::p50k_edit()?,
+            "r50k_base" => tiktoken_rs::r50k_base()?,
+            model => tiktoken_rs::get_bpe_from_model(model)
+                .map_err(|_| anyhow::anyhow!("Unknown tokenizer: {}", model))?,
+        };
+
+        let tokenizer = Tokenizer {
+            name: name.to_string(),
+            bpe: Arc::new(bpe),
+        };
+        loaded.insert(name.to_string(), tokenizer.clone());
+        Ok(tokenizer)
+    }
+}
@@ -0,0 +1,182 @@
+use pyo3::exceptions::{PyFileNotFoundError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pythonize::{depythonize_bound, pythonize};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sherlock_indexer_core::cache::DEFAULT_PARSE_CACHE_ENTRIES;
+use sherlock_indexer_core::chunker::{ChunkSettings, ChunkStrategy, WindowUnit};
+use sherlock_indexer_core::error::{self, DEFAULT_MAX_FILE_BYTES};
+use sherlock_indexer_core::symbol::SymbolFilter;
+use sherlock_indexer_core::tokens::TokenizerRegistry;
+use sherlock_indexer_core::{ExtractOptions, ParserService};
+use tokio::runtime::Runtime;
+
+/// Keyword arguments of `Indexer.extract_symbols`; the same names as the
+/// service's extract request.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ExtractArgs {
+    language: Option<String>,
+    include_metrics: bool,
+    include_halstead: bool,
+    include_tokens: bool,
+    tokenizer: Option<String>,
+    kinds: Vec<String>,
+    exported_only: bool,
+    min_lines: Option<i32>,
+}
+
+/// Keyword arguments of `Indexer.chunk_file`; the same names as the service's
+/// chunk request.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ChunkArgs {
+    strategy: Option<ChunkStrategy>,
+    max_tokens: Option<usize>,
+    window_size: Option<usize>,
+    overlap: Option<usize>,
+    unit: Option<WindowUnit>,
+    tokenizer: Option<String>,
+    include_tokens: bool,
+    language: Option<String>,
+}
+
+/// Extracts symbols from and chunks files in-process, with the same logic as
+/// the indexer service. Parsed files stay cached until they change on disk.
+#[pyclass(module = "sherlock_indexer")]
+struct Indexer {
+    parser: ParserService,
+    tokenizers: TokenizerRegistry,
+    runtime: Runtime,
+}
+
+#[pymethods]
+impl Indexer {
+    #[new]
+    #[pyo3(signature = (cache_entries = DEFAULT_PARSE_CACHE_ENTRIES, max_file_bytes = DEFAULT_MAX_FILE_BYTES))]
+    fn new(cache_entries: usize, max_file_bytes: u64) -> PyResult<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to start runtime: {}", e)))?;
+
+        Ok(Self {
+            parser: ParserService::with_cache_capacity(cache_entries).with_max_file_bytes(max_file_bytes),
+            tokenizers: TokenizerRegistry::new(),
+            runtime,
+        })
+    }
+
+    /// Names of the loaded grammars.
+    fn languages(&self) -> Vec<String> {
+        self.parser.languages()
+    }
+
+    /// Language a path would be parsed as, or `None` when it is unsupported.
+    #[staticmethod]
+    fn detect_language(path: &str) -> Option<String> {
+        ParserService::detect_language(path)
+    }
+
+    /// Symbols defined in a file, as dicts shaped like the service's.
+    #[pyo3(signature = (path, **options))]
+    fn extract_symbols(&self, py: Python<'_>, path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+        let args: ExtractArgs = parse_args(options)?;
+        let tokenizer = if args.include_tokens {
+            Some(self.tokenizers.get(args.tokenizer.as_deref()).map_err(to_py_err)?)
+        } else {
+            None
+        };
+        let options = ExtractOptions {
+            include_metrics: args.include_metrics,
+            include_halstead: args.include_halstead,
+            tokenizer,
+            language: args.language,
+            filter: SymbolFilter {
+                kinds: args.kinds.iter().map(|kind| kind.trim().to_lowercase()).collect(),
+                exported_only: args.exported_only,
+                min_lines: args.min_lines,
+            },
+        };
+
+        let symbols = py
+            .allow_threads(|| self.runtime.block_on(self.parser.extract_symbols(path, &options)))
+            .map_err(to_py_err)?;
+        to_python(py, &symbols)
+    }
+
+    /// A file cut into chunks, as dicts shaped like the service's.
+    #[pyo3(signature = (path, **options))]
+    fn chunk_file(&self, py: Python<'_>, path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<PyObject> {
+        let args: ChunkArgs = parse_args(options)?;
+        let settings = ChunkSettings {
+            strategy: args.strategy,
+            max_tokens: args.max_tokens,
+            window_size: args.window_size,
+            overlap: args.overlap,
+            unit: args.unit,
+            tokenizer: args.tokenizer,
+        };
+        let mut options = settings.resolve(&self.tokenizers, args.include_tokens).map_err(to_py_err)?;
+        options.language = args.language;
+
+        let chunks = py
+            .allow_threads(|| self.runtime.block_on(self.parser.chunk_file(path, &options)))
+            .map_err(to_py_err)?;
+        to_python(py, &chunks)
+    }
+
+    /// Content hash of a file, or of its lines `start_line` to `end_line`
+    /// (one-based, inclusive).
+    #[pyo3(signature = (path, *, start_line = None, end_line = None))]
+    fn hash(&self, py: Python<'_>, path: &str, start_line: Option<i32>, end_line: Option<i32>) -> PyResult<String> {
+        py.allow_threads(|| self.runtime.block_on(self.parser.get_chunk_hash(path, start_line, end_line)))
+            .map_err(to_py_err)
+    }
+
+    /// ERROR and MISSING nodes in a file's parse tree.
+    #[pyo3(signature = (path, *, language = None))]
+    fn diagnostics(&self, py: Python<'_>, path: &str, language: Option<&str>) -> PyResult<PyObject> {
+        let diagnostics = py
+            .allow_threads(|| self.runtime.block_on(self.parser.diagnostics(path, language)))
+            .map_err(to_py_err)?;
+        to_python(py, &diagnostics)
+    }
+}
+
+/// Reads keyword arguments into `T`, rejecting unknown names and wrong types.
+fn parse_args<T: DeserializeOwned + Default>(options: Option<&Bound<'_, PyDict>>) -> PyResult<T> {
+    match options {
+        Some(options) => {
+            depythonize_bound(options.clone().into_any()).map_err(|e| PyValueError::new_err(e.to_string()))
+        }
+        None => Ok(T::default()),
+    }
+}
+
+fn to_python<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    Ok(pythonize(py, value)?)
+}
+
+/// Problems with the file or the request become `ValueError`, missing files
+/// `FileNotFoundError`, anything else `RuntimeError`.
+fn to_py_err(e: anyhow::Error) -> PyErr {
+    let message = format!("{:#}", e);
+    let not_found = e.chain().any(|cause| {
+        matches!(cause.downcast_ref::<std::io::Error>(), Some(io) if io.kind() == std::io::ErrorKind::NotFound)
+    });
+    if error::source_error(&e).is_some() {
+        PyValueError::new_err(message)
+    } else if not_found {
+        PyFileNotFoundError::new_err(message)
+    } else {
+        PyRuntimeError::new_err(message)
+    }
+}
+
+#[pymodule]
+fn sherlock_indexer(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Indexer>()?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use crate::export::{self, ExportKind, Exporter};
+use crate::symbol::ChunkManifestEntry;
+use anyhow::{anyhow, Context, Result};
+use object_store::aws::AmazonS3Builder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutPayload, WriteMultipart};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+const EXPORT_BUFFER: usize = 256;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ArtifactQuery {
+    /// Also upload `chunks.jsonl`; symbols and the manifest are always written.
+    #[serde(default)]
+    pub chunks: bool,
+}
+
+/// `manifest.json` of one artifact version, also returned by the export endpoint.
+#[derive(Debug, Serialize)]
+pub struct ArtifactManifest {
+    pub repo_path: String,
+    pub version: String,
+    /// Object keys written for this version.
+    pub objects: Vec<String>,
+    pub symbol_count: usize,
+    pub chunk_count: usize,
+    /// Chunk manifest per file, in the shape `/chunk-diff` accepts as `previous`.
+    pub files: BTreeMap<String, Vec<ChunkManifestEntry>>,
+    pub success: bool,
+}
+
+/// Versioned index artifacts in S3 or GCS, under
+/// `<prefix>/<repo>/<version>/{symbols.jsonl,chunks.jsonl,manifest.json}`,
+/// with `<prefix>/<repo>/latest.json` naming the newest complete version.
+pub struct ArtifactStore {
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+}
+
+impl ArtifactStore {
+    /// Reads `ARTIFACT_STORE_URL` (`s3://bucket/prefix` or `gs://bucket/prefix`);
+    /// credentials come from the usual AWS_* / GOOGLE_* variables. `None` when unset.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(url) = std::env::var("ARTIFACT_STORE_URL").ok().filter(|u| !u.is_empty()) else {
+            return Ok(None);
+        };
+        let (scheme, rest) = url.split_once("://").context("ARTIFACT_STORE_URL must be a URL")?;
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        let store: Arc<dyn ObjectStore> = match scheme {
+            "s3" => Arc::new(AmazonS3Builder::from_env().with_bucket_name(bucket).build()?),
+            "gs" => Arc::new(GoogleCloudStorageBuilder::from_env().with_bucket_name(bucket).build()?),
+            other => return Err(anyhow!("Unsupported ARTIFACT_STORE_URL scheme: {}", other)),
+        };
+        Ok(Some(Self {
+            store,
+            prefix: prefix.trim_matches('/').to_string(),
+        }))
+    }
+
+    fn key(&self, parts: &[&str]) -> ObjectPath {
+        let joined = parts.iter().map(|p| p.trim_matches('/')).collect::<Vec<_>>().join("/");
+        match self.prefix.is_empty() {
+            true => ObjectPath::from(joined),
+            false => ObjectPath::from(format!("{}/{}", self.prefix, joined)),
+        }
+    }
+
+    /// Exports `repo_path` as a new artifact version and points `latest.json` at it.
+    pub async fn publish(&self, exporter: Exporter, repo_path: &str, include_chunks: bool) -> Result<ArtifactManifest> {
+        let version = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default()
+            .to_string();
+        let mut objects = Vec::new();
+
+        let symbols_key = self.key(&[repo_path, &version, "symbols.jsonl"]);
+        let symbol_count = self
+            .upload_export(&exporter, repo_path, ExportKind::Symbols, Some(&symbols_key), |_| {})
+            .await?;
+        objects.push(symbols_key.to_string());
+
+        // Chunks are always walked for the manifest, but only uploaded on request
+        let chunks_key = self.key(&[repo_path, &version, "chunks.jsonl"]);
+        let mut files: BTreeMap<String, Vec<ChunkManifestEntry>> = BTreeMap::new();
+        let chunk_count = self
+            .upload_export(
+                &exporter,
+                repo_path,
+                ExportKind::Chunks,
+                include_chunks.then_some(&chunks_key),
+                |record| {
+                    let metadata = &record["metadata"];
+                    let Some(file_path) = metadata["file_path"].as_str() else {
+                        return;
+                    };
+                    files.entry(file_path.to_string()).or_default().push(ChunkManifestEntry {
+                        hash: record["hash"].as_str().unwrap_or_default().to_string(),
+                        id: record["id"].as_str().map(str::to_string),
+                        line_start: metadata["line_start"].as_i64().map(|l| l as i32),
+                        line_end: metadata["line_end"].as_i64().map(|l| l as i32),
+                    });
+                },
+            )
+            .await?;
+        if include_chunks {
+            objects.push(chunks_key.to_string());
+        }
+
+        let manifest_key = self.key(&[repo_path, &version, "manifest.json"]);
+        objects.push(manifest_key.to_string());
+        let manifest = ArtifactManifest {
+            repo_path: repo_path.to_string(),
+            version: version.clone(),
+            objects,
+            symbol_count,
+            chunk_count,
+            files,
+            success: true,
+        };
+        self.store
+            .put(&manifest_key, PutPayload::from(serde_json::to_vec(&manifest)?))
+            .await
+            .context("Failed to upload manifest")?;
+
+        let latest = serde_json::json!({ "version": version, "manifest": manifest_key.to_string() });
+        self.store
+            .put(&self.key(&[repo_path, "latest.json"]), PutPayload::from(serde_json::to_vec(&latest)?))
+            .await
+            .context("Failed to update latest.json")?;
+        tracing::info!(repo = %repo_path, version = %version, "Published index artifacts");
+        Ok(manifest)
+    }
+
+    /// Streams one export kind into `key` (when given), calling `inspect` on
+    /// every record; returns the record count.
+    async fn upload_export(
+        &self,
+        exporter: &Exporter,
+        repo_path: &str,
+        kind: ExportKind,
+        key: Option<&ObjectPath>,
+        mut inspect: impl FnMut(&serde_json::Value),
+    ) -> Result<usize> {
+        let (tx, mut rx) = mpsc::channel(EXPORT_BUFFER);
+        let export = export::export_repo(exporter.clone(), repo_path.to_string(), kind, false, tx);
+        let handle = tokio::spawn(export);
+
+        let mut writer = match key {
+            Some(key) => Some(WriteMultipart::new(self.store.put_multipart(key).await?)),
+            None => None,
+        };
+        let mut count = 0;
+        while let Some(line) = rx.recv().await {
+            let line = line?;
+            if let Ok(record) = serde_json::from_str(&line) {
+                inspect(&record);
+            }
+            if let Some(writer) = writer.as_mut() {
+                writer.wait_for_capacity(4).await?;
+                writer.write(line.as_bytes());
+            }
+            count += 1;
+        }
+        handle.await?;
+        if let Some(writer) = writer {
+            writer.finish().await?;
+        }
+        Ok(count)
+    }
+}
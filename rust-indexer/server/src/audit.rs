@@ -0,0 +1,199 @@
+use crate::auth::Caller;
+use anyhow::{Context, Result};
+use axum::{
+    body::Body,
+    extract::{MatchedPath, RawPathParams, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    RequestPartsExt,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+/// Largest JSON body read to find a `repo_path` the route does not carry
+/// (`POST /jobs/index`).
+const MAX_PEEKED_BODY: usize = 64 * 1024;
+
+/// One authenticated API call: who ran which operation on which paths.
+#[derive(Debug, Serialize)]
+pub struct AuditRecord {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub request_id: Option<String>,
+    /// Key name, or `None` when the API is open to anonymous callers.
+    pub caller: Option<String>,
+    pub method: String,
+    /// Route template, e.g. `/chunk/:repo_path/*file_path`.
+    pub operation: String,
+    pub repo_path: Option<String>,
+    pub file_path: Option<String>,
+    pub status: u16,
+}
+
+/// Where audit records go: `AUDIT_LOG_FILE` (JSONL, appended by a writer
+/// task, so requests never wait on the disk) or `AUDIT_LOG_URL` (one JSON
+/// POST per record).
+pub enum AuditLog {
+    File(mpsc::UnboundedSender<AuditRecord>),
+    Http { url: String, http: reqwest::Client },
+}
+
+impl AuditLog {
+    /// `None` when neither variable is set. Must run inside the runtime.
+    pub fn from_env() -> Result<Option<Self>> {
+        if let Some(path) = std::env::var("AUDIT_LOG_FILE").ok().filter(|p| !p.is_empty()) {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("Failed to open audit log {}", path))?;
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(append(tokio::fs::File::from_std(file), rx));
+            return Ok(Some(Self::File(tx)));
+        }
+        Ok(std::env::var("AUDIT_LOG_URL")
+            .ok()
+            .filter(|url| !url.is_empty())
+            .map(|url| Self::Http {
+                url,
+                http: reqwest::Client::new(),
+            }))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::File(_) => "file",
+            Self::Http { .. } => "http",
+        }
+    }
+
+    /// Writes a record; failures are logged, never surfaced to the caller.
+    fn write(&self, record: AuditRecord) {
+        match self {
+            Self::File(tx) => {
+                if tx.send(record).is_err() {
+                    tracing::error!("Failed to write audit record: writer has stopped");
+                }
+            }
+            Self::Http { url, http } => {
+                let request = http.post(url).json(&record);
+                tokio::spawn(async move {
+                    if let Err(e) = request.send().await.and_then(|r| r.error_for_status()) {
+                        tracing::error!("Failed to send audit record: {}", e);
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Appends records to the audit file as they arrive, one JSON line each.
+async fn append(mut file: tokio::fs::File, mut records: mpsc::UnboundedReceiver<AuditRecord>) {
+    while let Some(record) = records.recv().await {
+        let mut line = serde_json::to_string(&record).unwrap_or_default();
+        line.push('\n');
+        if let Err(e) = file.write_all(line.as_bytes()).await.and(file.flush().await) {
+            tracing::error!("Failed to write audit record: {}", e);
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BodyRepo {
+    repo_path: Option<String>,
+}
+
+/// Whether the body is small JSON worth reading for a `repo_path`.
+fn peekable(parts: &axum::http::request::Parts) -> bool {
+    let json = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    let length = parts
+        .headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    json && length.is_some_and(|length| length <= MAX_PEEKED_BODY)
+}
+
+/// Records every call that made it past authentication, with the repository
+/// taken from the route or, for routes without one, from a JSON body.
+pub async fn record(State(log): State<Arc<AuditLog>>, request: Request, next: Next) -> Response {
+    let (mut parts, mut body) = request.into_parts();
+    let operation = parts
+        .extensions
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| parts.uri.path().to_string());
+    let params = parts.extract::<RawPathParams>().await.ok();
+    let param = |name: &str| {
+        params
+            .as_ref()
+            .and_then(|params| params.iter().find(|(key, _)| *key == name))
+            .map(|(_, value)| value.to_string())
+    };
+    let mut repo_path = param("repo_path");
+    if repo_path.is_none() && peekable(&parts) {
+        let Ok(bytes) = axum::body::to_bytes(body, MAX_PEEKED_BODY).await else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+        repo_path = serde_json::from_slice::<BodyRepo>(&bytes).ok().and_then(|body| body.repo_path);
+        body = Body::from(bytes);
+    }
+    let file_path = param("file_path");
+    let caller = parts.extensions.get::<Caller>().map(|caller| caller.name.clone());
+    let request_id = parts
+        .headers
+        .get(crate::middleware::REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let method = parts.method.to_string();
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    log.write(AuditRecord {
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        request_id,
+        caller,
+        method,
+        operation,
+        repo_path,
+        file_path,
+        status: response.status().as_u16(),
+    });
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn repository_in_a_json_body_is_recorded() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let app = Router::new()
+            .route("/jobs/index", post(|body: String| async move { body }))
+            .route_layer(axum::middleware::from_fn_with_state(Arc::new(AuditLog::File(tx)), record));
+        let body = r#"{"repo_path":"/repos/app"}"#;
+        let request = Request::post("/jobs/index")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(Body::from(body))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        let echoed = axum::body::to_bytes(response.into_body(), MAX_PEEKED_BODY).await.unwrap();
+        assert_eq!(echoed, body.as_bytes());
+        let record = rx.recv().await.unwrap();
+        assert_eq!(record.operation, "/jobs/index");
+        assert_eq!(record.repo_path.as_deref(), Some("/repos/app"));
+    }
+}
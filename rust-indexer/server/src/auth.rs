@@ -0,0 +1,296 @@
+use crate::cache;
+use crate::config::ConfigHandle;
+use crate::jwt::JwtVerifier;
+use crate::repo;
+use axum::{
+    extract::{RawPathParams, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::Response,
+    RequestPartsExt,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// A static API key from the `[[api_keys]]` tables of the config file.
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKey {
+    pub name: String,
+    pub key: String,
+    /// Grants access to the `/admin` endpoints.
+    #[serde(default)]
+    pub admin: bool,
+    /// Tenant the key belongs to, tagged onto the vectors it indexes.
+    #[serde(default)]
+    pub tenant: Option<String>,
+    /// Repository roots the key may touch; any repository when empty.
+    #[serde(default)]
+    pub roots: Vec<String>,
+    /// Requests allowed per minute; unlimited when unset.
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Requests this key may have in flight at once; unlimited when unset.
+    #[serde(default)]
+    pub max_concurrent: Option<u32>,
+}
+
+impl std::fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("name", &self.name)
+            .field("admin", &self.admin)
+            .field("tenant", &self.tenant)
+            .field("roots", &self.roots)
+            .field("requests_per_minute", &self.requests_per_minute)
+            .field("max_concurrent", &self.max_concurrent)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The authenticated caller, stored in the request extensions.
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub name: String,
+    pub admin: bool,
+    pub tenant: Option<String>,
+    pub roots: Vec<String>,
+}
+
+impl Caller {
+    /// Whether `repo_path` lies under one of the caller's roots. Paths with
+    /// `..` components never do, so a root cannot be escaped.
+    pub fn may_access(&self, repo_path: &str) -> bool {
+        if self.roots.is_empty() {
+            return true;
+        }
+        if repo::has_parent_dir(repo_path) {
+            return false;
+        }
+        self.roots.iter().any(|root| repo::is_within(repo_path, root))
+    }
+}
+
+/// 403 unless the caller (if any) may access `repo_path`.
+pub fn authorize_repo(caller: Option<&Caller>, repo_path: &str) -> Result<(), StatusCode> {
+    match caller {
+        Some(caller) if !caller.may_access(repo_path) => {
+            tracing::warn!("Key {} is not allowed to access {}", caller.name, repo_path);
+            Err(StatusCode::FORBIDDEN)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Counters for one key, kept across config reloads.
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyUsage {
+    pub name: String,
+    pub requests: u64,
+    /// Requests turned away with a 429 by the rate or concurrency limit.
+    pub rejected: u64,
+    pub in_flight: u32,
+    #[serde(skip)]
+    window_start: Instant,
+    #[serde(skip)]
+    window_count: u32,
+}
+
+impl KeyUsage {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            requests: 0,
+            rejected: 0,
+            in_flight: 0,
+            window_start: Instant::now(),
+            window_count: 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageList {
+    pub keys: Vec<KeyUsage>,
+    pub success: bool,
+}
+
+/// Per-key request counters and limits, keyed by key name.
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    keys: Mutex<HashMap<String, KeyUsage>>,
+}
+
+impl UsageTracker {
+    /// Admits a request for the key or JWT subject `name`, or returns `None`
+    /// when it is over its rate or concurrency limit. The request counts as
+    /// in flight until the returned guard is dropped, even if its handler
+    /// panics.
+    fn admit(self: &Arc<Self>, name: &str, requests_per_minute: Option<u32>, max_concurrent: Option<u32>) -> Option<InFlight> {
+        let mut keys = self.keys.lock().unwrap();
+        let usage = keys.entry(name.to_string()).or_insert_with(|| KeyUsage::new(name));
+
+        if usage.window_start.elapsed() >= RATE_WINDOW {
+            usage.window_start = Instant::now();
+            usage.window_count = 0;
+        }
+        let over_rate = requests_per_minute.is_some_and(|limit| usage.window_count >= limit);
+        let over_concurrency = max_concurrent.is_some_and(|limit| usage.in_flight >= limit);
+        if over_rate || over_concurrency {
+            usage.rejected += 1;
+            return None;
+        }
+
+        usage.requests += 1;
+        usage.window_count += 1;
+        usage.in_flight += 1;
+        Some(InFlight {
+            tracker: self.clone(),
+            name: name.to_string(),
+        })
+    }
+
+    fn release(&self, name: &str) {
+        if let Some(usage) = self.keys.lock().unwrap().get_mut(name) {
+            usage.in_flight = usage.in_flight.saturating_sub(1);
+        }
+    }
+
+    pub fn list(&self) -> Vec<KeyUsage> {
+        let mut keys: Vec<_> = self.keys.lock().unwrap().values().cloned().collect();
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+        keys
+    }
+}
+
+struct InFlight {
+    tracker: Arc<UsageTracker>,
+    name: String,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.tracker.release(&self.name);
+    }
+}
+
+/// State for `authenticate`: the live key list and the usage kept per key.
+#[derive(Clone)]
+pub struct AuthState {
+    pub config: Arc<ConfigHandle>,
+    pub usage: Arc<UsageTracker>,
+    pub jwt: Arc<JwtVerifier>,
+}
+
+/// Key from `X-Api-Key` or `Authorization: Bearer <key>`.
+fn presented_key(request: &Request) -> Option<&str> {
+    let headers = request.headers();
+    headers
+        .get("x-api-key")
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+        })
+}
+
+/// Compares in time independent of where the inputs first differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Identifies the caller by API key or, with `[jwt]` configured, by a bearer
+/// JWT. With neither configured the API stays open to anonymous callers;
+/// otherwise a missing or unknown credential is a 401, and a key or JWT
+/// subject over its rate or concurrency limit gets a 429.
+pub async fn authenticate(
+    State(auth): State<AuthState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let config = auth.config.current();
+    let key = presented_key(&request).and_then(|presented| {
+        config
+            .api_keys
+            .iter()
+            .find(|k| constant_time_eq(k.key.as_bytes(), presented.as_bytes()))
+    });
+
+    match key {
+        Some(key) => {
+            let Some(_in_flight) = auth.usage.admit(&key.name, key.requests_per_minute, key.max_concurrent) else {
+                tracing::warn!("Key {} is over its request limits", key.name);
+                return Err(StatusCode::TOO_MANY_REQUESTS);
+            };
+            request.extensions_mut().insert(Caller {
+                name: key.name.clone(),
+                admin: key.admin,
+                tenant: key.tenant.clone(),
+                roots: key.roots.clone(),
+            });
+            Ok(next.run(request).await)
+        }
+        None => match (&config.jwt, presented_key(&request)) {
+            (Some(settings), Some(token)) => match auth.jwt.verify(settings, token).await {
+                Ok(caller) => {
+                    // Subjects are counted apart from keys of the same name
+                    let subject = format!("jwt:{}", caller.name);
+                    let Some(_in_flight) = auth.usage.admit(&subject, settings.requests_per_minute, settings.max_concurrent) else {
+                        tracing::warn!("Subject {} is over its request limits", caller.name);
+                        return Err(StatusCode::TOO_MANY_REQUESTS);
+                    };
+                    request.extensions_mut().insert(caller);
+                    Ok(next.run(request).await)
+                }
+                Err(e) => {
+                    tracing::warn!("Rejected bearer token: {:#}", e);
+                    Err(StatusCode::UNAUTHORIZED)
+                }
+            },
+            (None, _) if config.api_keys.is_empty() => Ok(next.run(request).await),
+            _ => Err(StatusCode::UNAUTHORIZED),
+        },
+    }
+}
+
+/// Checks the `:repo_path` (and `*file_path`) route parameters against the
+/// caller's roots.
+pub async fn restrict_to_roots(request: Request, next: Next) -> Result<Response, StatusCode> {
+    let (mut parts, body) = request.into_parts();
+    if let Ok(params) = parts.extract::<RawPathParams>().await {
+        let param = |name: &str| params.iter().find(|(key, _)| *key == name).map(|(_, value)| value);
+        if let Some(repo_path) = param("repo_path") {
+            let path = match param("file_path") {
+                Some(file_path) => repo::join(repo_path, file_path),
+                None => repo_path.to_string(),
+            };
+            authorize_repo(parts.extensions.get::<Caller>(), &path)?;
+        }
+    }
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
+
+/// Partitions the parse and shared caches by the caller's tenant for the
+/// rest of the request.
+pub async fn partition_caches(request: Request, next: Next) -> Response {
+    let tenant = request.extensions().get::<Caller>().and_then(|caller| caller.tenant.clone());
+    cache::partitioned(tenant, next.run(request)).await
+}
+
+/// Lets only callers holding an admin key through.
+pub async fn require_admin(request: Request, next: Next) -> Result<Response, StatusCode> {
+    match request.extensions().get::<Caller>() {
+        Some(caller) if caller.admin => Ok(next.run(request).await),
+        Some(caller) => {
+            tracing::warn!("Key {} is not allowed to use admin endpoints", caller.name);
+            Err(StatusCode::FORBIDDEN)
+        }
+        None => Err(StatusCode::UNAUTHORIZED),
+    }
+}
@@ -0,0 +1,94 @@
+use serde::Serialize;
+
+/// A grammar this build was compiled with.
+#[derive(Debug, Serialize)]
+pub struct GrammarInfo {
+    pub language: String,
+    /// tree-sitter ABI version the grammar was generated for.
+    pub abi_version: usize,
+    /// Why the grammar is disabled; absent when it is usable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Grammar ABI versions the tree-sitter runtime of this build can load.
+#[derive(Debug, Serialize)]
+pub struct AbiRange {
+    pub min: usize,
+    pub max: usize,
+}
+
+/// Optional features, so clients can check for one instead of assuming it
+/// from the release they were written against.
+#[derive(Debug, Serialize)]
+pub struct Features {
+    pub symbols: bool,
+    /// Call and type-usage dependencies on symbols (`/extract-deps`).
+    pub dependencies: bool,
+    /// `reference_count` on indexed symbols, and usage reports (`/stats/usage`).
+    pub reference_counts: bool,
+    /// CODEOWNERS owners on symbols and owner lookups (`/owners`).
+    pub code_owners: bool,
+    /// Supertypes on symbols and type hierarchies (`/hierarchy`).
+    pub hierarchy: bool,
+    /// Rust trait implementation lookups (`/traits`).
+    pub trait_impls: bool,
+    /// Interface implementations, declared or structural (`/implementations`).
+    pub implementations: bool,
+    /// Exported symbols by module (`/report/api-surface`).
+    pub api_surface: bool,
+    /// Exported-symbol diffs between git refs (`/report/api-diff`).
+    pub api_diff: bool,
+    /// String literals and named constants (`/literals`).
+    pub literals: bool,
+    /// HTTP endpoint catalogs from web framework declarations (`/routes`).
+    pub routes: bool,
+    pub chunking: bool,
+    pub chunk_diff: bool,
+    pub diagnostics: bool,
+    /// Parse tree dumps (`/ast`).
+    pub ast: bool,
+    pub highlighting: bool,
+    pub metrics: bool,
+    pub token_counts: bool,
+    /// Pagination, filtering and sorting on `/extract` and `/symbols`.
+    pub symbol_paging: bool,
+    /// Chunks are embedded when `embed` is requested.
+    pub embeddings: bool,
+    pub enrichment: bool,
+    /// Index runs upsert into a vector store.
+    pub indexing: bool,
+    /// Semantic search over indexed vectors.
+    pub search: bool,
+    pub artifacts: bool,
+    pub events: bool,
+    pub distributed_indexing: bool,
+    pub shared_cache: bool,
+    /// Whether any query packs are loaded (`GET /query-packs`).
+    pub query_packs: bool,
+    /// Whether extracted symbols go through `SYMBOL_POSTPROCESSORS`.
+    pub postprocessing: bool,
+}
+
+/// Limits a client should respect, as configured on this deployment.
+#[derive(Debug, Clone, Serialize)]
+pub struct Limits {
+    pub max_file_bytes: u64,
+    pub job_concurrency: usize,
+    /// Queued work beyond which batch requests get 429; unbounded when absent.
+    pub max_queue_depth: Option<usize>,
+    pub default_page_size: usize,
+    pub max_page_size: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Capabilities {
+    pub service: &'static str,
+    pub version: &'static str,
+    pub api_versions: &'static [u32],
+    pub grammars: Vec<GrammarInfo>,
+    pub grammar_abi: AbiRange,
+    pub features: Features,
+    pub limits: Limits,
+    pub success: bool,
+}
@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Checkpoints are rewritten at most this often while a job runs.
+const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A file a job already indexed, with the `FileStamp` it was indexed at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileCheckpoint {
+    pub stamp: String,
+    pub keys: Vec<String>,
+    pub symbol_count: usize,
+}
+
+/// Everything needed to restart a job where it stopped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: String,
+    pub repo_path: String,
+    pub tenant: Option<String>,
+    pub callback_url: Option<String>,
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    pub created_at: u64,
+    #[serde(default)]
+    pub files: HashMap<String, FileCheckpoint>,
+}
+
+/// Directory of in-flight job checkpoints (`JOB_STATE_DIR`), one JSON file per job.
+pub struct CheckpointStore {
+    dir: PathBuf,
+}
+
+impl CheckpointStore {
+    /// `None` when `JOB_STATE_DIR` is unset.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(dir) = std::env::var("JOB_STATE_DIR").ok().filter(|d| !d.is_empty()) else {
+            return Ok(None);
+        };
+        std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir))?;
+        Ok(Some(Self { dir: PathBuf::from(dir) }))
+    }
+
+    /// Starts checkpointing a job, writing its record right away.
+    pub fn open(&self, record: JobRecord) -> Arc<Checkpoint> {
+        let checkpoint = Arc::new(Checkpoint {
+            path: self.dir.join(format!("{}.json", record.id)),
+            record: Mutex::new(record),
+            last_saved: Mutex::new(Instant::now()),
+        });
+        checkpoint.save();
+        checkpoint
+    }
+
+    /// Jobs that were still queued or running when the process stopped.
+    pub fn unfinished(&self) -> Vec<JobRecord> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut records: Vec<JobRecord> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let record = std::fs::read(&path)
+                    .ok()
+                    .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+                if record.is_none() {
+                    tracing::warn!("Ignoring unreadable job checkpoint {}", path.display());
+                }
+                record
+            })
+            .collect();
+        records.sort_by_key(|record| record.created_at);
+        records
+    }
+}
+
+pub struct Checkpoint {
+    path: PathBuf,
+    record: Mutex<JobRecord>,
+    last_saved: Mutex<Instant>,
+}
+
+impl Checkpoint {
+    /// Keys stored for `file_path` by an earlier attempt, if the file is unchanged since.
+    pub fn completed(&self, file_path: &str, stamp: &str) -> Option<FileCheckpoint> {
+        let record = self.record.lock().unwrap();
+        record.files.get(file_path).filter(|file| file.stamp == stamp).cloned()
+    }
+
+    pub fn record(&self, file_path: &str, file: FileCheckpoint) {
+        self.record.lock().unwrap().files.insert(file_path.to_string(), file);
+        let due = self.last_saved.lock().unwrap().elapsed() >= SAVE_INTERVAL;
+        if due {
+            self.save();
+        }
+    }
+
+    /// Writes the record atomically (temp file, then rename); failures are logged.
+    pub fn save(&self) {
+        let bytes = match serde_json::to_vec(&*self.record.lock().unwrap()) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::warn!("Failed to serialize job checkpoint: {}", e);
+                return;
+            }
+        };
+        let tmp = self.path.with_extension("json.tmp");
+        let result = std::fs::write(&tmp, bytes).and_then(|_| std::fs::rename(&tmp, &self.path));
+        if let Err(e) = result {
+            tracing::warn!("Failed to write job checkpoint {}: {}", self.path.display(), e);
+        }
+        *self.last_saved.lock().unwrap() = Instant::now();
+    }
+
+    /// Drops the checkpoint once the job has finished, successfully or not.
+    pub fn finish(&self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed to remove job checkpoint {}: {}", self.path.display(), e);
+        }
+    }
+}
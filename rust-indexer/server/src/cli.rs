@@ -0,0 +1,272 @@
+use crate::config::ConfigHandle;
+use crate::error;
+use crate::grammars;
+use crate::parser::{ExtractOptions, ParserService};
+use crate::symbol::CodeSymbol;
+use crate::watch;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Serialize;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: sherlock-indexer [<command> <target> [options]]
+
+Without a command, runs the HTTP server.
+
+Commands:
+  extract <file>    Symbols defined in a file
+  index <dir>       Symbols of every supported file under a directory
+  hash <file>       Content hash of a file, or of a line range with --lines
+  search <query>    Symbols under --dir (default: .) whose name contains the query
+  watch <dir>       Prints an NDJSON event per symbol added, removed or changed
+                    as files under a directory change
+
+Options:
+  --language <name>       Parse `extract` input as this language
+  --lines <start>:<end>   One-based, inclusive line range for `hash`
+  --dir <dir>             Directory `search` looks in
+  --interval <ms>         How often `watch` polls for changes (default: 500)
+  --format <json|table>   Output format; a table on a terminal, JSON otherwise
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Json,
+    Table,
+}
+
+#[derive(Debug, Default)]
+struct Args {
+    command: String,
+    target: String,
+    language: Option<String>,
+    lines: Option<(i32, i32)>,
+    dir: Option<String>,
+    interval: Option<Duration>,
+    format: Option<Format>,
+}
+
+#[derive(Serialize)]
+struct HashOutput<'a> {
+    file_path: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_line: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<i32>,
+    hash: String,
+}
+
+/// Runs a CLI command given the arguments after the program name, returning
+/// the process exit code: 0 on success, 1 when the command fails and 2 on a
+/// usage error.
+pub async fn run(args: Vec<String>) -> i32 {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        print!("{}", USAGE);
+        return 0;
+    }
+    let args = match parse(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return 2;
+        }
+    };
+
+    // Warnings go to stderr so they never mix with the output
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(tracing::Level::WARN)
+        .init();
+
+    match execute(&args).await {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            1
+        }
+    }
+}
+
+fn parse(args: Vec<String>) -> Result<Args> {
+    let mut parsed = Args::default();
+    let mut positional = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.strip_prefix("--") else {
+            positional.push(arg);
+            continue;
+        };
+        let value = args.next().ok_or_else(|| anyhow!("Missing value for --{}", flag))?;
+        match flag {
+            "language" => parsed.language = Some(value),
+            "lines" => parsed.lines = Some(parse_lines(&value)?),
+            "dir" => parsed.dir = Some(value),
+            "interval" => {
+                let millis = value.parse().with_context(|| format!("Invalid interval {:?}", value))?;
+                parsed.interval = Some(Duration::from_millis(millis).max(Duration::from_millis(1)));
+            }
+            "format" => {
+                parsed.format = Some(match value.as_str() {
+                    "json" => Format::Json,
+                    "table" => Format::Table,
+                    other => bail!("Unknown format {:?}", other),
+                })
+            }
+            other => bail!("Unknown option --{}", other),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    parsed.command = positional.next().ok_or_else(|| anyhow!("Missing command"))?;
+    if !matches!(parsed.command.as_str(), "extract" | "index" | "hash" | "search" | "watch") {
+        bail!("Unknown command {:?}", parsed.command);
+    }
+    parsed.target = positional.next().ok_or_else(|| anyhow!("Missing argument for {}", parsed.command))?;
+    if let Some(extra) = positional.next() {
+        bail!("Unexpected argument {:?}", extra);
+    }
+    Ok(parsed)
+}
+
+/// `a:b` as a one-based, inclusive line range.
+fn parse_lines(value: &str) -> Result<(i32, i32)> {
+    let (start, end) = value.split_once(':').ok_or_else(|| anyhow!("--lines takes <start>:<end>"))?;
+    let start = start.trim().parse().with_context(|| format!("Invalid start line {:?}", start))?;
+    let end = end.trim().parse().with_context(|| format!("Invalid end line {:?}", end))?;
+    Ok((start, end))
+}
+
+async fn execute(args: &Args) -> Result<()> {
+    // Picks up the grammars of GRAMMAR_DIR and the extension mappings and
+    // extraction rules of INDEXER_CONFIG, as the server does
+    let grammars = grammars::from_env().context("Invalid GRAMMAR_DIR")?;
+    let config = ConfigHandle::from_env().context("Invalid INDEXER_CONFIG")?.current();
+    let max_file_bytes = std::env::var("MAX_FILE_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(error::DEFAULT_MAX_FILE_BYTES);
+    let mut parser = grammars
+        .into_iter()
+        .fold(ParserService::with_cache_capacity(0).with_max_file_bytes(max_file_bytes), ParserService::with_extractor)
+        .with_extensions(config.extensions.clone())
+        .with_include_generated(config.include_generated);
+    for (language, rules) in &config.rules {
+        parser = parser.with_rules(language, rules).context("Invalid extraction rules")?;
+    }
+    let format = args.format.unwrap_or(if std::io::stdout().is_terminal() {
+        Format::Table
+    } else {
+        Format::Json
+    });
+
+    match args.command.as_str() {
+        "extract" => {
+            let options = ExtractOptions {
+                language: args.language.clone(),
+                ..ExtractOptions::default()
+            };
+            let symbols = parser.extract_symbols(&args.target, &options).await?;
+            print_symbols(&symbols, format)
+        }
+        "index" => {
+            let symbols = parser.extract_repo_symbols(&args.target, &ExtractOptions::default()).await?;
+            print_symbols(&symbols, format)
+        }
+        "hash" => {
+            let (start_line, end_line) = args.lines.unzip();
+            let hash = parser.get_chunk_hash(&args.target, start_line, end_line).await?;
+            match format {
+                Format::Json => print_json(&HashOutput {
+                    file_path: &args.target,
+                    start_line,
+                    end_line,
+                    hash,
+                }),
+                Format::Table => {
+                    println!("{}", hash);
+                    Ok(())
+                }
+            }
+        }
+        "search" => {
+            let dir = args.dir.as_deref().unwrap_or(".");
+            let symbols = parser.extract_repo_symbols(dir, &ExtractOptions::default()).await?;
+            print_symbols(&search(symbols, &args.target), format)
+        }
+        "watch" => {
+            let interval = args.interval.unwrap_or(watch::DEFAULT_POLL_INTERVAL);
+            watch::run(&parser, &args.target, interval).await
+        }
+        _ => unreachable!("commands are checked while parsing"),
+    }
+}
+
+/// Symbols whose name or qualified name contains `query`, ignoring case:
+/// exact name matches first, then prefixes, then the rest.
+fn search(symbols: Vec<CodeSymbol>, query: &str) -> Vec<CodeSymbol> {
+    let query = query.to_lowercase();
+    let mut matches: Vec<(u8, CodeSymbol)> = symbols
+        .into_iter()
+        .filter_map(|symbol| {
+            let name = symbol.symbol_name.to_lowercase();
+            let qualified = symbol.qualified_name.as_deref().unwrap_or_default().to_lowercase();
+            let rank = if name == query {
+                0
+            } else if name.starts_with(&query) {
+                1
+            } else if name.contains(&query) || qualified.contains(&query) {
+                2
+            } else {
+                return None;
+            };
+            Some((rank, symbol))
+        })
+        .collect();
+    matches.sort_by(|(a_rank, a), (b_rank, b)| {
+        a_rank
+            .cmp(b_rank)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then_with(|| a.line_start.cmp(&b.line_start))
+    });
+    matches.into_iter().map(|(_, symbol)| symbol).collect()
+}
+
+fn print_symbols(symbols: &[CodeSymbol], format: Format) -> Result<()> {
+    if format == Format::Json {
+        return print_json(&symbols);
+    }
+
+    let rows: Vec<[String; 4]> = symbols
+        .iter()
+        .map(|symbol| {
+            [
+                symbol.symbol_type.clone(),
+                symbol.qualified_name.clone().unwrap_or_else(|| symbol.symbol_name.clone()),
+                format!("{}-{}", symbol.line_start, symbol.line_end),
+                symbol.file_path.clone(),
+            ]
+        })
+        .collect();
+    let header = ["KIND", "NAME", "LINES", "FILE"].map(String::from);
+    let mut widths = [0; 4];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(&rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
+    Ok(())
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
@@ -0,0 +1,204 @@
+use crate::auth::ApiKey;
+use crate::chunker::ChunkSettings;
+use crate::jwt::JwtSettings;
+use crate::language;
+use crate::parser::ParserService;
+use crate::rules::ExtractionRule;
+use crate::schedule;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// Operator configuration read from the TOML file named by `INDEXER_CONFIG`.
+///
+/// ```toml
+/// repo_roots = ["/repos"]
+///
+/// [extensions]
+/// pyx = "python"
+/// tpp = "cpp"
+///
+/// [chunking.java]
+/// strategy = "class"
+///
+/// [chunking.sql]
+/// strategy = "window"
+/// window_size = 40
+///
+/// [[rules.java]]
+/// node = "enum_declaration"
+/// kind = "enum"
+/// exported = '(modifiers "public" @public)'
+///
+/// [[api_keys]]
+/// name = "ops"
+/// key = "..."
+/// admin = true
+///
+/// [[api_keys]]
+/// name = "batch"
+/// key = "..."
+/// tenant = "search-team"
+/// roots = ["/repos/search"]
+/// requests_per_minute = 600
+/// max_concurrent = 4
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexerConfig {
+    /// Directories repositories must live under; every repository is
+    /// refused when empty.
+    #[serde(default)]
+    pub repo_roots: Vec<String>,
+    /// Extra extension to language mappings (`pyx = "python"`), taking
+    /// precedence over the built-in ones. Keys are matched without the dot,
+    /// case-insensitively.
+    #[serde(default)]
+    pub extensions: HashMap<String, String>,
+    /// Chunking defaults keyed by language name (`rust`, `go`, ...) or, for
+    /// files without a grammar, by extension (`sql`, `ipynb`, ...).
+    #[serde(default)]
+    pub chunking: HashMap<String, ChunkSettings>,
+    /// Extraction rules keyed by language name, applied before the built-in
+    /// extraction. Read at startup; a reload does not change them.
+    #[serde(default)]
+    pub rules: HashMap<String, Vec<ExtractionRule>>,
+    /// Keys accepted by the API; when empty only the admin endpoints are closed.
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+    /// Cron expression (with seconds) per repository path for periodic re-indexing.
+    #[serde(default)]
+    pub schedules: HashMap<String, String>,
+    /// Callback URL per repository path for finished index jobs that did not
+    /// name one themselves.
+    #[serde(default)]
+    pub job_callbacks: HashMap<String, String>,
+    /// URL prefixes a job's own `callback_url` must fall under (same scheme,
+    /// host and port, path below the prefix's); jobs naming any other URL are
+    /// refused, and with none listed only `job_callbacks` are used.
+    #[serde(default)]
+    pub callback_url_prefixes: Vec<String>,
+    /// Also accept SSO-issued bearer JWTs.
+    #[serde(default)]
+    pub jwt: Option<JwtSettings>,
+    /// Index generated files (`Code generated ... DO NOT EDIT`, `@generated`,
+    /// bundles with a source map); they are skipped by default.
+    #[serde(default)]
+    pub include_generated: bool,
+}
+
+impl IndexerConfig {
+    /// Path named by `INDEXER_CONFIG`, if any.
+    fn path_from_env() -> Option<PathBuf> {
+        std::env::var("INDEXER_CONFIG").ok().filter(|p| !p.is_empty()).map(PathBuf::from)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut config: Self = toml::from_str(&text).with_context(|| format!("Failed to parse {}", path.display()))?;
+        config.extensions = std::mem::take(&mut config.extensions)
+            .into_iter()
+            .map(|(ext, name)| (ext.trim_start_matches('.').to_lowercase(), name.to_lowercase()))
+            .collect();
+        for (ext, name) in &config.extensions {
+            if !language::is_supported(name) {
+                bail!("Unknown language {:?} for extension {:?}", name, ext);
+            }
+        }
+        config.rules = std::mem::take(&mut config.rules)
+            .into_iter()
+            .map(|(name, rules)| (name.to_lowercase(), rules))
+            .collect();
+        for name in config.rules.keys() {
+            if !language::is_supported(name) {
+                bail!("Unknown language {:?} for extraction rules", name);
+            }
+        }
+        for (name, settings) in &config.chunking {
+            if settings.max_tokens == Some(0) {
+                bail!("max_tokens for {:?} must be at least 1", name);
+            }
+        }
+        for (repo_path, cron) in &config.schedules {
+            schedule::parse(cron).with_context(|| format!("Invalid schedule for {}", repo_path))?;
+        }
+        Ok(config)
+    }
+
+    /// Configured chunking settings for a file, empty when none apply.
+    pub fn chunking_for(&self, file_path: &str) -> ChunkSettings {
+        self.chunking
+            .get(&language_key(file_path, &self.extensions))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// The live configuration. A reload swaps in a new snapshot, so requests
+/// already running finish with the one they started with.
+#[derive(Debug)]
+pub struct ConfigHandle {
+    path: Option<PathBuf>,
+    current: RwLock<Arc<IndexerConfig>>,
+}
+
+/// What a reload picked up.
+#[derive(Debug, Serialize)]
+pub struct ReloadSummary {
+    pub path: Option<String>,
+    pub chunking_languages: usize,
+    pub api_keys: usize,
+    pub success: bool,
+}
+
+impl ConfigHandle {
+    /// Loads the file named by `INDEXER_CONFIG`; an unset variable means defaults.
+    pub fn from_env() -> Result<Self> {
+        let path = IndexerConfig::path_from_env();
+        let config = match &path {
+            Some(path) => IndexerConfig::load(path)?,
+            None => IndexerConfig::default(),
+        };
+        Ok(Self {
+            path,
+            current: RwLock::new(Arc::new(config)),
+        })
+    }
+
+    pub fn current(&self) -> Arc<IndexerConfig> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Re-reads the config file and applies its extension mappings and
+    /// generated-file setting to `parser`; on error the current config stays
+    /// in place.
+    pub fn reload(&self, parser: &ParserService) -> Result<ReloadSummary> {
+        let config = match &self.path {
+            Some(path) => IndexerConfig::load(path)?,
+            None => IndexerConfig::default(),
+        };
+        let summary = ReloadSummary {
+            path: self.path.as_ref().map(|p| p.display().to_string()),
+            chunking_languages: config.chunking.len(),
+            api_keys: config.api_keys.len(),
+            success: true,
+        };
+        parser.set_extensions(config.extensions.clone());
+        parser.set_include_generated(config.include_generated);
+        *self.current.write().unwrap() = Arc::new(config);
+        Ok(summary)
+    }
+}
+
+/// The file's language, `extensions` mapping it first, or its lowercased
+/// extension when no grammar handles it.
+fn language_key(file_path: &str, extensions: &HashMap<String, String>) -> String {
+    let ext = Path::new(file_path).extension().map(|ext| ext.to_string_lossy().to_lowercase());
+    ext.as_deref()
+        .and_then(|ext| extensions.get(ext).cloned())
+        .or_else(|| ParserService::detect_language(file_path))
+        .unwrap_or_else(|| ext.unwrap_or_default())
+}
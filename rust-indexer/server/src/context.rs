@@ -0,0 +1,276 @@
+use crate::parser::ParserService;
+use crate::symbol::CodeSymbol;
+use crate::tokens::Tokenizer;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+/// A symbol's source together with how far it sits from the requested symbol.
+#[derive(Debug, Serialize, Clone)]
+pub struct ContextEntry {
+    pub symbol: CodeSymbol,
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docstring: Option<String>,
+    pub depth: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpandedContext {
+    pub symbol: ContextEntry,
+    pub dependencies: Vec<ContextEntry>,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PackedSnippet {
+    pub symbol_id: String,
+    pub symbol_name: String,
+    pub file_path: String,
+    pub line_start: i32,
+    pub line_end: i32,
+    /// `target` for requested symbols, `dependency` for symbols they reference.
+    pub role: String,
+    pub content: String,
+    pub token_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PackedContext {
+    pub snippets: Vec<PackedSnippet>,
+    pub total_tokens: usize,
+    pub token_budget: usize,
+    /// Symbols that were relevant but did not fit in the budget.
+    pub omitted: Vec<String>,
+    pub tokenizer: String,
+}
+
+/// Output format for symbol cards.
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CardFormat {
+    #[default]
+    Json,
+    Markdown,
+}
+
+/// Compact summary of a symbol for prompts and hover popups.
+#[derive(Debug, Serialize)]
+pub struct SymbolCard {
+    pub id: String,
+    pub qualified_name: String,
+    pub symbol_type: String,
+    pub file_path: String,
+    pub line_start: i32,
+    pub line_end: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docstring: Option<String>,
+    pub parameters: Vec<String>,
+    /// Repository symbols that reference this one.
+    pub callers: usize,
+    /// Repository symbols this one references.
+    pub callees: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolCardResponse {
+    pub card: SymbolCard,
+    pub success: bool,
+}
+
+impl SymbolCard {
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "### `{}`\n\n{} in `{}` (lines {}-{})\n",
+            self.qualified_name, self.symbol_type, self.file_path, self.line_start, self.line_end
+        );
+        if let Some(signature) = &self.signature {
+            out.push_str(&format!("\n```\n{}\n```\n", signature));
+        }
+        if let Some(doc) = &self.docstring {
+            out.push_str(&format!("\n{}\n", doc));
+        }
+        if !self.parameters.is_empty() {
+            out.push_str("\n**Parameters**\n\n");
+            for param in &self.parameters {
+                out.push_str(&format!("- `{}`\n", param));
+            }
+        }
+        out.push_str(&format!("\n**Callers:** {} · **Callees:** {}\n", self.callers, self.callees));
+        out
+    }
+}
+
+/// Picks the repository symbol a bare name most likely refers to, preferring
+/// definitions in the same file, then the same directory.
+fn resolve<'a>(
+    name: &str,
+    from: &CodeSymbol,
+    index: &HashMap<&str, Vec<&'a CodeSymbol>>,
+) -> Option<&'a CodeSymbol> {
+    let candidates = index.get(name)?;
+    let from_dir = Path::new(&from.file_path).parent();
+
+    candidates
+        .iter()
+        .find(|c| c.file_path == from.file_path)
+        .or_else(|| {
+            candidates
+                .iter()
+                .find(|c| Path::new(&c.file_path).parent() == from_dir)
+        })
+        .or_else(|| candidates.first())
+        .copied()
+}
+
+/// Breadth-first walk from `roots` through the symbols they reference, up to
+/// `max_depth` hops. Roots come first, then each layer in discovery order.
+pub async fn dependency_closure(
+    parser: &ParserService,
+    repo_symbols: &[CodeSymbol],
+    roots: &[&CodeSymbol],
+    max_depth: usize,
+) -> Result<Vec<ContextEntry>> {
+    let mut index: HashMap<&str, Vec<&CodeSymbol>> = HashMap::new();
+    for symbol in repo_symbols {
+        index.entry(symbol.symbol_name.as_str()).or_default().push(symbol);
+    }
+
+    let mut visited: HashSet<&str> = roots.iter().map(|s| s.id.as_str()).collect();
+    let mut queue: VecDeque<(&CodeSymbol, usize)> = roots.iter().map(|s| (*s, 0)).collect();
+    let mut entries = Vec::new();
+
+    while let Some((symbol, depth)) = queue.pop_front() {
+        let details = match parser.symbol_details(symbol).await {
+            Ok(details) => details,
+            Err(e) => {
+                tracing::warn!("Skipping {}: {}", symbol.id, e);
+                continue;
+            }
+        };
+
+        if depth < max_depth {
+            for name in &details.references {
+                if let Some(target) = resolve(name, symbol, &index) {
+                    if visited.insert(target.id.as_str()) {
+                        queue.push_back((target, depth + 1));
+                    }
+                }
+            }
+        }
+
+        entries.push(ContextEntry {
+            symbol: symbol.clone(),
+            source: details.source,
+            docstring: details.docstring,
+            depth,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Builds the card for `target`, counting callers across every file in
+/// `repo_symbols`. Files that fail to parse are skipped.
+pub async fn symbol_card(
+    parser: &ParserService,
+    repo_symbols: &[CodeSymbol],
+    target: &CodeSymbol,
+) -> Result<SymbolCard> {
+    let mut index: HashMap<&str, Vec<&CodeSymbol>> = HashMap::new();
+    let mut by_file: HashMap<&str, Vec<&CodeSymbol>> = HashMap::new();
+    for symbol in repo_symbols {
+        index.entry(symbol.symbol_name.as_str()).or_default().push(symbol);
+        by_file.entry(symbol.file_path.as_str()).or_default().push(symbol);
+    }
+
+    let details = parser.symbol_details(target).await?;
+    let callees: HashSet<&str> = details
+        .references
+        .iter()
+        .filter_map(|name| resolve(name, target, &index))
+        .filter(|callee| callee.id != target.id)
+        .map(|callee| callee.id.as_str())
+        .collect();
+
+    let mut callers = 0;
+    for (file_path, symbols) in &by_file {
+        let file_details = match parser.file_symbol_details(file_path, symbols).await {
+            Ok(details) => details,
+            Err(e) => {
+                tracing::warn!("Skipping {}: {}", file_path, e);
+                continue;
+            }
+        };
+        for (symbol, details) in symbols.iter().zip(&file_details) {
+            if symbol.id == target.id {
+                continue;
+            }
+            let calls_target = details
+                .references
+                .iter()
+                .any(|name| resolve(name, symbol, &index).is_some_and(|s| s.id == target.id));
+            if calls_target {
+                callers += 1;
+            }
+        }
+    }
+
+    Ok(SymbolCard {
+        id: target.id.clone(),
+        qualified_name: target.qualified_name.clone().unwrap_or_else(|| target.symbol_name.clone()),
+        symbol_type: target.symbol_type.clone(),
+        file_path: target.file_path.clone(),
+        line_start: target.line_start,
+        line_end: target.line_end,
+        signature: target.signature.clone(),
+        docstring: details.docstring,
+        parameters: details.parameters,
+        callers,
+        callees: callees.len(),
+    })
+}
+
+/// Fills the token budget with entries in order, skipping any that do not fit
+/// so that smaller, later snippets can still be included.
+pub fn pack(entries: Vec<ContextEntry>, token_budget: usize, tokenizer: &Tokenizer) -> PackedContext {
+    let mut snippets = Vec::new();
+    let mut omitted = Vec::new();
+    let mut total_tokens = 0;
+
+    for entry in entries {
+        let content = match &entry.docstring {
+            Some(doc) => format!("{}\n{}", doc, entry.source),
+            None => entry.source,
+        };
+        let token_count = tokenizer.count(&content);
+
+        if total_tokens + token_count > token_budget {
+            omitted.push(entry.symbol.id);
+            continue;
+        }
+
+        total_tokens += token_count;
+        snippets.push(PackedSnippet {
+            symbol_id: entry.symbol.id,
+            symbol_name: entry.symbol.symbol_name,
+            file_path: entry.symbol.file_path,
+            line_start: entry.symbol.line_start,
+            line_end: entry.symbol.line_end,
+            role: if entry.depth == 0 { "target" } else { "dependency" }.to_string(),
+            content,
+            token_count,
+        });
+    }
+
+    PackedContext {
+        snippets,
+        total_tokens,
+        token_budget,
+        omitted,
+        tokenizer: tokenizer.name().to_string(),
+    }
+}
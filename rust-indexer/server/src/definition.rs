@@ -0,0 +1,40 @@
+use crate::symbol::CodeSymbol;
+
+/// Definitions of `name` among a repository's symbols, best first.
+///
+/// A bare name (`new`) matches symbols of that name. A name with a path
+/// (`Parser::new`, `client.Client`) matches symbols whose qualified name
+/// ends with that path, so it disambiguates between same-named symbols;
+/// `::` and `.` are interchangeable. Whole qualified-name matches come
+/// first, then exported symbols, then the rest by file and line.
+pub fn candidates(symbols: Vec<CodeSymbol>, name: &str, kind: Option<&str>) -> Vec<CodeSymbol> {
+    let wanted = normalize(name.trim());
+    let has_path = wanted.contains('.');
+    let suffix = format!(".{}", wanted);
+
+    let mut matches: Vec<(bool, CodeSymbol)> = symbols
+        .into_iter()
+        .filter(|symbol| match kind {
+            Some(kind) => symbol.symbol_type.eq_ignore_ascii_case(kind),
+            None => true,
+        })
+        .filter_map(|symbol| {
+            let qualified = normalize(symbol.qualified_name.as_deref().unwrap_or(&symbol.symbol_name));
+            let exact = qualified == wanted;
+            let found = exact || if has_path { qualified.ends_with(&suffix) } else { symbol.symbol_name == wanted };
+            found.then_some((exact, symbol))
+        })
+        .collect();
+    matches.sort_by(|(a_exact, a), (b_exact, b)| {
+        b_exact
+            .cmp(a_exact)
+            .then_with(|| b.exported.cmp(&a.exported))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then_with(|| a.line_start.cmp(&b.line_start))
+    });
+    matches.into_iter().map(|(_, symbol)| symbol).collect()
+}
+
+fn normalize(name: &str) -> String {
+    name.replace("::", ".")
+}
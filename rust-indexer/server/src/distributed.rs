@@ -0,0 +1,117 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub const DEFAULT_SHARD_SIZE: usize = 200;
+/// A shard embeds and upserts every file in it, so workers get a long time.
+const SHARD_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Files of one repository a coordinator hands to a worker (`POST /admin/index/shard`).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShardRequest {
+    pub repo_path: String,
+    pub files: Vec<String>,
+    pub tenant: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShardFile {
+    pub file_path: String,
+    pub keys: Vec<String>,
+    pub symbol_count: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShardResponse {
+    pub files: Vec<ShardFile>,
+    /// Files the worker could not index.
+    pub failed: Vec<String>,
+    pub success: bool,
+}
+
+/// Indexer replicas a coordinator shards repository runs across. Workers
+/// read files from the same paths as the coordinator, so the repositories
+/// must be on storage they all mount.
+pub struct WorkerPool {
+    http: reqwest::Client,
+    workers: Vec<String>,
+    api_key: Option<String>,
+    shard_size: usize,
+}
+
+impl WorkerPool {
+    /// Reads `INDEX_WORKERS` (comma-separated base URLs), `INDEX_WORKER_API_KEY`
+    /// and `INDEX_SHARD_SIZE`; `None` when no workers are listed.
+    pub fn from_env() -> Result<Option<Self>> {
+        let workers: Vec<String> = std::env::var("INDEX_WORKERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+            .collect();
+        if workers.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            http: reqwest::Client::builder().timeout(SHARD_TIMEOUT).build()?,
+            workers,
+            api_key: std::env::var("INDEX_WORKER_API_KEY").ok().filter(|k| !k.is_empty()),
+            shard_size: std::env::var("INDEX_SHARD_SIZE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&size| size > 0)
+                .unwrap_or(DEFAULT_SHARD_SIZE),
+        }))
+    }
+
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn shards(&self, files: Vec<String>) -> Vec<Vec<String>> {
+        files.chunks(self.shard_size).map(<[String]>::to_vec).collect()
+    }
+
+    /// Sends a shard to worker `preferred` (modulo the pool size), then to the
+    /// others in turn; `None` when every worker failed it.
+    pub async fn index_shard(&self, preferred: usize, request: &ShardRequest) -> Option<ShardResponse> {
+        for offset in 0..self.workers.len() {
+            let worker = &self.workers[(preferred + offset) % self.workers.len()];
+            match self.send(worker, request).await {
+                Ok(response) => return Some(response),
+                Err(e) => tracing::warn!(
+                    worker = %worker,
+                    repo = %request.repo_path,
+                    files = request.files.len(),
+                    "Shard failed on worker: {:#}",
+                    e
+                ),
+            }
+        }
+        None
+    }
+
+    /// Workers that do not answer `/livez` within `timeout`, with the reason.
+    pub async fn unreachable(&self, timeout: Duration) -> Vec<(String, String)> {
+        let checks = self.workers.iter().map(|worker| async move {
+            let response = self.http.get(format!("{}/livez", worker)).timeout(timeout).send().await;
+            match response.map(|r| r.error_for_status()) {
+                Ok(Ok(_)) => None,
+                Ok(Err(e)) | Err(e) => Some((worker.clone(), e.to_string())),
+            }
+        });
+        futures::future::join_all(checks).await.into_iter().flatten().collect()
+    }
+
+    async fn send(&self, worker: &str, request: &ShardRequest) -> Result<ShardResponse> {
+        let mut builder = self.http.post(format!("{}/admin/index/shard", worker)).json(request);
+        if let Some(key) = &self.api_key {
+            builder = builder.bearer_auth(key);
+        }
+        let response = builder.send().await.context("Worker unreachable")?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Worker returned {}", response.status()));
+        }
+        response.json().await.context("Invalid shard response")
+    }
+}
@@ -0,0 +1,251 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Wire format spoken by the embedding endpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmbeddingFormat {
+    /// `POST {model, input: [..]}` returning `{data: [{index, embedding}]}`.
+    OpenAi,
+    /// `POST {texts: [..]}` returning `{embeddings: [[..]]}`.
+    Custom,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmbeddingConfig {
+    pub url: String,
+    pub api_key: Option<String>,
+    pub model: Option<String>,
+    pub format: EmbeddingFormat,
+    pub batch_size: usize,
+    pub max_retries: u32,
+    /// Upper bound on outgoing requests per second; unlimited when unset.
+    pub requests_per_second: Option<f64>,
+    pub timeout: Duration,
+}
+
+impl EmbeddingConfig {
+    /// Reads `EMBEDDING_*` environment variables; `None` when no endpoint is configured.
+    pub fn from_env() -> Option<Self> {
+        let url = std::env::var("EMBEDDING_URL").ok().filter(|u| !u.is_empty())?;
+
+        Some(Self {
+            url,
+            api_key: std::env::var("EMBEDDING_API_KEY").ok().filter(|k| !k.is_empty()),
+            model: std::env::var("EMBEDDING_MODEL").ok().filter(|m| !m.is_empty()),
+            format: match std::env::var("EMBEDDING_FORMAT").as_deref() {
+                Ok("custom") => EmbeddingFormat::Custom,
+                _ => EmbeddingFormat::OpenAi,
+            },
+            batch_size: env_parse("EMBEDDING_BATCH_SIZE").unwrap_or(64).max(1),
+            max_retries: env_parse("EMBEDDING_MAX_RETRIES").unwrap_or(3),
+            requests_per_second: env_parse::<f64>("EMBEDDING_REQUESTS_PER_SECOND").filter(|r| *r > 0.0),
+            timeout: Duration::from_secs(env_parse("EMBEDDING_TIMEOUT_SECS").unwrap_or(30)),
+        })
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok().and_then(|v| v.parse().ok())
+}
+
+#[derive(Serialize)]
+struct OpenAiRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<&'a str>,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Serialize)]
+struct CustomRequest<'a> {
+    texts: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct CustomResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// Batching, retrying and rate-limited client for an external embedding endpoint.
+pub struct EmbeddingClient {
+    config: EmbeddingConfig,
+    http: reqwest::Client,
+    next_request: Mutex<Instant>,
+}
+
+impl EmbeddingClient {
+    pub fn new(config: EmbeddingConfig) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .context("Failed to build embedding HTTP client")?;
+
+        Ok(Self {
+            config,
+            http,
+            next_request: Mutex::new(Instant::now()),
+        })
+    }
+
+    pub fn model(&self) -> Option<&str> {
+        self.config.model.as_deref()
+    }
+
+    /// Embeds `texts`, returning one vector per input in the same order.
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.config.batch_size) {
+            vectors.extend(self.embed_batch_with_retry(batch).await?);
+        }
+        Ok(vectors)
+    }
+
+    async fn embed_batch_with_retry(&self, batch: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut attempt = 0;
+        loop {
+            self.wait_for_rate_limit().await;
+            match self.embed_batch(batch).await {
+                Ok(vectors) => return Ok(vectors),
+                Err(e) if attempt < self.config.max_retries && is_transient(&e) => {
+                    attempt += 1;
+                    let backoff = Duration::from_millis(250 * 2u64.pow(attempt));
+                    tracing::warn!("Embedding request failed (attempt {}): {}; retrying in {:?}", attempt, e, backoff);
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Spaces requests evenly so the configured rate is never exceeded.
+    async fn wait_for_rate_limit(&self) {
+        let Some(rate) = self.config.requests_per_second else {
+            return;
+        };
+        let interval = Duration::from_secs_f64(1.0 / rate);
+
+        let mut next = self.next_request.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            tokio::time::sleep_until(*next).await;
+        }
+        *next = Instant::now().max(*next) + interval;
+    }
+
+    async fn embed_batch(&self, batch: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut request = self.http.post(&self.config.url);
+        if let Some(key) = &self.config.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let request = match self.config.format {
+            EmbeddingFormat::OpenAi => request.json(&OpenAiRequest {
+                model: self.config.model.as_deref(),
+                input: batch,
+            }),
+            EmbeddingFormat::Custom => request.json(&CustomRequest { texts: batch }),
+        };
+
+        let response = request.send().await?.error_for_status()?;
+
+        let vectors = match self.config.format {
+            EmbeddingFormat::OpenAi => {
+                let mut data = response.json::<OpenAiResponse>().await?.data;
+                data.sort_by_key(|d| d.index);
+                data.into_iter().map(|d| d.embedding).collect::<Vec<_>>()
+            }
+            EmbeddingFormat::Custom => response.json::<CustomResponse>().await?.embeddings,
+        };
+
+        if vectors.len() != batch.len() {
+            return Err(anyhow::anyhow!(
+                "Embedding endpoint returned {} vectors for {} inputs",
+                vectors.len(),
+                batch.len()
+            ));
+        }
+        Ok(vectors)
+    }
+}
+
+/// Whether a failed request may succeed when repeated: the connection
+/// failed, the service is rate limiting (429) or it had an internal error
+/// (5xx). Rejected requests and unreadable responses fail at once.
+fn is_transient(error: &anyhow::Error) -> bool {
+    let Some(error) = error.downcast_ref::<reqwest::Error>() else {
+        return false;
+    };
+    match error.status() {
+        Some(status) => status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+        None => error.is_connect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::post, Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Serves `statuses` in turn, then embeddings; returns its URL and hit count.
+    async fn endpoint(statuses: &'static [StatusCode]) -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = Router::new().route(
+            "/",
+            post(move || async move {
+                match statuses.get(counter.fetch_add(1, Ordering::SeqCst)) {
+                    Some(status) => Err(*status),
+                    None => Ok(Json(serde_json::json!({ "embeddings": [[1.0]] }))),
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, hits)
+    }
+
+    fn client(url: String) -> EmbeddingClient {
+        EmbeddingClient::new(EmbeddingConfig {
+            url,
+            api_key: None,
+            model: None,
+            format: EmbeddingFormat::Custom,
+            batch_size: 8,
+            max_retries: 3,
+            requests_per_second: None,
+            timeout: Duration::from_secs(5),
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried() {
+        let (url, hits) = endpoint(&[StatusCode::SERVICE_UNAVAILABLE]).await;
+        let vectors = client(url).embed(&["fn main() {}".to_string()]).await.unwrap();
+        assert_eq!(vectors, vec![vec![1.0]]);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn rejected_requests_fail_at_once() {
+        let (url, hits) = endpoint(&[StatusCode::BAD_REQUEST, StatusCode::BAD_REQUEST]).await;
+        assert!(client(url).embed(&["fn main() {}".to_string()]).await.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,34 @@
+use axum::http::StatusCode;
+use std::io::ErrorKind;
+
+pub use sherlock_indexer_core::error::*;
+
+/// Status code for an error from the parsing or indexing paths: 404 for
+/// missing files, query packs and git refs, 415 for unsupported languages and binary
+/// files, 422 for undecodable content, 400 for bad line ranges or language
+/// overrides, 413 for oversized files, 503 for languages whose grammar is
+/// disabled, 500 otherwise.
+pub fn status(error: &anyhow::Error) -> StatusCode {
+    for cause in error.chain() {
+        if let Some(error) = cause.downcast_ref::<SourceError>() {
+            return match error {
+                SourceError::NotADirectory | SourceError::UnknownQueryPack(_) | SourceError::UnknownRevision(_) => {
+                    StatusCode::NOT_FOUND
+                }
+                SourceError::UnsupportedLanguage | SourceError::BinaryFile => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                SourceError::InvalidLineRange { .. } | SourceError::UnknownLanguage(_) => StatusCode::BAD_REQUEST,
+                SourceError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+                SourceError::GrammarDisabled { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            };
+        }
+        if let Some(error) = cause.downcast_ref::<std::io::Error>() {
+            match error.kind() {
+                ErrorKind::NotFound => return StatusCode::NOT_FOUND,
+                ErrorKind::PermissionDenied => return StatusCode::FORBIDDEN,
+                ErrorKind::InvalidData => return StatusCode::UNPROCESSABLE_ENTITY,
+                _ => {}
+            }
+        }
+    }
+    StatusCode::INTERNAL_SERVER_ERROR
+}
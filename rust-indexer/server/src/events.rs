@@ -0,0 +1,188 @@
+use crate::chunker::content_hash;
+use crate::symbol::CodeSymbol;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Something downstream consumers (embedding workers, search updaters) may
+/// react to.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    FileIndexed {
+        repo_path: String,
+        file_path: String,
+        tenant: Option<String>,
+        symbol_count: usize,
+        chunk_count: usize,
+    },
+    /// Symbols of a file that differ from when it was last indexed by this
+    /// process; after a restart every symbol counts as added.
+    SymbolsChanged {
+        repo_path: String,
+        file_path: String,
+        added: Vec<String>,
+        modified: Vec<String>,
+        removed: Vec<String>,
+    },
+    JobFinished {
+        job_id: String,
+        repo_path: String,
+        state: crate::jobs::JobState,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        summary: Option<crate::indexing::IndexSummary>,
+    },
+    RepoDeleted {
+        repo_path: String,
+    },
+}
+
+impl Event {
+    fn kind(&self) -> &'static str {
+        match self {
+            Event::FileIndexed { .. } => "file_indexed",
+            Event::SymbolsChanged { .. } => "symbols_changed",
+            Event::JobFinished { .. } => "job_finished",
+            Event::RepoDeleted { .. } => "repo_deleted",
+        }
+    }
+
+    fn repo_path(&self) -> &str {
+        match self {
+            Event::FileIndexed { repo_path, .. }
+            | Event::SymbolsChanged { repo_path, .. }
+            | Event::JobFinished { repo_path, .. }
+            | Event::RepoDeleted { repo_path } => repo_path,
+        }
+    }
+}
+
+enum Transport {
+    Nats(async_nats::Client),
+    /// Kafka through a Confluent-compatible REST proxy.
+    Kafka { url: String, http: reqwest::Client },
+}
+
+/// Publishes events to `<EVENT_TOPIC_PREFIX>.<type>` (a NATS subject or Kafka topic).
+pub struct EventBus {
+    transport: Transport,
+    prefix: String,
+    /// Symbol fingerprints per file, for `symbols_changed`.
+    fingerprints: Mutex<HashMap<String, HashMap<String, String>>>,
+}
+
+impl EventBus {
+    /// Reads `EVENT_BUS` (`nats` or `kafka`) and its settings; `None` when unset.
+    pub async fn from_env() -> Result<Option<Self>> {
+        let transport = match std::env::var("EVENT_BUS").as_deref() {
+            Ok("nats") => {
+                let url = std::env::var("NATS_URL").context("NATS_URL must be set when EVENT_BUS=nats")?;
+                let client = async_nats::connect(&url)
+                    .await
+                    .with_context(|| format!("Failed to connect to NATS at {}", url))?;
+                Transport::Nats(client)
+            }
+            Ok("kafka") => Transport::Kafka {
+                url: std::env::var("KAFKA_REST_URL")
+                    .context("KAFKA_REST_URL must be set when EVENT_BUS=kafka")?
+                    .trim_end_matches('/')
+                    .to_string(),
+                http: reqwest::Client::new(),
+            },
+            Ok("") | Err(_) => return Ok(None),
+            Ok(other) => return Err(anyhow::anyhow!("Unknown EVENT_BUS: {}", other)),
+        };
+        Ok(Some(Self {
+            transport,
+            prefix: std::env::var("EVENT_TOPIC_PREFIX").unwrap_or_else(|_| "sherlock.indexer".to_string()),
+            fingerprints: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.transport {
+            Transport::Nats(_) => "nats",
+            Transport::Kafka { .. } => "kafka",
+        }
+    }
+
+    /// Publishes one event; failures are logged, never surfaced to the caller.
+    pub async fn publish(&self, event: Event) {
+        let topic = format!("{}.{}", self.prefix, event.kind());
+        if let Err(e) = self.send(&topic, &event).await {
+            tracing::warn!("Failed to publish {} event: {:#}", event.kind(), e);
+        }
+    }
+
+    async fn send(&self, topic: &str, event: &Event) -> Result<()> {
+        match &self.transport {
+            Transport::Nats(client) => {
+                let payload = serde_json::to_vec(event)?;
+                client.publish(topic.to_string(), payload.into()).await?;
+            }
+            Transport::Kafka { url, http } => {
+                let body = serde_json::json!({
+                    "records": [{ "key": event.repo_path(), "value": event }],
+                });
+                http.post(format!("{}/topics/{}", url, topic))
+                    .header(reqwest::header::CONTENT_TYPE, "application/vnd.kafka.json.v2+json")
+                    .json(&body)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Publishes `symbols_changed` for a freshly indexed file when its symbols
+    /// differ from the previous run; `source` is the file's full text.
+    pub async fn symbols_indexed(&self, repo_path: &str, file_path: &str, symbols: &[CodeSymbol], source: &str) {
+        let lines: Vec<&str> = source.lines().collect();
+        let current: HashMap<String, String> = symbols
+            .iter()
+            .map(|s| {
+                let start = (s.line_start - 1).max(0) as usize;
+                let end = (s.line_end.max(0) as usize).min(lines.len());
+                let body = lines.get(start..end).map(|l| l.join("\n")).unwrap_or_default();
+                (s.id.clone(), content_hash(&body))
+            })
+            .collect();
+
+        let previous = self
+            .fingerprints
+            .lock()
+            .unwrap()
+            .insert(file_path.to_string(), current.clone())
+            .unwrap_or_default();
+        let mut added: Vec<String> = current.keys().filter(|id| !previous.contains_key(*id)).cloned().collect();
+        let mut modified: Vec<String> = current
+            .iter()
+            .filter(|(id, hash)| previous.get(*id).is_some_and(|old| old != *hash))
+            .map(|(id, _)| id.clone())
+            .collect();
+        let mut removed: Vec<String> = previous.keys().filter(|id| !current.contains_key(*id)).cloned().collect();
+        if added.is_empty() && modified.is_empty() && removed.is_empty() {
+            return;
+        }
+        added.sort();
+        modified.sort();
+        removed.sort();
+
+        self.publish(Event::SymbolsChanged {
+            repo_path: repo_path.to_string(),
+            file_path: file_path.to_string(),
+            added,
+            modified,
+            removed,
+        })
+        .await;
+    }
+
+    /// Forgets fingerprints under `repo_path`, e.g. once the repository is deleted.
+    pub fn forget_repo(&self, repo_path: &str) {
+        let prefix = format!("{}/", repo_path.trim_end_matches('/'));
+        self.fingerprints.lock().unwrap().retain(|file, _| !file.starts_with(&prefix));
+    }
+}
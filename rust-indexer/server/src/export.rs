@@ -0,0 +1,231 @@
+use crate::chunker;
+use crate::config::IndexerConfig;
+use crate::parser::{ExtractOptions, ParserService};
+use crate::repo;
+use crate::symbol::{CodeChunk, CodeSymbol};
+use crate::tokens::TokenizerRegistry;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Which record kinds an export contains.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportKind {
+    #[default]
+    All,
+    Chunks,
+    Symbols,
+}
+
+/// One line of the JSONL export, shaped for embedding ingestion pipelines.
+#[derive(Debug, Serialize)]
+pub struct ExportRecord {
+    pub id: String,
+    pub text: String,
+    pub hash: String,
+    pub metadata: ExportMetadata,
+}
+
+/// Where a deduplicated chunk occurs.
+#[derive(Debug, Serialize)]
+pub struct ChunkLocation {
+    pub id: String,
+    pub file_path: String,
+    pub line_start: i32,
+    pub line_end: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportMetadata {
+    /// `chunk` or `symbol`.
+    pub kind: &'static str,
+    pub repo_path: String,
+    pub file_path: String,
+    pub line_start: i32,
+    pub line_end: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Qualified name of the symbol the record belongs to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<String>,
+    /// Every occurrence of this chunk when exporting with deduplication.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<ChunkLocation>,
+}
+
+impl ExportRecord {
+    fn from_chunk(repo_path: &str, chunk: CodeChunk) -> Self {
+        ExportRecord {
+            id: chunk.id,
+            text: chunk.content,
+            hash: chunk.hash,
+            metadata: ExportMetadata {
+                kind: "chunk",
+                repo_path: repo_path.to_string(),
+                file_path: chunk.file_path,
+                line_start: chunk.line_start,
+                line_end: chunk.line_end,
+                language: chunk.language,
+                symbol: chunk.symbol,
+                symbol_type: None,
+                signature: None,
+                imports: chunk.imports,
+                locations: Vec::new(),
+            },
+        }
+    }
+
+    fn location(&self) -> ChunkLocation {
+        ChunkLocation {
+            id: self.id.clone(),
+            file_path: self.metadata.file_path.clone(),
+            line_start: self.metadata.line_start,
+            line_end: self.metadata.line_end,
+        }
+    }
+
+    fn from_symbol(repo_path: &str, language: Option<String>, symbol: CodeSymbol, text: String) -> Self {
+        ExportRecord {
+            id: symbol.id,
+            hash: chunker::content_hash(&text),
+            text,
+            metadata: ExportMetadata {
+                kind: "symbol",
+                repo_path: repo_path.to_string(),
+                language,
+                file_path: symbol.file_path,
+                line_start: symbol.line_start,
+                line_end: symbol.line_end,
+                symbol: Some(symbol.qualified_name.unwrap_or(symbol.symbol_name)),
+                symbol_type: Some(symbol.symbol_type),
+                signature: symbol.signature,
+                imports: Vec::new(),
+                locations: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Shared services and settings an export runs with.
+#[derive(Clone)]
+pub struct Exporter {
+    pub parser: Arc<ParserService>,
+    pub config: Arc<IndexerConfig>,
+    pub tokenizers: Arc<TokenizerRegistry>,
+}
+
+/// Chunk and symbol records for one file. Chunks follow the configured settings
+/// for the file's language; symbol text is the docstring followed by the
+/// symbol's source, as in packed context.
+async fn file_records(exporter: &Exporter, repo_path: &str, file_path: &str, kind: ExportKind) -> Result<Vec<ExportRecord>> {
+    let parser = &exporter.parser;
+    let mut records = Vec::new();
+
+    if kind != ExportKind::Symbols {
+        let options = exporter.config.chunking_for(file_path).resolve(&exporter.tokenizers, false)?;
+        let chunks = parser.chunk_file(file_path, &options).await?;
+        records.extend(chunks.into_iter().map(|c| ExportRecord::from_chunk(repo_path, c)));
+    }
+
+    if kind != ExportKind::Chunks {
+        let symbols = parser.extract_symbols(file_path, &ExtractOptions::default()).await?;
+        let refs: Vec<&CodeSymbol> = symbols.iter().collect();
+        let details = parser.file_symbol_details(file_path, &refs).await?;
+        let language = parser.language_for(file_path);
+        for (symbol, details) in symbols.iter().zip(details) {
+            let text = match details.docstring {
+                Some(doc) => format!("{}\n{}", doc, details.source),
+                None => details.source,
+            };
+            records.push(ExportRecord::from_symbol(repo_path, language.clone(), symbol.clone(), text));
+        }
+    }
+
+    Ok(records)
+}
+
+/// Groups identical chunks by normalized hash, keeping the first occurrence
+/// as the record and every occurrence in its locations.
+#[derive(Default)]
+struct ChunkDeduper {
+    records: Vec<ExportRecord>,
+    by_hash: HashMap<String, usize>,
+}
+
+impl ChunkDeduper {
+    fn add(&mut self, record: ExportRecord) {
+        let location = record.location();
+        let key = chunker::normalized_hash(&record.text);
+        match self.by_hash.get(&key) {
+            Some(&index) => self.records[index].metadata.locations.push(location),
+            None => {
+                let mut record = record;
+                record.metadata.locations.push(location);
+                self.by_hash.insert(key, self.records.len());
+                self.records.push(record);
+            }
+        }
+    }
+}
+
+/// Serializes a record as one JSONL line and sends it; false once the receiver is gone.
+async fn send(tx: &mpsc::Sender<std::io::Result<String>>, record: &ExportRecord) -> bool {
+    match serde_json::to_string(record) {
+        Ok(json) => tx.send(Ok(json + "\n")).await.is_ok(),
+        Err(e) => {
+            tracing::warn!("Skipping record {}: {}", record.id, e);
+            true
+        }
+    }
+}
+
+/// Sends one serialized JSON line per record for every source file under
+/// `repo_path`. Files that fail to parse are skipped; the walk stops early
+/// once the receiver is dropped. With `dedup`, chunk records are held until
+/// the walk finishes and emitted once per distinct normalized hash.
+pub async fn export_repo(
+    exporter: Exporter,
+    repo_path: String,
+    kind: ExportKind,
+    dedup: bool,
+    tx: mpsc::Sender<std::io::Result<String>>,
+) {
+    let mut deduper = ChunkDeduper::default();
+
+    for path in repo::source_files(Path::new(&repo_path), &exporter.parser) {
+        let Some(path) = path.to_str() else {
+            continue;
+        };
+        let records = match file_records(&exporter, &repo_path, path, kind).await {
+            Ok(records) => records,
+            Err(e) => {
+                tracing::warn!("Skipping {}: {}", path, e);
+                continue;
+            }
+        };
+
+        for record in records {
+            if dedup && record.metadata.kind == "chunk" {
+                deduper.add(record);
+            } else if !send(&tx, &record).await {
+                return;
+            }
+        }
+    }
+
+    for record in &deduper.records {
+        if !send(&tx, record).await {
+            return;
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::distributed::WorkerPool;
+use crate::jobs::JobRegistry;
+use crate::parser::ParserService;
+use crate::sink::VectorSink;
+use serde::Serialize;
+use std::time::Duration;
+
+/// How long a single dependency may take to answer before it counts as down.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize)]
+pub struct DependencyStatus {
+    pub name: String,
+    pub ready: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// A problem that leaves the service usable, such as a disabled grammar.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+impl DependencyStatus {
+    fn new(name: &str, error: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            ready: error.is_none(),
+            error,
+            warning: None,
+        }
+    }
+}
+
+/// Checks everything a request may need: the grammars, the index job run
+/// slots and, when configured, the vector store, the shared Redis cache and
+/// the index workers. Disabled grammars, busy run slots and some unreachable
+/// workers are warnings, as requests still get served.
+pub async fn readiness(
+    parser: &ParserService,
+    sink: Option<&VectorSink>,
+    jobs: &JobRegistry,
+    workers: Option<&WorkerPool>,
+) -> Readiness {
+    let mut dependencies = Vec::new();
+
+    let disabled: Vec<String> = parser
+        .broken_grammars()
+        .into_iter()
+        .map(|language| format!("{} ({})", language, parser.grammar_error(&language).unwrap_or_default()))
+        .collect();
+    let mut grammars = DependencyStatus::new("grammars", None);
+    grammars.warning = (!disabled.is_empty()).then(|| format!("Disabled: {}", disabled.join(", ")));
+    dependencies.push(grammars);
+
+    if let Some(sink) = sink {
+        let error = match tokio::time::timeout(CHECK_TIMEOUT, sink.ping()).await {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(format!("{:#}", e)),
+            Err(_) => Some(format!("No answer within {}s", CHECK_TIMEOUT.as_secs())),
+        };
+        dependencies.push(DependencyStatus::new(&format!("store:{}", sink.name()), error));
+    }
+
+    if let Some(shared) = parser.shared_cache() {
+        let error = match tokio::time::timeout(CHECK_TIMEOUT, shared.ping()).await {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(format!("{:#}", e)),
+            Err(_) => Some(format!("No answer within {}s", CHECK_TIMEOUT.as_secs())),
+        };
+        dependencies.push(DependencyStatus::new("cache:redis", error));
+    }
+
+    let (free, total) = jobs.slots_free();
+    let mut slots = DependencyStatus::new("job_slots", None);
+    slots.warning = (free == 0).then(|| format!("All {} run slots busy; new jobs queue", total));
+    dependencies.push(slots);
+
+    if let Some(workers) = workers {
+        let unreachable = workers.unreachable(CHECK_TIMEOUT).await;
+        let listed: Vec<String> = unreachable.iter().map(|(worker, e)| format!("{} ({})", worker, e)).collect();
+        let status = if unreachable.len() == workers.size() {
+            DependencyStatus::new("workers", Some(format!("No worker reachable: {}", listed.join(", "))))
+        } else {
+            let mut status = DependencyStatus::new("workers", None);
+            status.warning = (!listed.is_empty()).then(|| format!("Unreachable: {}", listed.join(", ")));
+            status
+        };
+        dependencies.push(status);
+    }
+
+    Readiness {
+        ready: dependencies.iter().all(|d| d.ready),
+        dependencies,
+    }
+}
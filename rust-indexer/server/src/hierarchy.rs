@@ -0,0 +1,88 @@
+use crate::symbol::CodeSymbol;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// `symbol_type` values that declare a type others can extend or implement.
+const TYPE_KINDS: &[&str] = &["class", "interface", "struct", "enum", "trait", "type", "impl"];
+
+/// A type above or below the requested one.
+#[derive(Debug, Serialize, Clone)]
+pub struct TypeRelation {
+    pub name: String,
+    /// 1 for direct supertypes or subtypes, 2 for theirs, and so on.
+    pub depth: usize,
+    /// Where the type is declared; absent for types from outside the
+    /// repository (`Serialize`, `Exception`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<CodeSymbol>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Hierarchy {
+    pub symbol: CodeSymbol,
+    pub ancestors: Vec<TypeRelation>,
+    pub descendants: Vec<TypeRelation>,
+    pub success: bool,
+}
+
+/// Ancestors and descendants of `target` among a repository's symbols.
+///
+/// Types are matched by name, without their path or generic arguments, so
+/// `impl serde::Serialize for Config` makes `Config` a descendant of any
+/// `Serialize` and same-named types in different modules share relations.
+pub fn hierarchy(symbols: &[CodeSymbol], target: &CodeSymbol) -> Hierarchy {
+    let mut parents: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    for symbol in symbols {
+        for supertype in &symbol.supertypes {
+            let supertype = base_name(supertype);
+            parents.entry(symbol.symbol_name.as_str()).or_default().push(supertype);
+            children.entry(supertype).or_default().push(symbol.symbol_name.as_str());
+        }
+    }
+
+    // Prefer the declaration over an `impl` block of the same name
+    let mut declared: HashMap<&str, &CodeSymbol> = HashMap::new();
+    for symbol in symbols.iter().filter(|symbol| TYPE_KINDS.contains(&symbol.symbol_type.as_str())) {
+        let entry = declared.entry(symbol.symbol_name.as_str()).or_insert(symbol);
+        if entry.symbol_type == "impl" && symbol.symbol_type != "impl" {
+            *entry = symbol;
+        }
+    }
+
+    Hierarchy {
+        symbol: target.clone(),
+        ancestors: walk(&target.symbol_name, &parents, &declared),
+        descendants: walk(&target.symbol_name, &children, &declared),
+        success: true,
+    }
+}
+
+/// Breadth-first walk of `edges` from `start`, each type listed once at its
+/// shortest distance.
+fn walk(start: &str, edges: &HashMap<&str, Vec<&str>>, declared: &HashMap<&str, &CodeSymbol>) -> Vec<TypeRelation> {
+    let mut relations = Vec::new();
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((name, depth)) = queue.pop_front() {
+        for &next in edges.get(name).into_iter().flatten() {
+            if !seen.insert(next) {
+                continue;
+            }
+            relations.push(TypeRelation {
+                name: next.to_string(),
+                depth: depth + 1,
+                symbol: declared.get(next).map(|symbol| (*symbol).clone()),
+            });
+            queue.push_back((next, depth + 1));
+        }
+    }
+    relations
+}
+
+/// `Serialize` for `serde::Serialize`, `Base` for `Base<T>` or `Base[T]`.
+pub fn base_name(written: &str) -> &str {
+    let end = written.find(['<', '[', '(']).unwrap_or(written.len());
+    let path = written[..end].trim();
+    path.rsplit(['.', ':']).next().unwrap_or(path)
+}
@@ -0,0 +1,146 @@
+use crate::symbol::{CodeChunk, CodeSymbol};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+/// What a hook is called for.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookTarget {
+    Symbol,
+    Chunk,
+}
+
+/// One enrichment endpoint. It receives `{kind, item, content}` and answers
+/// with a JSON object (e.g. `{"summary": "...", "labels": ["io"]}`) that is
+/// merged into the item's metadata.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HookConfig {
+    pub name: String,
+    pub url: String,
+    pub target: HookTarget,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+/// Post-extraction enrichment hooks, run in configuration order.
+#[derive(Default)]
+pub struct HookPipeline {
+    hooks: Vec<HookConfig>,
+    http: reqwest::Client,
+}
+
+impl HookPipeline {
+    /// Reads hooks from `ENRICHMENT_HOOKS`, a JSON array of hook configs.
+    pub fn from_env() -> Result<Self> {
+        let hooks = match std::env::var("ENRICHMENT_HOOKS") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                serde_json::from_str(&raw).context("ENRICHMENT_HOOKS must be a JSON array of hooks")?
+            }
+            _ => Vec::new(),
+        };
+        Ok(Self {
+            hooks,
+            http: reqwest::Client::new(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    /// Enriches symbols in place; `source` is the full text of their file.
+    pub async fn enrich_symbols(&self, symbols: &mut [CodeSymbol], source: &str) {
+        if !self.hooks.iter().any(|h| h.target == HookTarget::Symbol) {
+            return;
+        }
+        let lines: Vec<&str> = source.lines().collect();
+        for symbol in symbols.iter_mut() {
+            let start = (symbol.line_start - 1).max(0) as usize;
+            let end = (symbol.line_end as usize).min(lines.len());
+            let content = lines.get(start..end).map(|l| l.join("\n")).unwrap_or_default();
+            let item = serde_json::to_value(&*symbol).unwrap_or(Value::Null);
+            let enrichment = self.run(HookTarget::Symbol, item, &content).await;
+            if !enrichment.is_empty() {
+                symbol.enrichment = Some(enrichment);
+            }
+        }
+    }
+
+    /// Enriches chunks in place.
+    pub async fn enrich_chunks(&self, chunks: &mut [CodeChunk]) {
+        if !self.hooks.iter().any(|h| h.target == HookTarget::Chunk) {
+            return;
+        }
+        for chunk in chunks.iter_mut() {
+            let mut item = serde_json::to_value(&*chunk).unwrap_or(Value::Null);
+            if let Some(fields) = item.as_object_mut() {
+                fields.remove("embedding");
+            }
+            let enrichment = self.run(HookTarget::Chunk, item, &chunk.content).await;
+            if !enrichment.is_empty() {
+                chunk.enrichment = Some(enrichment);
+            }
+        }
+    }
+
+    /// Calls every hook for `target` and merges their answers. A failing hook
+    /// is logged and skipped so enrichment never blocks indexing.
+    async fn run(&self, target: HookTarget, item: Value, content: &str) -> Map<String, Value> {
+        let mut merged = Map::new();
+        for hook in self.hooks.iter().filter(|h| h.target == target) {
+            match self.call(hook, target, &item, content).await {
+                Ok(output) => merge(&mut merged, output),
+                Err(e) => tracing::warn!("Enrichment hook {} failed: {}", hook.name, e),
+            }
+        }
+        merged
+    }
+
+    async fn call(&self, hook: &HookConfig, target: HookTarget, item: &Value, content: &str) -> Result<Map<String, Value>> {
+        let kind = match target {
+            HookTarget::Symbol => "symbol",
+            HookTarget::Chunk => "chunk",
+        };
+        let response = self
+            .http
+            .post(&hook.url)
+            .timeout(Duration::from_secs(hook.timeout_secs))
+            .json(&json!({ "kind": kind, "item": item, "content": content }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        match response.json::<Value>().await? {
+            Value::Object(output) => Ok(output),
+            _ => Err(anyhow::anyhow!("hook response is not a JSON object")),
+        }
+    }
+}
+
+/// Later hooks override earlier keys, except `labels`, which accumulate.
+fn merge(into: &mut Map<String, Value>, output: Map<String, Value>) {
+    for (key, value) in output {
+        match (key.as_str(), into.get_mut(&key), value) {
+            ("labels", Some(Value::Array(existing)), Value::Array(labels)) => {
+                for label in labels {
+                    if !existing.contains(&label) {
+                        existing.push(label);
+                    }
+                }
+            }
+            (_, _, value) => {
+                into.insert(key, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,729 @@
+use crate::cache::FileStamp;
+use crate::checkpoint::{Checkpoint, FileCheckpoint};
+use crate::config::IndexerConfig;
+use crate::distributed::{ShardFile, ShardRequest, ShardResponse, WorkerPool};
+use crate::embedding::EmbeddingClient;
+use crate::encoding;
+use crate::error::SourceError;
+use crate::events::{Event, EventBus};
+use crate::generated;
+use crate::hooks::HookPipeline;
+use crate::owners::CodeOwners;
+use crate::postprocess::PostProcessor;
+use crate::parser::{ExtractOptions, ParserService};
+use crate::priority::{WorkClass, WorkQueue};
+use crate::repo;
+use crate::sink::{VectorRecord, VectorSink};
+use crate::stats::ReferenceCounts;
+use crate::tokens::TokenizerRegistry;
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize, Default, Clone)]
+pub struct IndexSummary {
+    pub files_indexed: usize,
+    pub chunks_upserted: usize,
+    pub sink: String,
+    pub success: bool,
+}
+
+/// Outcome of the latest full index run of a repository.
+#[derive(Debug, Serialize, Clone)]
+pub struct IndexedRepo {
+    pub repo_path: String,
+    pub files_indexed: usize,
+    pub chunks_upserted: usize,
+    pub sink: String,
+    /// Unix timestamp (seconds) at which the run finished.
+    pub last_indexed_at: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepoList {
+    pub repos: Vec<IndexedRepo>,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepoDeletion {
+    pub repo_path: String,
+    /// Store the repository's vectors were removed from, if one is configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sink: Option<String>,
+    /// Whether the repository had been indexed since startup.
+    pub was_registered: bool,
+    pub success: bool,
+}
+
+/// Repositories fully indexed since startup.
+#[derive(Default)]
+pub struct IndexRegistry {
+    repos: RwLock<HashMap<String, IndexedRepo>>,
+}
+
+impl IndexRegistry {
+    pub fn record(&self, repo_path: &str, summary: &IndexSummary) {
+        let last_indexed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let entry = IndexedRepo {
+            repo_path: repo_path.to_string(),
+            files_indexed: summary.files_indexed,
+            chunks_upserted: summary.chunks_upserted,
+            sink: summary.sink.clone(),
+            last_indexed_at,
+        };
+        self.repos.write().unwrap().insert(repo_path.to_string(), entry);
+    }
+
+    /// Registered repositories ordered by path.
+    pub fn list(&self) -> Vec<IndexedRepo> {
+        let mut repos: Vec<IndexedRepo> = self.repos.read().unwrap().values().cloned().collect();
+        repos.sort_by(|a, b| a.repo_path.cmp(&b.repo_path));
+        repos
+    }
+
+    pub fn remove(&self, repo_path: &str) -> bool {
+        self.repos.write().unwrap().remove(repo_path).is_some()
+    }
+}
+
+/// Live counters of a repository index run, read by job status requests.
+#[derive(Debug, Default)]
+pub struct IndexProgress {
+    files_discovered: AtomicUsize,
+    files_done: AtomicUsize,
+    files_parsed: AtomicUsize,
+    symbols_extracted: AtomicUsize,
+    errors: AtomicUsize,
+    current_file: Mutex<Option<String>>,
+    /// Set once discovery is done; the ETA extrapolates from here.
+    started: OnceLock<Instant>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ProgressSnapshot {
+    pub files_discovered: usize,
+    /// Files handled so far, whether indexed or skipped.
+    pub files_done: usize,
+    pub files_parsed: usize,
+    pub symbols_extracted: usize,
+    pub errors: usize,
+    pub current_file: Option<String>,
+    pub elapsed_ms: u64,
+    /// Seconds left at the average pace so far, once a file has been handled.
+    pub eta_secs: Option<u64>,
+}
+
+impl IndexProgress {
+    fn discovered(&self, files: usize) {
+        self.files_discovered.store(files, Ordering::Relaxed);
+        self.started.get_or_init(Instant::now);
+    }
+
+    fn begin_file(&self, file_path: &str) {
+        *self.current_file.lock().unwrap() = Some(file_path.to_string());
+    }
+
+    fn end_file(&self, symbols: Option<usize>) {
+        match symbols {
+            Some(symbols) => {
+                self.files_parsed.fetch_add(1, Ordering::Relaxed);
+                self.symbols_extracted.fetch_add(symbols, Ordering::Relaxed);
+            }
+            None => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.files_done.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn finish(&self) {
+        *self.current_file.lock().unwrap() = None;
+    }
+
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        let files_discovered = self.files_discovered.load(Ordering::Relaxed);
+        let files_done = self.files_done.load(Ordering::Relaxed);
+        let elapsed = self.started.get().map(Instant::elapsed).unwrap_or_default();
+        let eta_secs = (files_done > 0).then(|| {
+            let remaining = files_discovered.saturating_sub(files_done) as f64;
+            (elapsed.as_secs_f64() / files_done as f64 * remaining).round() as u64
+        });
+        ProgressSnapshot {
+            files_discovered,
+            files_done,
+            files_parsed: self.files_parsed.load(Ordering::Relaxed),
+            symbols_extracted: self.symbols_extracted.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            current_file: self.current_file.lock().unwrap().clone(),
+            elapsed_ms: elapsed.as_millis() as u64,
+            eta_secs,
+        }
+    }
+}
+
+/// Settings and external services an index run goes through.
+pub struct IndexPipeline<'a> {
+    pub config: &'a IndexerConfig,
+    pub tokenizers: &'a TokenizerRegistry,
+    pub embedder: &'a EmbeddingClient,
+    pub sink: &'a VectorSink,
+    pub hooks: &'a HookPipeline,
+    pub postprocess: &'a PostProcessor,
+    pub tenant: Option<&'a str>,
+    pub events: Option<&'a EventBus>,
+    pub work: &'a Arc<WorkQueue>,
+    /// Replicas repository runs are sharded across, in coordinator mode.
+    pub workers: Option<&'a WorkerPool>,
+    /// Reference counts across the repository, set for repository runs.
+    pub references: Option<&'a ReferenceCounts>,
+    /// The repository's CODEOWNERS rules, when it has any.
+    pub owners: Option<&'a CodeOwners>,
+}
+
+/// What indexing one file stored.
+struct IndexedFile {
+    keys: Vec<String>,
+    symbol_count: usize,
+}
+
+/// Chunks, enriches, embeds and upserts one file, returning the keys now stored for it.
+async fn index_file(
+    parser: &ParserService,
+    pipeline: &IndexPipeline<'_>,
+    repo_path: &str,
+    file_path: &str,
+) -> Result<IndexedFile> {
+    let IndexPipeline { config, tokenizers, embedder, sink, hooks, postprocess, tenant, events, references, owners, .. } =
+        pipeline;
+    let started = Instant::now();
+    let mut symbols = parser.extract_symbols(file_path, &ExtractOptions::default()).await?;
+    if let Some(references) = references {
+        references.annotate(&mut symbols);
+    }
+    let file_owners = owners
+        .and_then(|owners| owners.owners_of(Path::new(file_path).strip_prefix(repo_path).ok()?))
+        .map(|(_, owners)| owners.to_vec())
+        .unwrap_or_default();
+    let external = Path::new(file_path).strip_prefix(repo_path).is_ok_and(repo::is_external);
+    for symbol in &mut symbols {
+        symbol.owners = file_owners.clone();
+        symbol.external = external;
+    }
+    let generated = generated::check(Path::new(file_path)).is_some();
+    postprocess.apply(&mut symbols).await;
+    let options = config.chunking_for(file_path).resolve(tokenizers, false)?;
+    let mut chunks = parser.chunk_file(file_path, &options).await?;
+    let symbol_count = symbols.len();
+
+    if !hooks.is_empty() {
+        let source = encoding::read(file_path).await?.text;
+        hooks.enrich_symbols(&mut symbols, &source).await;
+        hooks.enrich_chunks(&mut chunks).await;
+    }
+
+    let texts: Vec<String> = chunks.iter().map(|c| c.content.clone()).collect();
+    let vectors = embedder.embed(&texts).await?;
+
+    let records: Vec<VectorRecord> = chunks
+        .into_iter()
+        .zip(vectors)
+        .map(|(chunk, vector)| {
+            let symbols = symbols
+                .iter()
+                .filter(|s| s.line_start <= chunk.line_end && s.line_end >= chunk.line_start)
+                .cloned()
+                .collect();
+            VectorRecord {
+                repo_path: repo_path.to_string(),
+                tenant: tenant.map(str::to_string),
+                owners: file_owners.clone(),
+                generated,
+                external,
+                chunk,
+                vector,
+                symbols,
+            }
+        })
+        .collect();
+
+    sink.upsert(&records).await?;
+    tracing::info!(
+        repo = %repo_path,
+        file = %file_path,
+        language = parser.language_for(file_path).as_deref().unwrap_or("unknown"),
+        duration_ms = started.elapsed().as_millis() as u64,
+        symbol_count = symbol_count,
+        chunk_count = records.len(),
+        "Indexed file"
+    );
+    if let Some(events) = events {
+        if let Ok(source) = encoding::read(file_path).await.map(|decoded| decoded.text) {
+            events.symbols_indexed(repo_path, file_path, &symbols, &source).await;
+        }
+        events
+            .publish(Event::FileIndexed {
+                repo_path: repo_path.to_string(),
+                file_path: file_path.to_string(),
+                tenant: tenant.map(str::to_string),
+                symbol_count,
+                chunk_count: records.len(),
+            })
+            .await;
+    }
+    Ok(IndexedFile {
+        keys: records.iter().map(|r| r.key()).collect(),
+        symbol_count,
+    })
+}
+
+/// Re-indexes a single file and removes vectors for chunks it no longer has.
+pub async fn reindex_file(
+    parser: &ParserService,
+    pipeline: &IndexPipeline<'_>,
+    repo_path: &str,
+    file_path: &str,
+) -> Result<IndexSummary> {
+    let sink = pipeline.sink;
+    let owners = load_owners(repo_path);
+    let pipeline = &IndexPipeline {
+        owners: owners.as_ref(),
+        ..*pipeline
+    };
+    let keys = index_file(parser, pipeline, repo_path, file_path).await?.keys;
+    sink.delete_stale(pipeline.tenant, file_path, &keys).await?;
+
+    Ok(IndexSummary {
+        files_indexed: 1,
+        chunks_upserted: keys.len(),
+        sink: sink.name().to_string(),
+        success: true,
+    })
+}
+
+/// Indexes one file of a repository run in a batch slot, counting it in `progress`.
+async fn index_tracked(
+    parser: &ParserService,
+    pipeline: &IndexPipeline<'_>,
+    repo_path: &str,
+    path: &str,
+    progress: &IndexProgress,
+) -> Option<IndexedFile> {
+    // One batch slot per file, so interactive requests interleave with the run
+    let permit = pipeline.work.acquire(WorkClass::Batch).await;
+    progress.begin_file(path);
+    let result = index_file(parser, pipeline, repo_path, path).await;
+    drop(permit);
+    match result {
+        Ok(file) => {
+            progress.end_file(Some(file.symbol_count));
+            Some(file)
+        }
+        Err(e) => {
+            tracing::warn!("Skipping {}: {}", path, e);
+            progress.end_file(None);
+            None
+        }
+    }
+}
+
+/// The repository's CODEOWNERS rules; a file that cannot be read is logged
+/// and indexing goes on without owners.
+fn load_owners(repo_path: &str) -> Option<CodeOwners> {
+    match CodeOwners::load(Path::new(repo_path)) {
+        Ok(owners) => owners,
+        Err(e) => {
+            tracing::warn!("Failed to read CODEOWNERS of {}: {}", repo_path, e);
+            None
+        }
+    }
+}
+
+/// Reference counts over every symbol of a repository, for a repository run.
+async fn reference_counts(parser: &ParserService, repo_path: &str) -> Result<ReferenceCounts> {
+    let symbols = parser.extract_repo_symbols(repo_path, &ExtractOptions::default()).await?;
+    Ok(ReferenceCounts::from_symbols(&symbols))
+}
+
+/// Indexes a shard of files a coordinator sent; stale vectors are left for
+/// the coordinator to remove once every shard is in. Reference counts are
+/// taken over the whole repository, like on the coordinator.
+pub async fn index_shard(parser: &ParserService, pipeline: &IndexPipeline<'_>, request: ShardRequest) -> ShardResponse {
+    let references = match reference_counts(parser, &request.repo_path).await {
+        Ok(references) => Some(references),
+        Err(e) => {
+            tracing::warn!("Indexing shard without reference counts: {}", e);
+            None
+        }
+    };
+    let owners = load_owners(&request.repo_path);
+    let pipeline = &IndexPipeline {
+        references: references.as_ref(),
+        owners: owners.as_ref(),
+        ..*pipeline
+    };
+    let progress = IndexProgress::default();
+    let mut files = Vec::new();
+    let mut failed = Vec::new();
+    for path in request.files {
+        if repo::has_parent_dir(&path) || !repo::is_within(&path, &request.repo_path) {
+            tracing::warn!("Shard file {} is outside {}", path, request.repo_path);
+            failed.push(path);
+            continue;
+        }
+        match index_tracked(parser, pipeline, &request.repo_path, &path, &progress).await {
+            Some(file) => files.push(ShardFile {
+                file_path: path,
+                keys: file.keys,
+                symbol_count: file.symbol_count,
+            }),
+            None => failed.push(path),
+        }
+    }
+    ShardResponse {
+        files,
+        failed,
+        success: true,
+    }
+}
+
+/// Re-indexes every supported file and removes vectors for chunks, symbols
+/// and files that no longer exist in the repository. Files that fail to
+/// index keep the vectors they had.
+/// With a `checkpoint`, files an earlier attempt already indexed and that
+/// have not changed since are skipped, and each newly indexed file is recorded.
+/// With `workers`, the remaining files are sharded across them and only
+/// shards no worker could take are indexed here.
+pub async fn reindex_repo(
+    parser: &ParserService,
+    pipeline: &IndexPipeline<'_>,
+    repo_path: &str,
+    progress: &IndexProgress,
+    checkpoint: Option<&Checkpoint>,
+) -> Result<IndexSummary> {
+    let sink = pipeline.sink;
+    let root = Path::new(repo_path);
+    if !root.is_dir() {
+        return Err(SourceError::NotADirectory.into());
+    }
+    // Symbols are only counted against the repository as a whole, so they
+    // are extracted once up front; files indexed below hit the parse cache
+    let references = reference_counts(parser, repo_path).await?;
+    let owners = load_owners(repo_path);
+    let pipeline = &IndexPipeline {
+        references: Some(&references),
+        owners: owners.as_ref(),
+        ..*pipeline
+    };
+
+    let mut keys = Vec::new();
+    // Files that failed keep their vectors from an earlier run
+    let mut failed = Vec::new();
+    let mut files_indexed = 0;
+    let mut stamps: HashMap<String, Option<String>> = HashMap::new();
+    let mut pending = Vec::new();
+    let files = repo::source_files(root, parser);
+    progress.discovered(files.len());
+    for path in files {
+        let Some(path) = path.to_str() else {
+            progress.end_file(None);
+            continue;
+        };
+        let stamp = match checkpoint {
+            Some(_) => FileStamp::of(path).await.ok().map(|stamp| stamp.key()),
+            None => None,
+        };
+        if let Some(done) = checkpoint.zip(stamp.as_deref()).and_then(|(c, stamp)| c.completed(path, stamp)) {
+            files_indexed += 1;
+            progress.end_file(Some(done.symbol_count));
+            keys.extend(done.keys);
+            continue;
+        }
+        stamps.insert(path.to_string(), stamp);
+        pending.push(path.to_string());
+    }
+
+    let mut record = |path: &str, file: IndexedFile| {
+        files_indexed += 1;
+        if let (Some(checkpoint), Some(Some(stamp))) = (checkpoint, stamps.remove(path)) {
+            checkpoint.record(
+                path,
+                FileCheckpoint {
+                    stamp,
+                    keys: file.keys.clone(),
+                    symbol_count: file.symbol_count,
+                },
+            );
+        }
+        keys.extend(file.keys);
+    };
+
+    match pipeline.workers {
+        Some(workers) => {
+            let tenant = pipeline.tenant.map(str::to_string);
+            let mut shards = stream::iter(workers.shards(pending).into_iter().enumerate())
+                .map(|(i, files)| {
+                    let request = ShardRequest {
+                        repo_path: repo_path.to_string(),
+                        files,
+                        tenant: tenant.clone(),
+                    };
+                    async move { (workers.index_shard(i, &request).await, request.files) }
+                })
+                .buffer_unordered(workers.size());
+            while let Some((response, files)) = shards.next().await {
+                let Some(response) = response else {
+                    tracing::warn!(repo = %repo_path, files = files.len(), "No worker took shard; indexing locally");
+                    for path in files {
+                        match index_tracked(parser, pipeline, repo_path, &path, progress).await {
+                            Some(file) => record(&path, file),
+                            None => failed.push(path),
+                        }
+                    }
+                    continue;
+                };
+                for file in response.files {
+                    progress.end_file(Some(file.symbol_count));
+                    let ShardFile { file_path, keys, symbol_count } = file;
+                    record(&file_path, IndexedFile { keys, symbol_count });
+                }
+                for path in response.failed {
+                    progress.end_file(None);
+                    failed.push(path);
+                }
+            }
+        }
+        None => {
+            for path in pending {
+                match index_tracked(parser, pipeline, repo_path, &path, progress).await {
+                    Some(file) => record(&path, file),
+                    None => failed.push(path),
+                }
+            }
+        }
+    }
+    progress.finish();
+
+    sink.delete_stale_repo(pipeline.tenant, repo_path, &keys, &failed).await?;
+
+    Ok(IndexSummary {
+        files_indexed,
+        chunks_upserted: keys.len(),
+        sink: sink.name().to_string(),
+        success: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::{EmbeddingConfig, EmbeddingFormat};
+    use crate::sink::QdrantSink;
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::routing::{get, post, put};
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    /// An embedding endpoint and a Qdrant collection kept in memory.
+    /// Embedding fails for texts containing `fail_on` while it is set.
+    #[derive(Clone, Default)]
+    struct Backend {
+        points: Arc<Mutex<HashMap<String, Value>>>,
+        fail_on: Arc<Mutex<Option<String>>>,
+    }
+
+    impl Backend {
+        async fn serve(&self) -> String {
+            let app = Router::new()
+                .route("/embed", post(embed))
+                .route("/collections/:collection", get(|| async { Json(json!({})) }))
+                .route("/collections/:collection/points", put(upsert))
+                .route("/collections/:collection/points/delete", post(delete))
+                .with_state(self.clone());
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, app).await });
+            format!("http://{}", address)
+        }
+
+        /// Keys of the stored points of files named `file_name`.
+        fn keys_of(&self, file_name: &str) -> Vec<String> {
+            let points = self.points.lock().unwrap();
+            let mut keys: Vec<String> = points
+                .values()
+                .filter(|payload| payload["file_path"].as_str().is_some_and(|path| path.ends_with(file_name)))
+                .map(|payload| payload["key"].as_str().unwrap().to_string())
+                .collect();
+            keys.sort();
+            keys
+        }
+    }
+
+    async fn embed(State(backend): State<Backend>, Json(request): Json<Value>) -> Result<Json<Value>, StatusCode> {
+        let input = request["input"].as_array().cloned().unwrap_or_default();
+        if let Some(marker) = backend.fail_on.lock().unwrap().as_deref() {
+            if input.iter().any(|text| text.as_str().is_some_and(|text| text.contains(marker))) {
+                return Err(StatusCode::SERVICE_UNAVAILABLE);
+            }
+        }
+        let data: Vec<Value> = (0..input.len()).map(|index| json!({ "index": index, "embedding": [1.0, 0.0] })).collect();
+        Ok(Json(json!({ "data": data })))
+    }
+
+    async fn upsert(State(backend): State<Backend>, Json(request): Json<Value>) -> Json<Value> {
+        let mut points = backend.points.lock().unwrap();
+        for point in request["points"].as_array().into_iter().flatten() {
+            points.insert(point["id"].as_str().unwrap().to_string(), point["payload"].clone());
+        }
+        Json(json!({ "status": "ok" }))
+    }
+
+    async fn delete(State(backend): State<Backend>, Json(request): Json<Value>) -> Json<Value> {
+        let conditions = |name: &str| request["filter"][name].as_array().cloned().unwrap_or_default();
+        let (must, must_not) = (conditions("must"), conditions("must_not"));
+        backend.points.lock().unwrap().retain(|id, payload| {
+            let deleted = must.iter().all(|c| matches(c, id, payload)) && !must_not.iter().any(|c| matches(c, id, payload));
+            !deleted
+        });
+        Json(json!({ "status": "ok" }))
+    }
+
+    /// A `has_id`, `is_null` or `match` (`value` or `any`) filter condition.
+    fn matches(condition: &Value, id: &str, payload: &Value) -> bool {
+        if let Some(ids) = condition["has_id"].as_array() {
+            return ids.iter().any(|candidate| candidate.as_str() == Some(id));
+        }
+        if let Some(key) = condition["is_null"]["key"].as_str() {
+            return payload[key].is_null();
+        }
+        let field = &payload[condition["key"].as_str().unwrap()];
+        match condition["match"]["any"].as_array() {
+            Some(any) => any.contains(field),
+            None => &condition["match"]["value"] == field,
+        }
+    }
+
+    /// The services an index run goes through, backed by a [`Backend`].
+    struct Services {
+        embedder: EmbeddingClient,
+        sink: VectorSink,
+        parser: ParserService,
+        config: IndexerConfig,
+        tokenizers: TokenizerRegistry,
+        hooks: HookPipeline,
+        postprocess: PostProcessor,
+        work: Arc<WorkQueue>,
+    }
+
+    impl Services {
+        async fn new(backend: &Backend) -> Self {
+            let url = backend.serve().await;
+            Self {
+                embedder: EmbeddingClient::new(EmbeddingConfig {
+                    url: format!("{}/embed", url),
+                    api_key: None,
+                    model: None,
+                    format: EmbeddingFormat::OpenAi,
+                    batch_size: 64,
+                    max_retries: 0,
+                    requests_per_second: None,
+                    timeout: Duration::from_secs(5),
+                })
+                .unwrap(),
+                sink: VectorSink::Qdrant(QdrantSink::new(&url, "chunks", None)),
+                parser: ParserService::with_cache_capacity(16),
+                config: IndexerConfig::default(),
+                tokenizers: TokenizerRegistry::default(),
+                hooks: HookPipeline::default(),
+                postprocess: PostProcessor::default(),
+                work: WorkQueue::new(1, 1),
+            }
+        }
+
+        async fn reindex(&self, repo_path: &str, tenant: Option<&str>) -> IndexSummary {
+            let pipeline = IndexPipeline {
+                config: &self.config,
+                tokenizers: &self.tokenizers,
+                embedder: &self.embedder,
+                sink: &self.sink,
+                hooks: &self.hooks,
+                postprocess: &self.postprocess,
+                tenant,
+                events: None,
+                work: &self.work,
+                workers: None,
+                references: None,
+                owners: None,
+            };
+            let progress = IndexProgress::default();
+            let run = reindex_repo(&self.parser, &pipeline, repo_path, &progress, None);
+            crate::cache::partitioned(tenant.map(str::to_string), run).await.unwrap()
+        }
+    }
+
+    /// A repository of two Python files in a fresh temporary directory.
+    fn repository(name: &str) -> std::path::PathBuf {
+        let repo = std::env::temp_dir().join(format!("sherlock-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&repo).unwrap();
+        std::fs::write(repo.join("alpha.py"), "def alpha():\n    return 1\n").unwrap();
+        std::fs::write(repo.join("beta.py"), "def beta():\n    return 2\n").unwrap();
+        repo
+    }
+
+    #[tokio::test]
+    async fn failed_files_keep_their_vectors() {
+        let repo = repository("failed");
+        let repo_path = repo.to_str().unwrap();
+        let backend = Backend::default();
+        let services = Services::new(&backend).await;
+
+        assert_eq!(services.reindex(repo_path, None).await.files_indexed, 2);
+        let alpha = backend.keys_of("alpha.py");
+        let beta = backend.keys_of("beta.py");
+        assert!(!alpha.is_empty() && !beta.is_empty());
+
+        // beta.py fails to embed this time; alpha.py changed
+        *backend.fail_on.lock().unwrap() = Some("beta".to_string());
+        std::fs::write(repo.join("alpha.py"), "def alpha():\n    return 3\n").unwrap();
+        assert_eq!(services.reindex(repo_path, None).await.files_indexed, 1);
+        assert_eq!(backend.keys_of("beta.py"), beta);
+        let alpha_now = backend.keys_of("alpha.py");
+        assert!(!alpha_now.is_empty());
+        assert!(alpha_now.iter().all(|key| !alpha.contains(key)), "stale alpha.py vectors survived");
+
+        std::fs::remove_dir_all(&repo).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tenants_keep_separate_vectors() {
+        let repo = repository("tenants");
+        let repo_path = repo.to_str().unwrap();
+        let backend = Backend::default();
+        let services = Services::new(&backend).await;
+
+        services.reindex(repo_path, Some("acme")).await;
+        let acme = backend.keys_of("alpha.py");
+        services.reindex(repo_path, Some("globex")).await;
+        let both = backend.keys_of("alpha.py");
+        assert_eq!(both.len(), 2 * acme.len(), "tenants share points for identical chunks");
+
+        // globex's sweep after an edit leaves acme's vectors alone
+        std::fs::write(repo.join("alpha.py"), "def alpha():\n    return 3\n").unwrap();
+        services.reindex(repo_path, Some("globex")).await;
+        let after = backend.keys_of("alpha.py");
+        assert!(acme.iter().all(|key| after.contains(key)), "acme's vectors were swept by globex");
+        assert_eq!(after.len(), 2 * acme.len());
+
+        std::fs::remove_dir_all(&repo).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+use crate::hierarchy::{self, base_name};
+use crate::symbol::CodeSymbol;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// A type implementing the requested interface.
+#[derive(Debug, Serialize)]
+pub struct Implementation {
+    #[serde(rename = "type")]
+    pub type_symbol: CodeSymbol,
+    /// The type's method when a method of the interface was requested;
+    /// absent when it inherits the method rather than declaring it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<CodeSymbol>,
+    /// Satisfied by its method set (Go) rather than declared with
+    /// `implements` or `extends`.
+    pub structural: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Implementations {
+    pub interface: CodeSymbol,
+    /// The requested method, when `/implementations` was given one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<CodeSymbol>,
+    pub implementations: Vec<Implementation>,
+    pub success: bool,
+}
+
+/// Types implementing `target`, an interface or a method of one, among a
+/// repository's symbols. `None` when `target` is neither.
+///
+/// Java and TypeScript classes implement what they declare, directly or
+/// through their superclasses. A Go type implements an interface when it
+/// has a method of every name the interface and the interfaces it embeds
+/// require; signatures and methods promoted from embedded structs are not
+/// considered.
+pub fn implementations(symbols: &[CodeSymbol], target: &CodeSymbol) -> Option<Implementations> {
+    let (interface, method) = match target.symbol_type.as_str() {
+        "interface" => (target, None),
+        "method" => {
+            let interface = symbols
+                .iter()
+                .find(|symbol| symbol.symbol_type == "interface" && encloses(symbol, target))?;
+            (interface, Some(target))
+        }
+        _ => return None,
+    };
+
+    let mut implementations: Vec<Implementation> = hierarchy::hierarchy(symbols, interface)
+        .descendants
+        .into_iter()
+        .filter_map(|relation| relation.symbol)
+        .filter(|symbol| symbol.symbol_type != "interface")
+        .map(|type_symbol| Implementation {
+            method: method.and_then(|method| declared_method(symbols, &type_symbol, &method.symbol_name)),
+            type_symbol,
+            structural: false,
+        })
+        .collect();
+
+    // Everything satisfies an empty interface, which says nothing useful
+    let required = method_set(symbols, interface);
+    if is_go(&interface.file_path) && !required.is_empty() {
+        let methods = go_methods(symbols);
+        let explicit: HashSet<String> = implementations.iter().map(|i| i.type_symbol.id.clone()).collect();
+        let candidates = symbols.iter().filter(|symbol| {
+            is_go(&symbol.file_path) && matches!(symbol.symbol_type.as_str(), "struct" | "type") && !explicit.contains(&symbol.id)
+        });
+        for type_symbol in candidates {
+            let key = (package(&type_symbol.file_path), type_symbol.symbol_name.as_str());
+            let Some(provided) = methods.get(&key) else {
+                continue;
+            };
+            if !required.iter().all(|name| provided.contains_key(name.as_str())) {
+                continue;
+            }
+            implementations.push(Implementation {
+                type_symbol: type_symbol.clone(),
+                method: method.and_then(|method| provided.get(method.symbol_name.as_str()).map(|found| (*found).clone())),
+                structural: true,
+            });
+        }
+    }
+
+    implementations.sort_by(|a, b| {
+        a.type_symbol
+            .file_path
+            .cmp(&b.type_symbol.file_path)
+            .then_with(|| a.type_symbol.line_start.cmp(&b.type_symbol.line_start))
+    });
+    Some(Implementations {
+        interface: interface.clone(),
+        method: method.cloned(),
+        implementations,
+        success: true,
+    })
+}
+
+/// Names of the methods an interface requires, including those of the
+/// interfaces it extends or embeds.
+fn method_set(symbols: &[CodeSymbol], interface: &CodeSymbol) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut pending = vec![interface];
+    while let Some(current) = pending.pop() {
+        if !seen.insert(current.id.as_str()) {
+            continue;
+        }
+        names.extend(
+            symbols
+                .iter()
+                .filter(|symbol| symbol.symbol_type == "method" && encloses(current, symbol))
+                .map(|symbol| symbol.symbol_name.clone()),
+        );
+        for supertype in &current.supertypes {
+            pending.extend(
+                symbols
+                    .iter()
+                    .filter(|symbol| symbol.symbol_type == "interface" && symbol.symbol_name == base_name(supertype)),
+            );
+        }
+    }
+    names
+}
+
+/// Go methods by package and receiver type, then by name. Receivers are
+/// taken from qualified names (`Server.Start`).
+fn go_methods(symbols: &[CodeSymbol]) -> HashMap<(&Path, &str), HashMap<&str, &CodeSymbol>> {
+    let mut methods: HashMap<(&Path, &str), HashMap<&str, &CodeSymbol>> = HashMap::new();
+    for symbol in symbols.iter().filter(|symbol| symbol.symbol_type == "method" && is_go(&symbol.file_path)) {
+        let Some((receiver, _)) = symbol.qualified_name.as_deref().and_then(|name| name.rsplit_once('.')) else {
+            continue;
+        };
+        methods
+            .entry((package(&symbol.file_path), receiver))
+            .or_default()
+            .insert(symbol.symbol_name.as_str(), symbol);
+    }
+    methods
+}
+
+/// The method called `name` declared in the body of `type_symbol`.
+fn declared_method(symbols: &[CodeSymbol], type_symbol: &CodeSymbol, name: &str) -> Option<CodeSymbol> {
+    symbols
+        .iter()
+        .find(|symbol| symbol.symbol_type == "method" && symbol.symbol_name == name && encloses(type_symbol, symbol))
+        .cloned()
+}
+
+fn encloses(outer: &CodeSymbol, inner: &CodeSymbol) -> bool {
+    outer.id != inner.id
+        && outer.file_path == inner.file_path
+        && outer.line_start <= inner.line_start
+        && inner.line_end <= outer.line_end
+}
+
+fn is_go(file_path: &str) -> bool {
+    file_path.ends_with(".go")
+}
+
+/// A Go package is a directory.
+fn package(file_path: &str) -> &Path {
+    Path::new(file_path).parent().unwrap_or(Path::new(""))
+}
@@ -0,0 +1,90 @@
+use crate::config::ConfigHandle;
+use crate::repo;
+use axum::{
+    extract::{RawPathParams, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+    RequestPartsExt,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Checks a repository path, and optionally a file inside it, before any
+/// handler touches the filesystem:
+///
+/// - 400 for `..` components (under either separator) or NUL bytes, whatever
+///   the URL encoding was, and for absolute file paths;
+/// - 403 when the file resolves (through symlinks) outside its repository;
+/// - 403 when the repository is not under one of `roots`; with none
+///   configured every repository is refused.
+///
+/// Paths that do not exist yet pass the lexical checks only; the handler
+/// answers 404 for them.
+pub fn check(roots: &[String], repo_path: &str, file_path: Option<&str>) -> Result<(), StatusCode> {
+    for path in std::iter::once(repo_path).chain(file_path) {
+        if repo::has_parent_dir(path) || path.contains('\0') {
+            tracing::warn!("Rejected path traversal attempt: {:?}", path);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    if file_path.is_some_and(repo::is_absolute) {
+        tracing::warn!("Rejected absolute file path under {}", repo_path);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let repo = std::fs::canonicalize(repo_path).unwrap_or_else(|_| PathBuf::from(repo_path));
+    if let Some(file_path) = file_path {
+        if let Ok(file) = std::fs::canonicalize(repo::join(repo_path, file_path)) {
+            if !file.starts_with(&repo) {
+                tracing::warn!("{}/{} resolves outside its repository", repo_path, file_path);
+                return Err(StatusCode::FORBIDDEN);
+            }
+        }
+    }
+
+    if roots.is_empty() {
+        tracing::warn!("Refused {}: no repo_roots are configured", repo_path);
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let allowed = roots.iter().any(|root| {
+        let root = std::fs::canonicalize(root).unwrap_or_else(|_| PathBuf::from(root));
+        repo.starts_with(root)
+    });
+    if !allowed {
+        tracing::warn!("{} is outside the configured repo_roots", repo_path);
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(())
+}
+
+/// Applies [`check`] to the `repo_path` and `file_path` URL parameters.
+pub async fn confine(State(config): State<Arc<ConfigHandle>>, request: Request, next: Next) -> Result<Response, StatusCode> {
+    let (mut parts, body) = request.into_parts();
+    if let Ok(params) = parts.extract::<RawPathParams>().await {
+        let param = |name: &str| params.iter().find(|(key, _)| *key == name).map(|(_, value)| value);
+        if let Some(repo_path) = param("repo_path") {
+            check(&config.current().repo_roots, repo_path, param("file_path"))?;
+        }
+    }
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_is_served_without_roots() {
+        assert_eq!(check(&[], "/repos/app", None), Err(StatusCode::FORBIDDEN));
+        assert_eq!(check(&[], "/repos/app", Some("src/main.rs")), Err(StatusCode::FORBIDDEN));
+    }
+
+    #[test]
+    fn repositories_must_be_under_a_root() {
+        let roots = vec!["/repos".to_string()];
+        assert_eq!(check(&roots, "/repos/app", None), Ok(()));
+        assert_eq!(check(&roots, "/elsewhere/app", None), Err(StatusCode::FORBIDDEN));
+        assert_eq!(check(&roots, "/repos/../etc", None), Err(StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,328 @@
+use crate::checkpoint::JobRecord;
+use crate::indexing::{IndexProgress, IndexSummary, ProgressSnapshot};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::{Mutex as AsyncMutex, Semaphore, SemaphorePermit};
+use uuid::Uuid;
+
+pub const DEFAULT_JOB_CONCURRENCY: usize = 1;
+
+/// Finished jobs kept for status requests; the oldest are dropped first.
+const MAX_FINISHED_JOBS: usize = 500;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+#[derive(Debug, Deserialize)]
+pub struct IndexJobRequest {
+    pub repo_path: String,
+    /// Receives the job report once the job completes or fails.
+    pub callback_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    /// Another job for the same repository is running first.
+    Waiting,
+    Running,
+    Completed,
+    Failed,
+    /// Skipped because a newer job for the same repository was queued behind it.
+    Superseded,
+}
+
+#[derive(Debug)]
+struct JobStatus {
+    state: JobState,
+    started_at: Option<u64>,
+    finished_at: Option<u64>,
+    summary: Option<IndexSummary>,
+    error: Option<String>,
+}
+
+/// A repository index run in the background.
+#[derive(Debug)]
+pub struct Job {
+    pub id: String,
+    /// Submission order, to tell which of two jobs for a repository is newer.
+    seq: u64,
+    pub repo_path: String,
+    pub callback_url: Option<String>,
+    /// Tenant of the caller that submitted it, tagged onto the vectors.
+    pub tenant: Option<String>,
+    /// `Idempotency-Key` it was submitted under, scoped to the submitting key.
+    pub idempotency_key: Option<String>,
+    pub progress: IndexProgress,
+    created_at: u64,
+    status: Mutex<JobStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobReport {
+    pub id: String,
+    pub repo_path: String,
+    pub state: JobState,
+    pub progress: ProgressSnapshot,
+    /// Unix timestamps (seconds).
+    pub created_at: u64,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<IndexSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub success: bool,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+impl Job {
+    pub fn wait(&self) {
+        self.status.lock().unwrap().state = JobState::Waiting;
+    }
+
+    pub fn supersede(&self) {
+        let mut status = self.status.lock().unwrap();
+        status.state = JobState::Superseded;
+        status.finished_at = Some(now());
+    }
+
+    pub fn start(&self) {
+        let mut status = self.status.lock().unwrap();
+        status.state = JobState::Running;
+        status.started_at = Some(now());
+    }
+
+    pub fn finish(&self, result: Result<IndexSummary>) {
+        let mut status = self.status.lock().unwrap();
+        status.finished_at = Some(now());
+        match result {
+            Ok(summary) => {
+                status.state = JobState::Completed;
+                status.summary = Some(summary);
+            }
+            Err(e) => {
+                status.state = JobState::Failed;
+                status.error = Some(format!("{:#}", e));
+            }
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        matches!(self.status.lock().unwrap().state, JobState::Completed | JobState::Failed | JobState::Superseded)
+    }
+
+    /// A fresh checkpoint record for this job.
+    pub fn record(&self) -> JobRecord {
+        JobRecord {
+            id: self.id.clone(),
+            repo_path: self.repo_path.clone(),
+            tenant: self.tenant.clone(),
+            callback_url: self.callback_url.clone(),
+            idempotency_key: self.idempotency_key.clone(),
+            created_at: self.created_at,
+            files: Default::default(),
+        }
+    }
+
+    pub fn report(&self) -> JobReport {
+        let status = self.status.lock().unwrap();
+        JobReport {
+            id: self.id.clone(),
+            repo_path: self.repo_path.clone(),
+            state: status.state,
+            progress: self.progress.snapshot(),
+            created_at: self.created_at,
+            started_at: status.started_at,
+            finished_at: status.finished_at,
+            summary: status.summary.clone(),
+            error: status.error.clone(),
+            success: status.state != JobState::Failed,
+        }
+    }
+}
+
+/// Background index jobs, at most `concurrency` of them running at once and
+/// one at a time per repository.
+pub struct JobRegistry {
+    jobs: RwLock<HashMap<String, Arc<Job>>>,
+    slots: Semaphore,
+    concurrency: usize,
+    next_seq: AtomicU64,
+    repo_locks: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+}
+
+impl JobRegistry {
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            jobs: RwLock::new(HashMap::new()),
+            slots: Semaphore::new(concurrency.max(1)),
+            concurrency: concurrency.max(1),
+            next_seq: AtomicU64::new(0),
+            repo_locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registers a queued job for `repo_path`. When a job was already submitted
+    /// under `idempotency_key` it is returned instead, with `false`.
+    pub fn submit(
+        &self,
+        repo_path: &str,
+        callback_url: Option<String>,
+        tenant: Option<String>,
+        idempotency_key: Option<String>,
+    ) -> (Arc<Job>, bool) {
+        self.insert(Uuid::new_v4().to_string(), repo_path, callback_url, tenant, idempotency_key, now())
+    }
+
+    /// Re-registers a job checkpointed by an earlier process, under its old id.
+    pub fn resume(&self, record: &JobRecord) -> Arc<Job> {
+        self.insert(
+            record.id.clone(),
+            &record.repo_path,
+            record.callback_url.clone(),
+            record.tenant.clone(),
+            record.idempotency_key.clone(),
+            record.created_at,
+        )
+        .0
+    }
+
+    fn insert(
+        &self,
+        id: String,
+        repo_path: &str,
+        callback_url: Option<String>,
+        tenant: Option<String>,
+        idempotency_key: Option<String>,
+        created_at: u64,
+    ) -> (Arc<Job>, bool) {
+        let mut jobs = self.jobs.write().unwrap();
+        if let Some(key) = &idempotency_key {
+            let existing = jobs.values().find(|job| job.idempotency_key.as_ref() == Some(key));
+            if let Some(job) = existing {
+                return (job.clone(), false);
+            }
+        }
+        let job = Arc::new(Job {
+            id,
+            seq: self.next_seq.fetch_add(1, Ordering::Relaxed),
+            repo_path: repo_path.to_string(),
+            callback_url,
+            tenant,
+            idempotency_key,
+            progress: IndexProgress::default(),
+            created_at,
+            status: Mutex::new(JobStatus {
+                state: JobState::Queued,
+                started_at: None,
+                finished_at: None,
+                summary: None,
+                error: None,
+            }),
+        });
+        Self::prune(&mut jobs);
+        jobs.insert(job.id.clone(), job.clone());
+        (job, true)
+    }
+
+    fn prune(jobs: &mut HashMap<String, Arc<Job>>) {
+        let mut finished: Vec<(u64, String)> = jobs
+            .values()
+            .filter(|job| job.is_finished())
+            .map(|job| (job.created_at, job.id.clone()))
+            .collect();
+        if finished.len() < MAX_FINISHED_JOBS {
+            return;
+        }
+        finished.sort();
+        for (_, id) in &finished[..=finished.len() - MAX_FINISHED_JOBS] {
+            jobs.remove(id);
+        }
+    }
+
+    /// Whether a job for `repo_path` is queued or running.
+    pub fn is_active(&self, repo_path: &str) -> bool {
+        self.jobs
+            .read()
+            .unwrap()
+            .values()
+            .any(|job| job.repo_path == repo_path && !job.is_finished())
+    }
+
+    /// Jobs waiting for a run slot or for their repository.
+    pub fn queued(&self) -> usize {
+        self.jobs
+            .read()
+            .unwrap()
+            .values()
+            .filter(|job| matches!(job.status.lock().unwrap().state, JobState::Queued | JobState::Waiting))
+            .count()
+    }
+
+    /// Whether a newer job for the same repository is still to run, making `job` redundant.
+    pub fn is_superseded(&self, job: &Job) -> bool {
+        self.jobs
+            .read()
+            .unwrap()
+            .values()
+            .any(|other| other.repo_path == job.repo_path && other.seq > job.seq && !other.is_finished())
+    }
+
+    /// Lock held while anything writes a repository's vectors, so two runs
+    /// never interleave revisions in the store. Spellings of the same
+    /// directory (`foo/`, `./foo`) share a lock.
+    pub fn repo_lock(&self, repo_path: &str) -> Arc<AsyncMutex<()>> {
+        let key = std::fs::canonicalize(repo_path)
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| repo_path.to_string());
+        let mut locks = self.repo_locks.lock().unwrap();
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks.entry(key).or_default().clone()
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<Job>> {
+        self.jobs.read().unwrap().get(id).cloned()
+    }
+
+    /// Waits for a free run slot; the job runs while the permit is held.
+    pub async fn slot(&self) -> SemaphorePermit<'_> {
+        self.slots.acquire().await.expect("job semaphore is never closed")
+    }
+
+    /// Free and total run slots.
+    pub fn slots_free(&self) -> (usize, usize) {
+        (self.slots.available_permits(), self.concurrency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spellings_of_a_repository_share_a_lock() {
+        let repo = std::env::temp_dir().join(format!("sherlock-lock-{}", std::process::id()));
+        std::fs::create_dir_all(&repo).unwrap();
+        let repo_path = repo.to_str().unwrap();
+        let jobs = JobRegistry::new(1);
+
+        let lock = jobs.repo_lock(repo_path);
+        assert!(Arc::ptr_eq(&lock, &jobs.repo_lock(&format!("{}/", repo_path))));
+        assert!(Arc::ptr_eq(&lock, &jobs.repo_lock(&format!("{}/../{}", repo_path, repo.file_name().unwrap().to_str().unwrap()))));
+        assert!(!Arc::ptr_eq(&lock, &jobs.repo_lock(std::env::temp_dir().to_str().unwrap())));
+
+        std::fs::remove_dir_all(&repo).unwrap();
+    }
+}
@@ -0,0 +1,215 @@
+use crate::auth::Caller;
+use anyhow::{anyhow, Context, Result};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Keys are refetched after this long, or sooner when a token names an
+/// unknown `kid` (at most once per `MIN_REFRESH`).
+const JWKS_TTL: Duration = Duration::from_secs(600);
+const MIN_REFRESH: Duration = Duration::from_secs(30);
+
+/// `[jwt]` table of the config file: bearer tokens signed by an SSO issuer.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JwtSettings {
+    pub jwks_url: String,
+    pub issuer: String,
+    pub audience: String,
+    /// Scope (in `scope` or `scp`) that grants access to `/admin`.
+    #[serde(default = "default_admin_scope")]
+    pub admin_scope: String,
+    /// Claim holding the caller's tenant; tokens without it are refused.
+    #[serde(default)]
+    pub tenant_claim: Option<String>,
+    /// Claim holding the repository roots the caller may touch.
+    #[serde(default)]
+    pub roots_claim: Option<String>,
+    /// Requests each subject (`sub`) may make per minute; unlimited when unset.
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Requests each subject may have in flight at once; unlimited when unset.
+    #[serde(default)]
+    pub max_concurrent: Option<u32>,
+}
+
+fn default_admin_scope() -> String {
+    "indexer:admin".to_string()
+}
+
+struct CachedKeys {
+    url: String,
+    fetched: Instant,
+    keys: JwkSet,
+}
+
+/// Validates bearer JWTs against the configured issuer's JWKS.
+#[derive(Default)]
+pub struct JwtVerifier {
+    http: reqwest::Client,
+    cached: Mutex<Option<CachedKeys>>,
+}
+
+impl JwtVerifier {
+    /// The caller a valid token identifies: its `sub`, with admin, tenant and
+    /// roots taken from the configured claims. Tokens without the configured
+    /// roots claim are rejected.
+    pub async fn verify(&self, settings: &JwtSettings, token: &str) -> Result<Caller> {
+        let header = jsonwebtoken::decode_header(token).context("Malformed token")?;
+        if !is_asymmetric(header.alg) {
+            return Err(anyhow!("Unsupported token algorithm {:?}", header.alg));
+        }
+        let kid = header.kid.ok_or_else(|| anyhow!("Token has no kid"))?;
+        let key = self.decoding_key(settings, &kid).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&settings.issuer]);
+        validation.set_audience(&[&settings.audience]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = jsonwebtoken::decode::<Value>(token, &key, &validation)?.claims;
+        caller(settings, &claims)
+    }
+
+    async fn decoding_key(&self, settings: &JwtSettings, kid: &str) -> Result<DecodingKey> {
+        let mut cached = self.cached.lock().await;
+        let stale = match cached.as_ref() {
+            Some(c) if c.url != settings.jwks_url => true,
+            Some(c) if c.fetched.elapsed() >= JWKS_TTL => true,
+            Some(c) => c.keys.find(kid).is_none() && c.fetched.elapsed() >= MIN_REFRESH,
+            None => true,
+        };
+        if stale {
+            let keys = self.fetch(&settings.jwks_url).await?;
+            *cached = Some(CachedKeys {
+                url: settings.jwks_url.clone(),
+                fetched: Instant::now(),
+                keys,
+            });
+        }
+        let jwk = cached
+            .as_ref()
+            .and_then(|c| c.keys.find(kid))
+            .ok_or_else(|| anyhow!("Unknown signing key {}", kid))?;
+        DecodingKey::from_jwk(jwk).context("Unusable signing key")
+    }
+
+    async fn fetch(&self, url: &str) -> Result<JwkSet> {
+        tracing::info!("Fetching JWKS from {}", url);
+        let response = self.http.get(url).send().await?.error_for_status()?;
+        response.json().await.context("Invalid JWKS document")
+    }
+}
+
+/// The caller `claims` identify. With `roots_claim` configured a token
+/// must name at least one root, since a caller without roots may access
+/// every repository; with `tenant_claim` configured it must name a tenant,
+/// since a caller without one shares the untenanted caches and vectors.
+fn caller(settings: &JwtSettings, claims: &Value) -> Result<Caller> {
+    let claim_strings = |name: &str| -> Option<Vec<String>> {
+        match claims.get(name)? {
+            Value::String(s) => Some(s.split_whitespace().map(str::to_string).collect()),
+            Value::Array(items) => items.iter().map(|v| v.as_str().map(str::to_string)).collect(),
+            _ => None,
+        }
+    };
+    let admin = claim_strings("scope")
+        .into_iter()
+        .chain(claim_strings("scp"))
+        .flatten()
+        .any(|scope| scope == settings.admin_scope);
+    let roots = match settings.roots_claim.as_deref() {
+        Some(claim) => claim_strings(claim)
+            .filter(|roots| !roots.is_empty())
+            .ok_or_else(|| anyhow!("Token has no usable {} claim", claim))?,
+        None => Vec::new(),
+    };
+    let tenant = match settings.tenant_claim.as_deref() {
+        Some(claim) => Some(
+            claims
+                .get(claim)
+                .and_then(Value::as_str)
+                .filter(|tenant| !tenant.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("Token has no usable {} claim", claim))?,
+        ),
+        None => None,
+    };
+    Ok(Caller {
+        name: claims["sub"].as_str().unwrap_or_default().to_string(),
+        admin,
+        tenant,
+        roots,
+    })
+}
+
+/// Public-key algorithms only, so a JWKS key can never be used as an HMAC secret.
+fn is_asymmetric(alg: Algorithm) -> bool {
+    !matches!(alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn settings() -> JwtSettings {
+        JwtSettings {
+            jwks_url: "https://sso.example.com/jwks".to_string(),
+            issuer: "https://sso.example.com".to_string(),
+            audience: "indexer".to_string(),
+            admin_scope: default_admin_scope(),
+            tenant_claim: Some("tenant".to_string()),
+            roots_claim: Some("roots".to_string()),
+            requests_per_minute: None,
+            max_concurrent: None,
+        }
+    }
+
+    #[test]
+    fn roots_come_from_the_configured_claim() {
+        let claims = json!({"sub": "alice", "tenant": "acme", "roots": ["/repos/acme"], "scope": "indexer:admin"});
+        let caller = caller(&settings(), &claims).unwrap();
+        assert_eq!(caller.roots, vec!["/repos/acme"]);
+        assert_eq!(caller.tenant.as_deref(), Some("acme"));
+        assert!(caller.admin);
+        assert!(!caller.may_access("/repos/other"));
+    }
+
+    #[test]
+    fn token_without_roots_claim_is_rejected() {
+        let claims = json!({"sub": "alice", "tenant": "acme"});
+        assert!(caller(&settings(), &claims).is_err());
+    }
+
+    #[test]
+    fn malformed_or_empty_roots_claim_is_rejected() {
+        for roots in [json!(42), json!([1, 2]), json!([]), json!("")] {
+            let claims = json!({"sub": "alice", "tenant": "acme", "roots": roots});
+            assert!(caller(&settings(), &claims).is_err(), "accepted roots {}", roots);
+        }
+    }
+
+    #[test]
+    fn roots_are_optional_without_a_roots_claim() {
+        let settings = JwtSettings {
+            roots_claim: None,
+            ..settings()
+        };
+        let caller = caller(&settings, &json!({"sub": "alice", "tenant": "acme"})).unwrap();
+        assert!(caller.roots.is_empty());
+    }
+
+    #[test]
+    fn token_without_tenant_claim_is_rejected() {
+        for claims in [
+            json!({"sub": "alice", "roots": ["/repos/acme"]}),
+            json!({"sub": "alice", "tenant": "", "roots": ["/repos/acme"]}),
+            json!({"sub": "alice", "tenant": 7, "roots": ["/repos/acme"]}),
+        ] {
+            assert!(caller(&settings(), &claims).is_err(), "accepted {}", claims);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use anyhow::{Context, Result};
+use axum::{extract::Request, middleware::Next, response::Response, Router};
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use std::os::fd::{FromRawFd, IntoRawFd, RawFd};
+use std::os::unix::fs::FileTypeExt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tower::ServiceExt;
+
+pub const DEFAULT_LISTEN_ADDR: &str = "0.0.0.0:8081";
+
+/// First descriptor passed by systemd (`SD_LISTEN_FDS_START`).
+const SD_LISTEN_FDS_START: RawFd = 3;
+
+/// Where the service accepts connections, from `LISTEN_ADDR` (TCP; `off`
+/// disables it) and `UNIX_SOCKET_PATH`.
+#[derive(Debug)]
+pub struct ListenConfig {
+    pub tcp: Option<String>,
+    pub unix: Option<String>,
+}
+
+impl ListenConfig {
+    pub fn from_env() -> Result<Self> {
+        let tcp = match std::env::var("LISTEN_ADDR") {
+            Ok(addr) if addr == "off" => None,
+            Ok(addr) if !addr.is_empty() => Some(addr),
+            _ => Some(DEFAULT_LISTEN_ADDR.to_string()),
+        };
+        let unix = std::env::var("UNIX_SOCKET_PATH").ok().filter(|p| !p.is_empty());
+        if tcp.is_none() && unix.is_none() {
+            return Err(anyhow::anyhow!("LISTEN_ADDR=off requires UNIX_SOCKET_PATH"));
+        }
+        Ok(Self { tcp, unix })
+    }
+}
+
+/// A listening socket handed over by systemd socket activation.
+#[derive(Debug)]
+pub enum ActivatedListener {
+    Tcp(std::net::TcpListener),
+    Unix(std::os::unix::net::UnixListener),
+}
+
+impl ActivatedListener {
+    /// The first socket passed via `LISTEN_FDS`/`LISTEN_PID` (as
+    /// `sd_listen_fds` would report it), or `None` when the service was not
+    /// socket-activated.
+    pub fn from_env() -> Result<Option<Self>> {
+        let for_us = std::env::var("LISTEN_PID")
+            .ok()
+            .and_then(|pid| pid.parse::<u32>().ok())
+            .is_some_and(|pid| pid == std::process::id());
+        if !for_us {
+            return Ok(None);
+        }
+        let count: u32 = match std::env::var("LISTEN_FDS").ok().and_then(|n| n.parse().ok()) {
+            Some(count) if count > 0 => count,
+            _ => return Ok(None),
+        };
+        if count > 1 {
+            tracing::warn!("systemd passed {} sockets, serving only the first", count);
+        }
+
+        // SAFETY: systemd hands us SD_LISTEN_FDS_START as an open listening
+        // socket owned by this process; nothing else in the process uses it.
+        let unix = unsafe { std::os::unix::net::UnixListener::from_raw_fd(SD_LISTEN_FDS_START) };
+        let listener = if unix.local_addr().is_ok() {
+            Self::Unix(unix)
+        } else {
+            // Not AF_UNIX; release the descriptor and take it as TCP instead
+            let fd = unix.into_raw_fd();
+            Self::Tcp(unsafe { std::net::TcpListener::from_raw_fd(fd) })
+        };
+        match &listener {
+            Self::Tcp(socket) => socket.set_nonblocking(true),
+            Self::Unix(socket) => socket.set_nonblocking(true),
+        }
+        .context("Failed to make the activated socket non-blocking")?;
+        Ok(Some(listener))
+    }
+
+    pub async fn serve(self, app: Router) -> Result<()> {
+        match self {
+            Self::Tcp(socket) => {
+                let listener = tokio::net::TcpListener::from_std(socket)
+                    .context("Failed to adopt the activated TCP socket")?;
+                tracing::info!("🚀 Rust indexer service listening on systemd socket {:?}", listener.local_addr().ok());
+                axum::serve(listener, app).await.context("TCP server failed")
+            }
+            Self::Unix(socket) => {
+                let listener = tokio::net::UnixListener::from_std(socket)
+                    .context("Failed to adopt the activated Unix socket")?;
+                tracing::info!("🚀 Rust indexer service listening on systemd socket {:?}", listener.local_addr().ok());
+                accept_unix(listener, app).await
+            }
+        }
+    }
+}
+
+pub async fn serve_tcp(addr: &str, app: Router) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind to {}", addr))?;
+    tracing::info!("🚀 Rust indexer service listening on {}", addr);
+    axum::serve(listener, app).await.context("TCP server failed")
+}
+
+/// Serves `app` on a Unix domain socket, replacing a stale socket file left by
+/// a previous run (but never any other kind of file).
+pub async fn serve_unix(path: &str, app: Router) -> Result<()> {
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(anyhow::anyhow!("{} exists and is not a socket", path));
+        }
+        std::fs::remove_file(path).with_context(|| format!("Failed to remove stale socket {}", path))?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("Failed to bind to {}", path))?;
+    tracing::info!("🚀 Rust indexer service listening on unix:{}", path);
+    accept_unix(listener, app).await
+}
+
+async fn accept_unix(listener: tokio::net::UnixListener, app: Router) -> Result<()> {
+    loop {
+        let (socket, _) = listener.accept().await.context("Unix socket accept failed")?;
+        let app = app.clone();
+        tokio::spawn(async move {
+            let service = hyper::service::service_fn(move |request: Request<Incoming>| app.clone().oneshot(request));
+            if let Err(e) = auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(socket), service)
+                .await
+            {
+                tracing::debug!("Unix socket connection closed: {}", e);
+            }
+        });
+    }
+}
+
+/// Requests currently being handled, and when the last one finished
+/// (milliseconds since `started()`), for idle exit.
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static LAST_ACTIVITY_MS: AtomicU64 = AtomicU64::new(0);
+
+fn started() -> Instant {
+    static STARTED: OnceLock<Instant> = OnceLock::new();
+    *STARTED.get_or_init(Instant::now)
+}
+
+fn touch() {
+    LAST_ACTIVITY_MS.store(started().elapsed().as_millis() as u64, Ordering::Relaxed);
+}
+
+/// Records request activity so `idle_exit` knows when the service is idle.
+pub async fn track_activity(request: Request, next: Next) -> Response {
+    IN_FLIGHT.fetch_add(1, Ordering::Relaxed);
+    touch();
+    let response = next.run(request).await;
+    touch();
+    IN_FLIGHT.fetch_sub(1, Ordering::Relaxed);
+    response
+}
+
+/// `IDLE_TIMEOUT_SECS`, if set to a positive number.
+pub fn idle_timeout_from_env() -> Option<Duration> {
+    std::env::var("IDLE_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// Resolves once no request has been in flight for `timeout`. Under socket
+/// activation systemd keeps the socket open and starts us again on the next
+/// connection.
+pub async fn idle_exit(timeout: Duration) {
+    touch();
+    let tick = timeout.min(Duration::from_secs(1));
+    loop {
+        tokio::time::sleep(tick).await;
+        let last = Duration::from_millis(LAST_ACTIVITY_MS.load(Ordering::Relaxed));
+        if IN_FLIGHT.load(Ordering::Relaxed) == 0 && started().elapsed().saturating_sub(last) >= timeout {
+            return;
+        }
+    }
+}
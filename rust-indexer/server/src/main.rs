@@ -0,0 +1,2049 @@
+use axum::{
+    body::Body,
+    extract::{Extension, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post},
+    Router,
+};
+// Serialization handled by ExtractRequest/ExtractResponse
+use std::sync::Arc;
+use std::time::Instant;
+use tokio_stream::wrappers::ReceiverStream;
+use tower::ServiceBuilder;
+use tower_http::catch_panic::CatchPanicLayer;
+use tower_http::cors::CorsLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::TraceLayer;
+use tracing::Instrument;
+
+mod audit;
+mod artifacts;
+mod auth;
+mod capabilities;
+mod checkpoint;
+mod cli;
+mod config;
+mod context;
+mod definition;
+mod distributed;
+mod embedding;
+mod error;
+mod events;
+mod export;
+mod health;
+mod hierarchy;
+mod hooks;
+mod indexing;
+mod interfaces;
+mod jail;
+mod jobs;
+mod jwt;
+mod listener;
+mod middleware;
+mod page;
+mod postprocess;
+mod priority;
+mod revision;
+mod schedule;
+mod sink;
+mod surface;
+mod symbol;
+mod telemetry;
+mod timing;
+mod traits;
+mod version;
+mod watch;
+mod webhook;
+
+// Parsing, the symbol model and chunking live in the core library
+use sherlock_indexer_core::{
+    cache, chunker, encoding, generated, grammars, language, literals, metrics, owners, parser, query_pack, repo, routes, rules, shared_cache, stats,
+    tokens,
+};
+
+use parser::{ExtractOptions, ParserService};
+use chunker::ChunkOptions;
+use config::{ConfigHandle, IndexerConfig};
+use context::CardFormat;
+use embedding::{EmbeddingClient, EmbeddingConfig};
+use hooks::HookPipeline;
+use sink::VectorSink;
+use symbol::{
+    ApiDiffQuery, AstQuery, AstResponse, CachePurgeQuery, CardQuery, ChunkDiffRequest, ChunkDiffResponse, ChunkRequest, ChunkResponse, CodeChunk,
+    ContextPackRequest, ContextQuery, DefinitionQuery, DefinitionResponse, DiagnosticsResponse, LanguageQuery, ExportQuery, ExtractRequest, ExtractResponse, FileLiteralsResponse, FileMeta, HierarchyQuery, HighlightQuery, HighlightResponse, ImplementationsQuery, LiteralQuery, LiteralsResponse, OwnedSymbols, OwnerQuery, FileOwners, QueryPackQuery, QueryPackResponse, QueryPacksResponse, RankingQuery, RouteQuery, RoutesResponse, SelectionQuery, SelectionResponse,
+    SymbolListQuery, TraitQuery, UsageOrder, UsageQuery, WarmupRequest, WarmupResponse,
+};
+use tokens::TokenizerRegistry;
+
+#[derive(Clone)]
+struct AppState {
+    config: Arc<ConfigHandle>,
+    parser: Arc<ParserService>,
+    tokenizers: Arc<TokenizerRegistry>,
+    embedder: Option<Arc<EmbeddingClient>>,
+    sink: Option<Arc<VectorSink>>,
+    hooks: Arc<HookPipeline>,
+    postprocess: Arc<postprocess::PostProcessor>,
+    registry: Arc<indexing::IndexRegistry>,
+    usage: Arc<auth::UsageTracker>,
+    jobs: Arc<jobs::JobRegistry>,
+    webhooks: Arc<webhook::JobWebhooks>,
+    events: Option<Arc<events::EventBus>>,
+    artifacts: Option<Arc<artifacts::ArtifactStore>>,
+    scheduler: Arc<schedule::Scheduler>,
+    work: Arc<priority::WorkQueue>,
+    checkpoints: Option<Arc<checkpoint::CheckpointStore>>,
+    workers: Option<Arc<distributed::WorkerPool>>,
+    limits: Arc<capabilities::Limits>,
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(args).await);
+    }
+
+    init_tracing();
+
+    // Loaded first so extension mappings in the config can name these languages
+    let grammars = grammars::from_env().expect("Invalid GRAMMAR_DIR");
+    let config = Arc::new(ConfigHandle::from_env().expect("Invalid INDEXER_CONFIG"));
+    if config.current().repo_roots.is_empty() {
+        tracing::warn!("No repo_roots configured in INDEXER_CONFIG; every repository request will be refused");
+    }
+    let chunking = config.current().chunking.len();
+    if chunking > 0 {
+        tracing::info!("Chunking configured for {} language(s)", chunking);
+    }
+    let cache_entries = std::env::var("PARSE_CACHE_ENTRIES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(cache::DEFAULT_PARSE_CACHE_ENTRIES);
+    let max_file_bytes = std::env::var("MAX_FILE_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(error::DEFAULT_MAX_FILE_BYTES);
+    let mut parser = ParserService::with_cache_capacity(cache_entries)
+        .with_max_file_bytes(max_file_bytes)
+        .with_extensions(config.current().extensions.clone())
+        .with_include_generated(config.current().include_generated);
+    for grammar in grammars {
+        parser = parser.with_extractor(grammar);
+    }
+    for (language, rules) in &config.current().rules {
+        parser = parser.with_rules(language, rules).expect("Invalid extraction rules");
+        tracing::info!("{} extraction rule(s) configured for {}", rules.len(), language);
+    }
+    if let Some(dir) = std::env::var("QUERY_PACK_DIR").ok().filter(|dir| !dir.is_empty()) {
+        parser = parser.with_query_packs(std::path::Path::new(&dir)).expect("Invalid QUERY_PACK_DIR");
+        tracing::info!("{} query pack(s) loaded", parser.query_packs().len());
+    }
+    // Incompatible grammars are disabled (and reported by /readyz) unless every one is required
+    let broken = parser.broken_grammars();
+    if !broken.is_empty() && matches!(std::env::var("REQUIRE_ALL_GRAMMARS").as_deref(), Ok("1" | "true")) {
+        panic!("Incompatible grammars with REQUIRE_ALL_GRAMMARS set: {}", broken.join(", "));
+    }
+    if let Some(shared) = shared_cache::RedisCache::from_env().await.expect("Failed to initialise Redis cache") {
+        tracing::info!("Shared Redis cache configured");
+        parser = parser.with_shared_cache(Arc::new(shared));
+    }
+    let parser = Arc::new(parser);
+    tokio::spawn(reload_on_sighup(config.clone(), parser.clone()));
+    let tokenizers = Arc::new(TokenizerRegistry::new());
+    let embedder = EmbeddingConfig::from_env().map(|config| {
+        tracing::info!("Embedding endpoint configured: {}", config.url);
+        Arc::new(EmbeddingClient::new(config).expect("Failed to create embedding client"))
+    });
+    let sink = VectorSink::from_env()
+        .await
+        .expect("Failed to initialise vector store")
+        .map(|sink| {
+            tracing::info!("Vector store configured: {}", sink.name());
+            Arc::new(sink)
+        });
+    let events = events::EventBus::from_env()
+        .await
+        .expect("Failed to initialise event bus")
+        .map(|bus| {
+            tracing::info!("Event bus configured: {}", bus.name());
+            Arc::new(bus)
+        });
+    let hooks = Arc::new(HookPipeline::from_env().expect("Invalid ENRICHMENT_HOOKS"));
+    if !hooks.is_empty() {
+        tracing::info!("{} enrichment hook(s) configured", hooks.len());
+    }
+    let postprocess = Arc::new(postprocess::PostProcessor::from_env().expect("Invalid SYMBOL_POSTPROCESSORS"));
+    if !postprocess.is_empty() {
+        tracing::info!("{} symbol post-processing stage(s) configured", postprocess.len());
+    }
+    let job_concurrency = std::env::var("INDEX_JOB_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(jobs::DEFAULT_JOB_CONCURRENCY);
+    let work = priority::WorkQueue::from_env();
+    let jobs = Arc::new(jobs::JobRegistry::new(job_concurrency));
+    // Repository-wide work is turned away with 429 while the queue is full
+    let backpressure = priority::Backpressure::from_env(work.clone(), jobs.clone());
+    let limits = capabilities::Limits {
+        max_file_bytes,
+        job_concurrency,
+        max_queue_depth: backpressure.as_ref().map(|limit| limit.max_depth()),
+        default_page_size: page::DEFAULT_PAGE_SIZE,
+        max_page_size: page::MAX_PAGE_SIZE,
+    };
+    let state = AppState {
+        config: config.clone(),
+        parser,
+        tokenizers,
+        embedder,
+        sink,
+        hooks,
+        postprocess,
+        registry: Arc::new(indexing::IndexRegistry::default()),
+        usage: Arc::new(auth::UsageTracker::default()),
+        jobs,
+        webhooks: Arc::new(webhook::JobWebhooks::from_env().expect("Failed to create webhook client")),
+        events,
+        artifacts: artifacts::ArtifactStore::from_env()
+            .expect("Invalid ARTIFACT_STORE_URL")
+            .map(Arc::new),
+        scheduler: Arc::new(schedule::Scheduler::default()),
+        work,
+        checkpoints: checkpoint::CheckpointStore::from_env()
+            .expect("Invalid JOB_STATE_DIR")
+            .map(Arc::new),
+        workers: distributed::WorkerPool::from_env()
+            .expect("Failed to create worker client")
+            .map(|pool| {
+                tracing::info!("Coordinating index runs across {} worker(s)", pool.size());
+                Arc::new(pool)
+            }),
+        limits: Arc::new(limits),
+    };
+    resume_index_jobs(&state);
+    tokio::spawn(run_schedules(state.clone()));
+    let auth_state = auth::AuthState {
+        config: config.clone(),
+        usage: state.usage.clone(),
+        jwt: Arc::new(jwt::JwtVerifier::default()),
+    };
+
+    // Repository-wide work, subject to the backpressure limit above
+    let mut batch = Router::new()
+        .route("/export/jsonl/:repo_path", get(export_jsonl))
+        .route("/symbols/:repo_path", get(list_symbols))
+        .route("/export/artifacts/:repo_path", post(export_artifacts))
+        .route("/index/:repo_path", post(index_repo))
+        .route("/jobs/index", post(submit_index_job));
+    let mut admin_batch = Router::new()
+        .route("/repos/:repo_path/reindex", post(index_repo))
+        .route("/warmup", post(warmup))
+        .route("/index/shard", post(index_shard));
+    if let Some(limit) = backpressure {
+        batch = batch.route_layer(axum::middleware::from_fn_with_state(limit.clone(), priority::backpressure));
+        admin_batch = admin_batch.route_layer(axum::middleware::from_fn_with_state(limit, priority::backpressure));
+    }
+
+    let admin = Router::new()
+        .merge(admin_batch)
+        .route("/repos", get(list_repos))
+        .route("/repos/:repo_path", delete(delete_repo))
+        .route("/cache/stats", get(cache_stats))
+        .route("/cache/purge", post(purge_cache))
+        .route("/config/reload", post(reload_config))
+        .route("/keys/usage", get(key_usage))
+        .route("/work/stats", get(work_stats))
+        .route("/schedules", get(list_schedules))
+        .route("/schedules/:repo_path", axum::routing::put(set_schedule).delete(delete_schedule))
+        .route_layer(axum::middleware::from_fn(auth::require_admin));
+
+    // Single-file work an editor waits on, scheduled ahead of batch index runs
+    let interactive = Router::new()
+        .route("/extract/:repo_path/*file_path", get(extract_symbols_query).post(extract_symbols))
+        .route("/extract-deps/:repo_path/*file_path", post(extract_dependencies))
+        .route("/hash/:repo_path/*file_path", get(get_chunk_hash_query).post(get_chunk_hash))
+        .route("/chunk/:repo_path/*file_path", post(chunk_file))
+        .route("/chunk-diff/:repo_path/*file_path", post(chunk_diff))
+        .route("/diagnostics/:repo_path/*file_path", get(file_diagnostics))
+        .route("/meta/:repo_path/*file_path", get(file_meta))
+        .route("/ast/:repo_path/*file_path", get(file_ast))
+        .route("/highlight/:repo_path/*file_path", get(file_highlight))
+        .route("/query/:repo_path/*file_path", get(run_query_pack))
+        .route("/selection/:repo_path/*file_path", get(selection_ranges))
+        .route("/literals/:repo_path/*file_path", get(file_literals))
+        .route("/index/:repo_path/*file_path", post(index_file))
+        .route("/card/*symbol_id", get(symbol_card))
+        .route("/context/*symbol_id", get(expand_context))
+        .route("/hierarchy/*symbol_id", get(type_hierarchy))
+        .route("/implementations/*symbol_id", get(find_implementations))
+        .route_layer(axum::middleware::from_fn_with_state(state.work.clone(), priority::interactive));
+
+    let mut api = Router::new()
+        .merge(interactive)
+        .merge(batch)
+        .route("/capabilities", get(capabilities))
+        .route("/languages", get(list_languages))
+        .route("/query-packs", get(list_query_packs))
+        .route("/jobs/:job_id", get(job_status))
+        .route("/context/pack", post(pack_context))
+        .route("/definition", get(find_definition))
+        .route("/stats/functions/:repo_path", get(rank_functions))
+        .route("/stats/languages/:repo_path", get(language_stats))
+        .route("/stats/documentation/:repo_path", get(documentation_stats))
+        .route("/stats/usage/:repo_path", get(usage_stats))
+        .route("/traits/:repo_path", get(trait_implementations))
+        .route("/literals/:repo_path", get(repo_literals))
+        .route("/routes/:repo_path", get(route_catalog))
+        .route("/owners/:repo_path", get(owned_symbols))
+        .route("/owners/:repo_path/*file_path", get(file_owners))
+        .route("/report/api-surface/:repo_path", get(api_surface_report))
+        .route("/report/api-diff/:repo_path", get(api_diff_report))
+        .nest("/admin", admin)
+        .route_layer(axum::middleware::from_fn(auth::partition_caches))
+        .route_layer(axum::middleware::from_fn(auth::restrict_to_roots))
+        .route_layer(axum::middleware::from_fn_with_state(config.clone(), jail::confine));
+    if let Some(log) = audit::AuditLog::from_env().expect("Invalid audit log configuration") {
+        tracing::info!("Audit log enabled ({})", log.name());
+        api = api.route_layer(axum::middleware::from_fn_with_state(Arc::new(log), audit::record));
+    }
+    let api = api.route_layer(axum::middleware::from_fn_with_state(auth_state, auth::authenticate));
+
+    // Probes and metrics stay reachable without a key; the API is served
+    // under /v1 and, for clients predating versioning, without a prefix
+    let app = Router::new()
+        .route("/health", get(health_check))
+        .route("/livez", get(livez))
+        .route("/readyz", get(readyz))
+        .route("/metrics", get(metrics_text))
+        .nest("/v1", api.clone())
+        .merge(api)
+        .layer(
+            ServiceBuilder::new()
+                .layer(axum::middleware::from_fn(listener::track_activity))
+                .layer(axum::middleware::from_fn(timing::annotate))
+                .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+                .layer(TraceLayer::new_for_http().make_span_with(middleware::request_span))
+                .layer(PropagateRequestIdLayer::x_request_id())
+                .layer(axum::middleware::from_fn(middleware::error_body))
+                .layer(axum::middleware::from_fn(version::negotiate))
+                .layer(CatchPanicLayer::custom(middleware::panic_response)),
+        )
+        .layer(CorsLayer::permissive())
+        .with_state(state);
+
+    let activated = listener::ActivatedListener::from_env().expect("Invalid systemd socket");
+    let server = async move {
+        if let Some(socket) = activated {
+            return socket.serve(app).await;
+        }
+        let listen = listener::ListenConfig::from_env()?;
+        match (listen.tcp, listen.unix) {
+            (Some(addr), Some(path)) => tokio::select! {
+                result = listener::serve_tcp(&addr, app.clone()) => result,
+                result = listener::serve_unix(&path, app) => result,
+            },
+            (Some(addr), None) => listener::serve_tcp(&addr, app).await,
+            (None, Some(path)) => listener::serve_unix(&path, app).await,
+            (None, None) => unreachable!("ListenConfig requires at least one listener"),
+        }
+    };
+    let result = match listener::idle_timeout_from_env() {
+        Some(timeout) => tokio::select! {
+            result = server => result,
+            _ = listener::idle_exit(timeout) => {
+                tracing::info!("No requests for {:?}, exiting", timeout);
+                Ok(())
+            }
+        },
+        None => server.await,
+    };
+    result.expect("Server failed");
+}
+
+/// Human-readable logs by default; one JSON object per event with
+/// `LOG_FORMAT=json`, including the fields of the enclosing request span.
+fn init_tracing() {
+    let format = std::env::var("LOG_FORMAT").unwrap_or_default();
+    if format == "json" {
+        tracing_subscriber::fmt().json().with_current_span(true).with_span_list(false).init();
+    } else {
+        tracing_subscriber::fmt::init();
+        if !format.is_empty() && format != "text" {
+            tracing::warn!("Unknown LOG_FORMAT {:?}, using text", format);
+        }
+    }
+}
+
+/// Liveness: the process is up and serving requests.
+async fn livez() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+/// Readiness: every dependency a request may need is usable; 503 otherwise.
+async fn readyz(State(state): State<AppState>) -> (StatusCode, Json<health::Readiness>) {
+    let readiness = health::readiness(&state.parser, state.sink.as_deref(), &state.jobs, state.workers.as_deref()).await;
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
+
+async fn metrics_text() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], telemetry::render())
+}
+
+async fn health_check() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "ok",
+        "service": "sherlock-indexer",
+        "version": "0.1.0"
+    }))
+}
+
+/// What this deployment supports, for clients to feature-detect against.
+async fn capabilities(State(state): State<AppState>) -> Json<capabilities::Capabilities> {
+    let grammars = state
+        .parser
+        .grammars()
+        .into_iter()
+        .map(|(language, abi_version)| capabilities::GrammarInfo {
+            error: state.parser.grammar_error(&language).map(str::to_string),
+            language,
+            abi_version,
+        })
+        .collect();
+    Json(capabilities::Capabilities {
+        service: "sherlock-indexer",
+        version: env!("CARGO_PKG_VERSION"),
+        api_versions: version::SUPPORTED,
+        grammars,
+        grammar_abi: capabilities::AbiRange {
+            min: *parser::ABI_VERSIONS.start(),
+            max: *parser::ABI_VERSIONS.end(),
+        },
+        features: capabilities::Features {
+            symbols: true,
+            dependencies: true,
+            reference_counts: true,
+            code_owners: true,
+            hierarchy: true,
+            trait_impls: language::SUPPORTED.contains(&"rust"),
+            implementations: true,
+            api_surface: true,
+            api_diff: true,
+            literals: true,
+            routes: true,
+            chunking: true,
+            chunk_diff: true,
+            diagnostics: true,
+            ast: true,
+            highlighting: true,
+            metrics: true,
+            token_counts: true,
+            symbol_paging: true,
+            embeddings: state.embedder.is_some(),
+            enrichment: !state.hooks.is_empty(),
+            indexing: state.sink.is_some(),
+            search: false,
+            artifacts: state.artifacts.is_some(),
+            events: state.events.is_some(),
+            distributed_indexing: state.workers.is_some(),
+            shared_cache: state.parser.shared_cache().is_some(),
+            query_packs: !state.parser.query_packs().is_empty(),
+            postprocessing: !state.postprocess.is_empty(),
+        },
+        limits: (*state.limits).clone(),
+        success: true,
+    })
+}
+
+/// Each loaded language with its extensions and how much of extraction it supports.
+async fn list_languages(State(state): State<AppState>) -> Json<language::LanguagesResponse> {
+    Json(language::LanguagesResponse {
+        languages: state.parser.languages().iter().map(|name| state.parser.language_info(name)).collect(),
+        success: true,
+    })
+}
+
+async fn extract_symbols(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Json(payload): Json<ExtractRequest>,
+) -> Result<Json<ExtractResponse>, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+    let tokenizer = if payload.include_tokens {
+        match state.tokenizers.get(payload.tokenizer.as_deref()) {
+            Ok(tokenizer) => Some(tokenizer),
+            Err(e) => {
+                tracing::error!("Failed to load tokenizer: {}", e);
+                return Err(StatusCode::BAD_REQUEST);
+            }
+        }
+    } else {
+        None
+    };
+    let options = ExtractOptions {
+        include_metrics: payload.include_metrics,
+        include_halstead: payload.include_halstead,
+        tokenizer,
+        language: payload.language.clone(),
+        filter: payload.filter(),
+    };
+
+    let started = Instant::now();
+    match state.parser.extract_symbols(&full_path, &options).await {
+        Ok(mut symbols) => {
+            let external = repo::is_external(std::path::Path::new(&file_path));
+            for symbol in &mut symbols {
+                symbol.external = external;
+            }
+            state.postprocess.apply(&mut symbols).await;
+            let next_cursor = match (payload.limit, payload.cursor.as_deref()) {
+                (None, None) => {
+                    if let Some(sort) = payload.sort {
+                        page::sort(&mut symbols, sort, payload.order);
+                    }
+                    None
+                }
+                (limit, cursor) => {
+                    let sort = payload.sort.unwrap_or_default();
+                    let (page, next) = page::paginate(symbols, sort, payload.order, limit, cursor)?;
+                    symbols = page;
+                    next
+                }
+            };
+            if payload.enrich && !state.hooks.is_empty() {
+                match encoding::read(&full_path).await {
+                    Ok(source) => state.hooks.enrich_symbols(&mut symbols, &source.text).await,
+                    Err(e) => tracing::warn!("Skipping enrichment for {}: {}", full_path, e),
+                }
+            }
+            let language = payload.language.as_deref();
+            let errors = state.parser.syntax_errors(&full_path, language).await.unwrap_or_default();
+            tracing::info!(
+                repo = %repo_path,
+                file = %file_path,
+                language = state.parser.language_for(&full_path).as_deref().unwrap_or("unknown"),
+                duration_ms = started.elapsed().as_millis() as u64,
+                symbol_count = symbols.len(),
+                error_count = errors.len(),
+                "Extracted symbols"
+            );
+            Ok(Json(ExtractResponse {
+                symbols,
+                has_errors: !errors.is_empty(),
+                error_count: errors.len(),
+                error_ranges: errors,
+                lossy_decoded: state.parser.lossy_decoded(&full_path, language).await,
+                binary_file: false,
+                next_cursor,
+                success: true,
+            }))
+        }
+        Err(e) if error::is_binary(&e) => {
+            tracing::info!(repo = %repo_path, file = %file_path, "Skipped binary file");
+            Ok(Json(ExtractResponse {
+                symbols: Vec::new(),
+                has_errors: false,
+                error_count: 0,
+                error_ranges: Vec::new(),
+                lossy_decoded: false,
+                binary_file: true,
+                next_cursor: None,
+                success: true,
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to extract symbols: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// `GET /extract/...` with the request fields as query parameters.
+async fn extract_symbols_query(
+    state: State<AppState>,
+    path: Path<(String, String)>,
+    Query(query): Query<ExtractRequest>,
+) -> Result<Json<ExtractResponse>, StatusCode> {
+    extract_symbols(state, path, Json(query)).await
+}
+
+async fn extract_dependencies(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Json(_payload): Json<ExtractRequest>,
+) -> Result<Json<ExtractResponse>, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+
+    match state.parser.extract_dependencies(&full_path).await {
+        Ok(deps) => Ok(Json(ExtractResponse {
+            symbols: deps,
+            has_errors: false,
+            error_count: 0,
+            error_ranges: Vec::new(),
+            lossy_decoded: false,
+            binary_file: false,
+            next_cursor: None,
+            success: true,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to extract dependencies: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+async fn get_chunk_hash(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Json(payload): Json<ExtractRequest>,
+) -> Result<Response, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+
+    match state.parser.get_chunk_hash(&full_path, payload.start_line, payload.end_line).await {
+        Ok(hash) => Ok(Json(serde_json::json!({
+            "hash": hash,
+            "success": true
+        }))
+        .into_response()),
+        Err(e) => match error::source_error(&e) {
+            Some(range @ error::SourceError::InvalidLineRange { line_count, .. }) => {
+                tracing::warn!("Rejected chunk hash request for {}: {}", full_path, range);
+                let body = Json(serde_json::json!({
+                    "success": false,
+                    "error": range.to_string(),
+                    "line_count": line_count,
+                }));
+                Ok((StatusCode::BAD_REQUEST, body).into_response())
+            }
+            _ => {
+                tracing::error!("Failed to get chunk hash: {}", e);
+                Err(error::status(&e))
+            }
+        },
+    }
+}
+
+/// `GET /hash/...?start_line=..&end_line=..`.
+async fn get_chunk_hash_query(
+    state: State<AppState>,
+    path: Path<(String, String)>,
+    Query(query): Query<ExtractRequest>,
+) -> Result<Response, StatusCode> {
+    get_chunk_hash(state, path, Json(query)).await
+}
+
+/// Locations of ERROR and MISSING nodes in a file's parse tree, for CI checks
+/// and editor underlines.
+async fn file_diagnostics(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Query(query): Query<LanguageQuery>,
+) -> Result<Json<DiagnosticsResponse>, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+
+    match state.parser.diagnostics(&full_path, query.language.as_deref()).await {
+        Ok(diagnostics) => Ok(Json(DiagnosticsResponse {
+            language: query.language.or_else(|| state.parser.language_for(&full_path)),
+            file_path,
+            has_errors: !diagnostics.is_empty(),
+            diagnostics,
+            success: true,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to collect diagnostics: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// Size, language and content hash of a file, without parsing it, so sync
+/// clients can tell whether a full extract is needed. HEAD answers with just
+/// the headers: `ETag` (the hash) and `X-Language`.
+async fn file_meta(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+) -> Result<Response, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+    let size = match tokio::fs::metadata(&full_path).await {
+        Ok(metadata) if metadata.is_file() => metadata.len(),
+        Ok(_) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => return Err(error::status(&anyhow::Error::from(e))),
+    };
+    let language = state.parser.language_for(&full_path);
+    let (hash, binary_file) = match state.parser.get_chunk_hash(&full_path, None, None).await {
+        Ok(hash) => (Some(hash), false),
+        Err(e) if error::is_binary(&e) => (None, true),
+        Err(e) => match error::source_error(&e) {
+            Some(error::SourceError::TooLarge { .. }) => (None, false),
+            _ => {
+                tracing::error!("Failed to hash {}: {}", full_path, e);
+                return Err(error::status(&e));
+            }
+        },
+    };
+
+    let mut response = Json(FileMeta {
+        file_path,
+        size,
+        language: language.clone(),
+        hash: hash.clone(),
+        binary_file,
+        generated: generated::check(std::path::Path::new(&full_path)),
+        success: true,
+    })
+    .into_response();
+    if let Some(value) = hash.and_then(|hash| header::HeaderValue::from_str(&format!("\"{}\"", hash)).ok()) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    if let Some(value) = language.and_then(|language| header::HeaderValue::from_str(&language).ok()) {
+        response.headers_mut().insert(timing::LANGUAGE_HEADER, value);
+    }
+    Ok(response)
+}
+
+const SEXP_CONTENT_TYPE: &str = "text/x-sexp";
+
+/// The parse tree of a file (or of the smallest node spanning a line range),
+/// for debugging extractors and for analyses that need raw tree access. JSON
+/// nodes by default, filtered by `max_depth` and `kinds`; tree-sitter's
+/// s-expression as plain text with `Accept: text/x-sexp` or `format=sexp`.
+async fn file_ast(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Query(query): Query<AstQuery>,
+    headers: axum::http::HeaderMap,
+) -> Result<Response, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+    let language = query.language.as_deref();
+    let wants_sexp = query.format.as_deref() == Some("sexp")
+        || headers
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .is_some_and(|accept| accept.contains(SEXP_CONTENT_TYPE));
+
+    let result = if wants_sexp {
+        state
+            .parser
+            .sexp(&full_path, language, query.start_line, query.end_line)
+            .await
+            .map(|(sexp, _)| ([(header::CONTENT_TYPE, SEXP_CONTENT_TYPE)], sexp).into_response())
+    } else {
+        state
+            .parser
+            .ast(&full_path, language, query.start_line, query.end_line, &query.filter())
+            .await
+            .map(|(nodes, language)| {
+                Json(AstResponse {
+                    file_path,
+                    language,
+                    nodes,
+                    success: true,
+                })
+                .into_response()
+            })
+    };
+    result.map_err(|e| {
+        tracing::error!("Failed to dump AST for {}: {}", full_path, e);
+        error::status(&e)
+    })
+}
+
+/// Syntax highlighting spans from the grammar's highlight query, computed on
+/// the same cached parse the other endpoints use.
+async fn file_highlight(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Query(query): Query<HighlightQuery>,
+) -> Result<Json<HighlightResponse>, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+    match state
+        .parser
+        .highlight(&full_path, query.language.as_deref(), query.start_line, query.end_line)
+        .await
+    {
+        Ok((spans, language)) => Ok(Json(HighlightResponse {
+            file_path,
+            language,
+            spans,
+            success: true,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to highlight {}: {}", full_path, e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+async fn list_query_packs(State(state): State<AppState>) -> Json<QueryPacksResponse> {
+    Json(QueryPacksResponse {
+        packs: state.parser.query_packs(),
+        success: true,
+    })
+}
+
+/// Matches of a loaded query pack in a file.
+async fn run_query_pack(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Query(query): Query<QueryPackQuery>,
+) -> Result<Json<QueryPackResponse>, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+    match state
+        .parser
+        .run_query_pack(&full_path, query.language.as_deref(), &query.pack, query.start_line, query.end_line)
+        .await
+    {
+        Ok((matches, language)) => Ok(Json(QueryPackResponse {
+            file_path,
+            language,
+            pack: query.pack,
+            matches,
+            success: true,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to run query pack {} on {}: {}", query.pack, full_path, e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// String literals and named constants in a file, e.g. user-facing text to
+/// translate or configuration keys.
+async fn file_literals(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Query(query): Query<LiteralQuery>,
+) -> Result<Json<FileLiteralsResponse>, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+    match state.parser.literals(&full_path, query.language.as_deref(), &query.filter()).await {
+        Ok((literals, language)) => Ok(Json(FileLiteralsResponse {
+            file_path,
+            language,
+            literals,
+            success: true,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to extract literals from {}: {}", full_path, e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// String literals and named constants across a repository.
+async fn repo_literals(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Query(query): Query<LiteralQuery>,
+) -> Result<Json<LiteralsResponse>, StatusCode> {
+    match state.parser.repo_literals(&repo_path, &query.filter()).await {
+        Ok(literals) => Ok(Json(LiteralsResponse { literals, success: true })),
+        Err(e) => {
+            tracing::error!("Failed to extract repository literals: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// HTTP endpoints a repository declares through web frameworks, with their
+/// handlers, ordered by path.
+async fn route_catalog(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Query(query): Query<RouteQuery>,
+) -> Result<Json<RoutesResponse>, StatusCode> {
+    let mut routes = match state.parser.repo_routes(&repo_path).await {
+        Ok(routes) => routes,
+        Err(e) => {
+            tracing::error!("Failed to extract repository routes: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+    if let Some(method) = &query.method {
+        routes.retain(|route| route.method.eq_ignore_ascii_case(method) || route.method == "ANY");
+    }
+    if let Some(framework) = &query.framework {
+        routes.retain(|route| route.framework.eq_ignore_ascii_case(framework));
+    }
+    routes.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.method.cmp(&b.method)));
+    Ok(Json(RoutesResponse { routes, success: true }))
+}
+
+/// Progressively larger syntactic ranges around a cursor, for "expand selection".
+async fn selection_ranges(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Query(query): Query<SelectionQuery>,
+) -> Result<Json<SelectionResponse>, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+    match state
+        .parser
+        .selection_ranges(&full_path, query.language.as_deref(), query.line, query.column)
+        .await
+    {
+        Ok((ranges, language)) => Ok(Json(SelectionResponse {
+            file_path,
+            language,
+            ranges,
+            success: true,
+        })),
+        Err(e) => {
+            tracing::error!("Failed to compute selection ranges for {}: {}", full_path, e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// Resolves a chunk request against the configured settings for the file's language.
+fn chunk_options(state: &AppState, payload: &ChunkRequest, file_path: &str) -> Result<ChunkOptions, StatusCode> {
+    let settings = payload.settings.clone().or(&state.config.current().chunking_for(file_path));
+    let mut options = settings.resolve(&state.tokenizers, payload.include_tokens).map_err(|e| {
+        tracing::error!("Invalid chunk settings: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    options.language = payload.language.clone();
+    Ok(options)
+}
+
+/// Runs the enrichment and embedding stages a chunk request asks for,
+/// returning the embedding model used, if any.
+async fn process_chunks(
+    state: &AppState,
+    payload: &ChunkRequest,
+    chunks: &mut [CodeChunk],
+) -> Result<Option<String>, StatusCode> {
+    let embedder = match (payload.embed, &state.embedder) {
+        (false, _) => None,
+        (true, Some(embedder)) => Some(embedder.clone()),
+        (true, None) => {
+            tracing::error!("Embedding requested but EMBEDDING_URL is not configured");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+
+    if payload.enrich {
+        state.hooks.enrich_chunks(chunks).await;
+    }
+
+    if let Some(embedder) = &embedder {
+        let texts: Vec<String> = chunks.iter().map(|c| c.content.clone()).collect();
+        match embedder.embed(&texts).await {
+            Ok(vectors) => {
+                for (chunk, vector) in chunks.iter_mut().zip(vectors) {
+                    chunk.embedding = Some(vector);
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to embed chunks: {}", e);
+                return Err(StatusCode::BAD_GATEWAY);
+            }
+        }
+    }
+
+    Ok(embedder.and_then(|e| e.model().map(str::to_string)))
+}
+
+async fn chunk_file(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Json(payload): Json<ChunkRequest>,
+) -> Result<Json<ChunkResponse>, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+    let options = chunk_options(&state, &payload, &full_path)?;
+
+    let started = Instant::now();
+    let mut chunks = match state.parser.chunk_file(&full_path, &options).await {
+        Ok(chunks) => chunks,
+        Err(e) if error::is_binary(&e) => {
+            tracing::info!(repo = %repo_path, file = %file_path, "Skipped binary file");
+            return Ok(Json(ChunkResponse {
+                chunks: Vec::new(),
+                binary_file: true,
+                tokenizer: None,
+                embedding_model: None,
+                success: true,
+            }));
+        }
+        Err(e) => {
+            tracing::error!("Failed to chunk file: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+    tracing::info!(
+        repo = %repo_path,
+        file = %file_path,
+        language = state.parser.language_for(&full_path).as_deref().unwrap_or("unknown"),
+        duration_ms = started.elapsed().as_millis() as u64,
+        chunk_count = chunks.len(),
+        "Chunked file"
+    );
+    let embedding_model = process_chunks(&state, &payload, &mut chunks).await?;
+
+    Ok(Json(ChunkResponse {
+        chunks,
+        binary_file: false,
+        tokenizer: options.tokenizer.map(|t| t.name().to_string()),
+        embedding_model,
+        success: true,
+    }))
+}
+
+/// Re-chunks a file and returns only chunks whose hash is not in the caller's
+/// previous manifest, plus tombstones for manifest entries that disappeared.
+/// Enrichment and embedding run on the changed chunks only.
+async fn chunk_diff(
+    State(state): State<AppState>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+    Json(payload): Json<ChunkDiffRequest>,
+) -> Result<Json<ChunkDiffResponse>, StatusCode> {
+    let full_path = repo::join(&repo_path, &file_path);
+    let options = chunk_options(&state, &payload.chunking, &full_path)?;
+
+    let chunks = match state.parser.chunk_file(&full_path, &options).await {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            tracing::error!("Failed to chunk file: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+
+    let mut diff = chunker::diff_chunks(chunks, &payload.previous);
+    let embedding_model = process_chunks(&state, &payload.chunking, &mut diff.changed).await?;
+
+    Ok(Json(ChunkDiffResponse {
+        changed: diff.changed,
+        removed: diff.removed,
+        unchanged: diff.unchanged,
+        tokenizer: options.tokenizer.map(|t| t.name().to_string()),
+        embedding_model,
+        success: true,
+    }))
+}
+
+async fn rank_functions(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Query(query): Query<RankingQuery>,
+) -> Result<Json<ExtractResponse>, StatusCode> {
+    let options = ExtractOptions {
+        include_metrics: true,
+        include_halstead: query.include_halstead,
+        ..Default::default()
+    };
+
+    match state.parser.extract_repo_symbols(&repo_path, &options).await {
+        Ok(symbols) => {
+            let mut symbols: Vec<_> = symbols
+                .into_iter()
+                .filter(|s| s.symbol_type == "function" || s.symbol_type == "method")
+                .collect();
+            symbols.sort_by_key(|s| {
+                std::cmp::Reverse(s.metrics.as_ref().map(|m| query.metric.value(m)).unwrap_or(0))
+            });
+            symbols.truncate(query.limit.unwrap_or(20));
+
+            Ok(Json(ExtractResponse {
+                symbols,
+                has_errors: false,
+                error_count: 0,
+                error_ranges: Vec::new(),
+                lossy_decoded: false,
+                binary_file: false,
+                next_cursor: None,
+                success: true,
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to rank functions: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// Every symbol in a repository, in the requested order, one page at a time.
+async fn list_symbols(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Query(query): Query<SymbolListQuery>,
+) -> Result<Json<ExtractResponse>, StatusCode> {
+    match state.parser.extract_repo_symbols(&repo_path, &ExtractOptions::default()).await {
+        Ok(mut symbols) => {
+            if let Some(owners) = load_owners(&repo_path)? {
+                owners.annotate(&repo_path, &mut symbols);
+            }
+            if let Some(external) = query.external {
+                symbols.retain(|s| s.external == external);
+            }
+            state.postprocess.apply(&mut symbols).await;
+            let limit = query.limit.unwrap_or(page::DEFAULT_PAGE_SIZE);
+            let (symbols, next_cursor) =
+                page::paginate(symbols, query.sort, query.order, Some(limit), query.cursor.as_deref())?;
+            Ok(Json(ExtractResponse {
+                symbols,
+                has_errors: false,
+                error_count: 0,
+                error_ranges: Vec::new(),
+                lossy_decoded: false,
+                binary_file: false,
+                next_cursor,
+                success: true,
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to list symbols: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// Symbols ranked by how many others reference them, most or least used first.
+async fn usage_stats(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<ExtractResponse>, StatusCode> {
+    let mut symbols = match state.parser.extract_repo_symbols(&repo_path, &ExtractOptions::default()).await {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            tracing::error!("Failed to extract repository symbols: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+    stats::ReferenceCounts::from_symbols(&symbols).annotate(&mut symbols);
+    state.postprocess.apply(&mut symbols).await;
+
+    if query.exported_only {
+        symbols.retain(|s| s.exported);
+    }
+    match query.order {
+        UsageOrder::Most => symbols.sort_by_key(|s| std::cmp::Reverse(s.reference_count.unwrap_or(0))),
+        UsageOrder::Least => symbols.sort_by_key(|s| s.reference_count.unwrap_or(0)),
+    }
+    symbols.truncate(query.limit.unwrap_or(20));
+
+    Ok(Json(ExtractResponse {
+        symbols,
+        has_errors: false,
+        error_count: 0,
+        error_ranges: Vec::new(),
+        lossy_decoded: false,
+        binary_file: false,
+        next_cursor: None,
+        success: true,
+    }))
+}
+
+async fn language_stats(
+    Path(repo_path): Path<String>,
+) -> Result<Json<stats::LanguageBreakdown>, StatusCode> {
+    match stats::language_breakdown(&repo_path).await {
+        Ok(breakdown) => Ok(Json(breakdown)),
+        Err(e) => {
+            tracing::error!("Failed to compute language stats: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+async fn documentation_stats(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+) -> Result<Json<stats::DocumentationReport>, StatusCode> {
+    match stats::documentation_report(&state.parser, &repo_path).await {
+        Ok(report) => Ok(Json(report)),
+        Err(e) => {
+            tracing::error!("Failed to compute documentation stats: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// Candidate definitions of a name across a repository, for go-to-definition.
+async fn find_definition(
+    State(state): State<AppState>,
+    caller: Option<Extension<auth::Caller>>,
+    Query(query): Query<DefinitionQuery>,
+) -> Result<Json<DefinitionResponse>, StatusCode> {
+    auth::authorize_repo(caller.as_deref(), &query.repo)?;
+    jail::check(&state.config.current().repo_roots, &query.repo, None)?;
+    if query.name.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut symbols = match state.parser.extract_repo_symbols(&query.repo, &ExtractOptions::default()).await {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            tracing::error!("Failed to extract repository symbols: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+    state.postprocess.apply(&mut symbols).await;
+    let definitions = definition::candidates(symbols, &query.name, query.kind.as_deref());
+    Ok(Json(DefinitionResponse {
+        name: query.name,
+        definitions,
+        success: true,
+    }))
+}
+
+async fn pack_context(
+    State(state): State<AppState>,
+    caller: Option<Extension<auth::Caller>>,
+    Json(payload): Json<ContextPackRequest>,
+) -> Result<Json<context::PackedContext>, StatusCode> {
+    auth::authorize_repo(caller.as_deref(), &payload.repo_path)?;
+    jail::check(&state.config.current().repo_roots, &payload.repo_path, None)?;
+    let tokenizer = match state.tokenizers.get(payload.tokenizer.as_deref()) {
+        Ok(tokenizer) => tokenizer,
+        Err(e) => {
+            tracing::error!("Failed to load tokenizer: {}", e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+
+    let mut target_ids = payload.symbol_ids;
+    if let Some(id) = payload.symbol_id {
+        target_ids.insert(0, id);
+    }
+    if target_ids.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let repo_symbols = match state.parser.extract_repo_symbols(&payload.repo_path, &ExtractOptions::default()).await {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            tracing::error!("Failed to extract repository symbols: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+
+    let mut targets = Vec::new();
+    for id in &target_ids {
+        match repo_symbols.iter().find(|s| &s.id == id) {
+            Some(symbol) => targets.push(symbol),
+            None => return Err(StatusCode::NOT_FOUND),
+        }
+    }
+
+    match context::dependency_closure(&state.parser, &repo_symbols, &targets, 1).await {
+        Ok(entries) => Ok(Json(context::pack(entries, payload.token_budget, &tokenizer))),
+        Err(e) => {
+            tracing::error!("Failed to pack context: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// Largest dependency depth accepted by `/context`, to bound the walk.
+const MAX_CONTEXT_DEPTH: usize = 5;
+/// Serialized export lines buffered ahead of a slow client.
+const EXPORT_BUFFER: usize = 256;
+/// How often re-index schedules are checked.
+const SCHEDULE_TICK: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// The repository a request about one symbol is answered from, once the
+/// caller and the configured roots allow it. Symbol ids carry no repository,
+/// so a missing `repo_path` is a 400.
+fn symbol_repo(state: &AppState, caller: Option<&auth::Caller>, repo_path: Option<String>) -> Result<String, StatusCode> {
+    let Some(repo_path) = repo_path.filter(|repo_path| !repo_path.is_empty()) else {
+        tracing::warn!("Symbol request without repo_path");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    auth::authorize_repo(caller, &repo_path)?;
+    jail::check(&state.config.current().repo_roots, &repo_path, None)?;
+    Ok(repo_path)
+}
+
+async fn expand_context(
+    State(state): State<AppState>,
+    caller: Option<Extension<auth::Caller>>,
+    Path(symbol_id): Path<String>,
+    Query(query): Query<ContextQuery>,
+) -> Result<Json<context::ExpandedContext>, StatusCode> {
+    let depth = query.depth.unwrap_or(1).min(MAX_CONTEXT_DEPTH);
+    let repo_path = symbol_repo(&state, caller.as_deref(), query.repo_path)?;
+
+    let repo_symbols = match state.parser.extract_repo_symbols(&repo_path, &ExtractOptions::default()).await {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            tracing::error!("Failed to extract repository symbols: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+
+    let Some(target) = repo_symbols.iter().find(|s| s.id == symbol_id) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    match context::dependency_closure(&state.parser, &repo_symbols, &[target], depth).await {
+        Ok(mut entries) if !entries.is_empty() => {
+            let symbol = entries.remove(0);
+            Ok(Json(context::ExpandedContext {
+                symbol,
+                dependencies: entries,
+                success: true,
+            }))
+        }
+        Ok(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to expand context: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// Exported symbols of a repository by module, with their signatures.
+async fn api_surface_report(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+) -> Result<Json<surface::ApiSurface>, StatusCode> {
+    Ok(Json(api_surface_at(&state, &repo_path, None).await?))
+}
+
+/// Added, removed and changed exported symbols between two git refs.
+async fn api_diff_report(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Query(query): Query<ApiDiffQuery>,
+) -> Result<Json<surface::ApiDiff>, StatusCode> {
+    let base = api_surface_at(&state, &repo_path, Some(&query.base)).await?;
+    let head = api_surface_at(&state, &repo_path, query.head.as_deref()).await?;
+    let head_ref = query.head.as_deref().unwrap_or("working tree");
+    Ok(Json(surface::diff(base, head, &query.base, head_ref)))
+}
+
+/// The API surface of a repository at `git_ref`, or of its working tree.
+/// File paths are reported as they are in the repository either way.
+async fn api_surface_at(state: &AppState, repo_path: &str, git_ref: Option<&str>) -> Result<surface::ApiSurface, StatusCode> {
+    let snapshot = match git_ref {
+        Some(git_ref) => match revision::Snapshot::export(repo_path, git_ref).await {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                tracing::error!("Failed to export {} at {}: {}", repo_path, git_ref, e);
+                return Err(error::status(&e));
+            }
+        },
+        None => None,
+    };
+    let root = match &snapshot {
+        Some(snapshot) => snapshot.path.to_string_lossy().into_owned(),
+        None => repo_path.to_string(),
+    };
+
+    let extracted = state.parser.extract_repo_symbols(&root, &ExtractOptions::default()).await;
+    let mut symbols = match extracted {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            tracing::error!("Failed to extract repository symbols: {}", e);
+            if let Some(snapshot) = snapshot {
+                snapshot.remove().await;
+            }
+            return Err(error::status(&e));
+        }
+    };
+    if let Some(snapshot) = snapshot {
+        for symbol in &mut symbols {
+            let file_path = snapshot.repo_file_path(&symbol.file_path, repo_path);
+            if let Some(rest) = symbol.id.strip_prefix(&symbol.file_path) {
+                symbol.id = format!("{}{}", file_path, rest);
+            }
+            symbol.file_path = file_path;
+        }
+        snapshot.remove().await;
+    }
+    state.postprocess.apply(&mut symbols).await;
+    Ok(surface::api_surface(&state.parser, &symbols, repo_path))
+}
+
+/// A repository's CODEOWNERS rules, `None` when it has no CODEOWNERS file.
+fn load_owners(repo_path: &str) -> Result<Option<owners::CodeOwners>, StatusCode> {
+    owners::CodeOwners::load(std::path::Path::new(repo_path)).map_err(|e| {
+        tracing::error!("Failed to read CODEOWNERS: {}", e);
+        error::status(&anyhow::Error::from(e))
+    })
+}
+
+/// Files and symbols a CODEOWNERS owner owns.
+async fn owned_symbols(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Query(query): Query<OwnerQuery>,
+) -> Result<Json<OwnedSymbols>, StatusCode> {
+    let Some(owners) = load_owners(&repo_path)? else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let owns = |owners: &[String]| owners.iter().any(|owner| owner.eq_ignore_ascii_case(&query.owner));
+
+    let root = std::path::Path::new(&repo_path);
+    let files = repo::source_files(root, &state.parser)
+        .into_iter()
+        .filter_map(|path| path.strip_prefix(root).ok().map(std::path::Path::to_path_buf))
+        .filter(|relative| owners.owners_of(relative).is_some_and(|(_, file_owners)| owns(file_owners)))
+        .map(|relative| relative.to_string_lossy().into_owned())
+        .collect();
+
+    let mut symbols = match state.parser.extract_repo_symbols(&repo_path, &ExtractOptions::default()).await {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            tracing::error!("Failed to extract repository symbols: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+    owners.annotate(&repo_path, &mut symbols);
+    symbols.retain(|symbol| owns(&symbol.owners));
+    state.postprocess.apply(&mut symbols).await;
+
+    Ok(Json(OwnedSymbols {
+        owner: query.owner,
+        files,
+        symbols,
+        success: true,
+    }))
+}
+
+/// Owners of a file under the repository's CODEOWNERS.
+async fn file_owners(Path((repo_path, file_path)): Path<(String, String)>) -> Result<Json<FileOwners>, StatusCode> {
+    let Some(owners) = load_owners(&repo_path)? else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    let (pattern, file_owners) = match owners.owners_of(std::path::Path::new(&file_path)) {
+        Some((pattern, file_owners)) => (Some(pattern.to_string()), file_owners.to_vec()),
+        None => (None, Vec::new()),
+    };
+    Ok(Json(FileOwners {
+        file_path,
+        pattern,
+        owners: file_owners,
+        success: true,
+    }))
+}
+
+/// Rust trait implementations in a repository, with the methods each provides.
+async fn trait_implementations(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Query(query): Query<TraitQuery>,
+) -> Result<Json<traits::TraitImplsResponse>, StatusCode> {
+    let mut symbols = match state.parser.extract_repo_symbols(&repo_path, &ExtractOptions::default()).await {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            tracing::error!("Failed to extract repository symbols: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+    state.postprocess.apply(&mut symbols).await;
+    Ok(Json(traits::TraitImplsResponse {
+        implementations: traits::implementations(&symbols, query.trait_name.as_deref(), query.type_name.as_deref()),
+        success: true,
+    }))
+}
+
+/// Supertypes and subtypes of a class, interface, trait or struct.
+async fn type_hierarchy(
+    State(state): State<AppState>,
+    caller: Option<Extension<auth::Caller>>,
+    Path(symbol_id): Path<String>,
+    Query(query): Query<HierarchyQuery>,
+) -> Result<Json<hierarchy::Hierarchy>, StatusCode> {
+    let repo_path = symbol_repo(&state, caller.as_deref(), query.repo_path)?;
+
+    let mut repo_symbols = match state.parser.extract_repo_symbols(&repo_path, &ExtractOptions::default()).await {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            tracing::error!("Failed to extract repository symbols: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+    state.postprocess.apply(&mut repo_symbols).await;
+
+    let Some(target) = repo_symbols.iter().find(|s| s.id == symbol_id) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    Ok(Json(hierarchy::hierarchy(&repo_symbols, target)))
+}
+
+/// Types implementing an interface, or their versions of one of its methods.
+async fn find_implementations(
+    State(state): State<AppState>,
+    caller: Option<Extension<auth::Caller>>,
+    Path(symbol_id): Path<String>,
+    Query(query): Query<ImplementationsQuery>,
+) -> Result<Json<interfaces::Implementations>, StatusCode> {
+    let repo_path = symbol_repo(&state, caller.as_deref(), query.repo_path)?;
+
+    let mut repo_symbols = match state.parser.extract_repo_symbols(&repo_path, &ExtractOptions::default()).await {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            tracing::error!("Failed to extract repository symbols: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+    state.postprocess.apply(&mut repo_symbols).await;
+
+    let Some(target) = repo_symbols.iter().find(|s| s.id == symbol_id) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    // Neither an interface nor one of its methods
+    let Some(implementations) = interfaces::implementations(&repo_symbols, target) else {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    };
+    Ok(Json(implementations))
+}
+
+async fn symbol_card(
+    State(state): State<AppState>,
+    caller: Option<Extension<auth::Caller>>,
+    Path(symbol_id): Path<String>,
+    Query(query): Query<CardQuery>,
+) -> Result<Response, StatusCode> {
+    let repo_path = symbol_repo(&state, caller.as_deref(), query.repo_path)?;
+
+    let mut repo_symbols = match state.parser.extract_repo_symbols(&repo_path, &ExtractOptions::default()).await {
+        Ok(symbols) => symbols,
+        Err(e) => {
+            tracing::error!("Failed to extract repository symbols: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+    state.postprocess.apply(&mut repo_symbols).await;
+
+    let Some(target) = repo_symbols.iter().find(|s| s.id == symbol_id) else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let card = match context::symbol_card(&state.parser, &repo_symbols, target).await {
+        Ok(card) => card,
+        Err(e) => {
+            tracing::error!("Failed to build symbol card: {}", e);
+            return Err(error::status(&e));
+        }
+    };
+
+    Ok(match query.format {
+        CardFormat::Json => Json(context::SymbolCardResponse { card, success: true }).into_response(),
+        CardFormat::Markdown => {
+            let headers = [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")];
+            (headers, card.to_markdown()).into_response()
+        }
+    })
+}
+
+/// Streams every chunk and/or symbol of a repository as JSONL.
+async fn export_jsonl(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, StatusCode> {
+    if !std::path::Path::new(&repo_path).is_dir() {
+        tracing::error!("Repository path is not a directory: {}", repo_path);
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let exporter = export::Exporter {
+        parser: state.parser.clone(),
+        config: state.config.current(),
+        tokenizers: state.tokenizers.clone(),
+    };
+    let (tx, rx) = tokio::sync::mpsc::channel(EXPORT_BUFFER);
+    let export = export::export_repo(exporter, repo_path, query.kind, query.dedup, tx);
+    tokio::spawn(export.instrument(tracing::Span::current()));
+
+    let headers = [(header::CONTENT_TYPE, "application/x-ndjson")];
+    Ok((headers, Body::from_stream(ReceiverStream::new(rx))).into_response())
+}
+
+/// Writes a versioned set of index artifacts for the repository to object storage.
+async fn export_artifacts(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Query(query): Query<artifacts::ArtifactQuery>,
+) -> Result<Json<artifacts::ArtifactManifest>, StatusCode> {
+    let Some(store) = &state.artifacts else {
+        tracing::error!("Artifact export requires ARTIFACT_STORE_URL to be configured");
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    if !std::path::Path::new(&repo_path).is_dir() {
+        tracing::error!("Repository path is not a directory: {}", repo_path);
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let exporter = export::Exporter {
+        parser: state.parser.clone(),
+        config: state.config.current(),
+        tokenizers: state.tokenizers.clone(),
+    };
+    match store.publish(exporter, &repo_path, query.chunks).await {
+        Ok(manifest) => Ok(Json(manifest)),
+        Err(e) => {
+            tracing::error!("Failed to publish index artifacts: {:#}", e);
+            Err(StatusCode::BAD_GATEWAY)
+        }
+    }
+}
+
+fn index_pipeline<'a>(
+    state: &'a AppState,
+    config: &'a IndexerConfig,
+    tenant: Option<&'a str>,
+) -> Result<indexing::IndexPipeline<'a>, StatusCode> {
+    match (&state.embedder, &state.sink) {
+        (Some(embedder), Some(sink)) => Ok(indexing::IndexPipeline {
+            config,
+            tokenizers: &state.tokenizers,
+            embedder,
+            sink,
+            hooks: &state.hooks,
+            postprocess: &state.postprocess,
+            tenant,
+            events: state.events.as_deref(),
+            work: &state.work,
+            workers: state.workers.as_deref(),
+            references: None,
+            owners: None,
+        }),
+        _ => {
+            tracing::error!("Indexing requires both EMBEDDING_URL and VECTOR_STORE to be configured");
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+async fn index_file(
+    State(state): State<AppState>,
+    caller: Option<Extension<auth::Caller>>,
+    Path((repo_path, file_path)): Path<(String, String)>,
+) -> Result<Json<indexing::IndexSummary>, StatusCode> {
+    let config = state.config.current();
+    let tenant = caller.as_deref().and_then(|caller| caller.tenant.as_deref());
+    let pipeline = index_pipeline(&state, &config, tenant)?;
+    let full_path = repo::join(&repo_path, &file_path);
+
+    let lock = state.jobs.repo_lock(&repo_path);
+    let _repo = lock.lock().await;
+    match indexing::reindex_file(&state.parser, &pipeline, &repo_path, &full_path).await {
+        Ok(summary) => Ok(Json(summary)),
+        Err(e) => {
+            tracing::error!("Failed to index file: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+async fn index_repo(
+    State(state): State<AppState>,
+    caller: Option<Extension<auth::Caller>>,
+    Path(repo_path): Path<String>,
+) -> Result<Json<indexing::IndexSummary>, StatusCode> {
+    let config = state.config.current();
+    let tenant = caller.as_deref().and_then(|caller| caller.tenant.as_deref());
+    let pipeline = index_pipeline(&state, &config, tenant)?;
+
+    let lock = state.jobs.repo_lock(&repo_path);
+    let _repo = lock.lock().await;
+    let progress = indexing::IndexProgress::default();
+    match indexing::reindex_repo(&state.parser, &pipeline, &repo_path, &progress, None).await {
+        Ok(summary) => {
+            state.registry.record(&repo_path, &summary);
+            Ok(Json(summary))
+        }
+        Err(e) => {
+            tracing::error!("Failed to index repository: {}", e);
+            Err(error::status(&e))
+        }
+    }
+}
+
+/// Indexes a shard of a repository for a coordinator instance.
+async fn index_shard(
+    State(state): State<AppState>,
+    Json(request): Json<distributed::ShardRequest>,
+) -> Result<Json<distributed::ShardResponse>, StatusCode> {
+    let config = state.config.current();
+    let tenant = request.tenant.clone();
+    let pipeline = index_pipeline(&state, &config, tenant.as_deref())?;
+    jail::check(&config.repo_roots, &request.repo_path, None)?;
+    if !std::path::Path::new(&request.repo_path).is_dir() {
+        tracing::error!("Repository path is not a directory: {}", request.repo_path);
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    // The shard belongs to the tenant of the run, not of the coordinator's key
+    let shard = indexing::index_shard(&state.parser, &pipeline, request);
+    Ok(Json(cache::partitioned(tenant.clone(), shard).await))
+}
+
+/// Queues a repository index run and answers at once with the job to poll.
+async fn submit_index_job(
+    State(state): State<AppState>,
+    caller: Option<Extension<auth::Caller>>,
+    headers: axum::http::HeaderMap,
+    Json(payload): Json<jobs::IndexJobRequest>,
+) -> Result<(StatusCode, Json<jobs::JobReport>), StatusCode> {
+    auth::authorize_repo(caller.as_deref(), &payload.repo_path)?;
+    jail::check(&state.config.current().repo_roots, &payload.repo_path, None)?;
+    index_pipeline(&state, &state.config.current(), None)?;
+    if let Some(url) = &payload.callback_url {
+        if !webhook::is_allowed_callback(url, &state.config.current().callback_url_prefixes) {
+            tracing::error!("Callback URL {} is not under an allowed prefix", url);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    if !std::path::Path::new(&payload.repo_path).is_dir() {
+        tracing::error!("Repository path is not a directory: {}", payload.repo_path);
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    // Retries with the same key get the job the first attempt created
+    let idempotency_key = match headers.get(jobs::IDEMPOTENCY_KEY_HEADER) {
+        Some(value) => match value.to_str() {
+            Ok(key) if !key.is_empty() && key.len() <= jobs::MAX_IDEMPOTENCY_KEY_LEN => {
+                let owner = caller.as_deref().map(|caller| caller.name.as_str()).unwrap_or_default();
+                Some(format!("{}:{}", owner, key))
+            }
+            _ => {
+                tracing::error!("Invalid Idempotency-Key header");
+                return Err(StatusCode::BAD_REQUEST);
+            }
+        },
+        None => None,
+    };
+
+    let tenant = caller.and_then(|Extension(caller)| caller.tenant);
+    let (job, created) = start_index_job(state, &payload.repo_path, payload.callback_url, tenant, idempotency_key);
+    if created {
+        return Ok((StatusCode::ACCEPTED, Json(job.report())));
+    }
+    if job.repo_path != payload.repo_path {
+        tracing::error!(job_id = %job.id, "Idempotency-Key reused for a different repository");
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    tracing::info!(job_id = %job.id, repo = %job.repo_path, "Returning existing job for Idempotency-Key");
+    Ok((StatusCode::OK, Json(job.report())))
+}
+
+/// Queues and spawns an index job; with an idempotency key that already has
+/// a job, that job is returned with `false` and nothing new runs.
+fn start_index_job(
+    state: AppState,
+    repo_path: &str,
+    callback_url: Option<String>,
+    tenant: Option<String>,
+    idempotency_key: Option<String>,
+) -> (Arc<jobs::Job>, bool) {
+    let (job, created) = state.jobs.submit(repo_path, callback_url, tenant, idempotency_key);
+    if !created {
+        return (job, false);
+    }
+    tracing::info!(job_id = %job.id, repo = %job.repo_path, "Queued index job");
+    let checkpoint = state.checkpoints.as_ref().map(|store| store.open(job.record()));
+    tokio::spawn(run_index_job(state, job.clone(), checkpoint).instrument(tracing::Span::current()));
+    (job, true)
+}
+
+/// Requeues jobs a previous process left unfinished, keeping their checkpoints.
+fn resume_index_jobs(state: &AppState) {
+    let Some(store) = &state.checkpoints else {
+        return;
+    };
+    for record in store.unfinished() {
+        let job = state.jobs.resume(&record);
+        tracing::info!(
+            job_id = %job.id,
+            repo = %job.repo_path,
+            files_done = record.files.len(),
+            "Resuming index job"
+        );
+        let checkpoint = store.open(record);
+        tokio::spawn(run_index_job(state.clone(), job, Some(checkpoint)));
+    }
+}
+
+/// Starts a background index job for every repository whose schedule is due,
+/// unless one is already queued or running for it.
+async fn run_schedules(state: AppState) {
+    let mut ticks = tokio::time::interval(SCHEDULE_TICK);
+    loop {
+        ticks.tick().await;
+        let config = state.config.current();
+        for repo_path in state.scheduler.due(&config, chrono::Utc::now()) {
+            if state.jobs.is_active(&repo_path) {
+                tracing::info!(repo = %repo_path, "Skipping scheduled re-index, a job is already active");
+                continue;
+            }
+            if state.embedder.is_none() || state.sink.is_none() {
+                tracing::warn!(repo = %repo_path, "Scheduled re-index needs EMBEDDING_URL and VECTOR_STORE");
+                continue;
+            }
+            let (job, _) = start_index_job(state.clone(), &repo_path, None, None, None);
+            state.scheduler.record_job(&repo_path, &job.id);
+        }
+    }
+}
+
+async fn work_stats(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "work": state.work.stats(),
+        "success": true,
+    }))
+}
+
+async fn list_schedules(State(state): State<AppState>) -> Json<schedule::ScheduleList> {
+    Json(schedule::ScheduleList {
+        schedules: state.scheduler.list(&state.config.current()),
+        success: true,
+    })
+}
+
+async fn set_schedule(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+    Json(payload): Json<schedule::ScheduleRequest>,
+) -> Result<Json<schedule::ScheduleList>, StatusCode> {
+    if let Err(e) = state.scheduler.set(&repo_path, &payload.cron) {
+        tracing::warn!("Rejected schedule for {}: {:#}", repo_path, e);
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    Ok(list_schedules(State(state)).await)
+}
+
+async fn delete_schedule(State(state): State<AppState>, Path(repo_path): Path<String>) -> StatusCode {
+    match state.scheduler.remove(&repo_path) {
+        true => StatusCode::NO_CONTENT,
+        false => StatusCode::NOT_FOUND,
+    }
+}
+
+async fn run_index_job(state: AppState, job: Arc<jobs::Job>, checkpoint: Option<Arc<checkpoint::Checkpoint>>) {
+    let lock = state.jobs.repo_lock(&job.repo_path);
+    let repo = match lock.clone().try_lock_owned() {
+        Ok(guard) => guard,
+        Err(_) => {
+            job.wait();
+            lock.lock_owned().await
+        }
+    };
+    let config = state.config.current();
+    if state.jobs.is_superseded(&job) {
+        tracing::info!(job_id = %job.id, repo = %job.repo_path, "Index job superseded by a newer one");
+        job.supersede();
+        if let Some(checkpoint) = &checkpoint {
+            checkpoint.finish();
+        }
+    } else {
+        let slot = state.jobs.slot().await;
+        job.start();
+        let result = match index_pipeline(&state, &config, job.tenant.as_deref()) {
+            Ok(pipeline) => {
+                let checkpoint = checkpoint.as_deref();
+                let run = indexing::reindex_repo(&state.parser, &pipeline, &job.repo_path, &job.progress, checkpoint);
+                cache::partitioned(job.tenant.clone(), run).await
+            }
+            Err(_) => Err(anyhow::anyhow!("Indexing is no longer configured")),
+        };
+        match &result {
+            Ok(summary) => {
+                state.registry.record(&job.repo_path, summary);
+                tracing::info!(job_id = %job.id, repo = %job.repo_path, "Index job completed");
+            }
+            Err(e) => tracing::error!(job_id = %job.id, repo = %job.repo_path, "Index job failed: {:#}", e),
+        }
+        job.finish(result);
+        if let Some(checkpoint) = &checkpoint {
+            checkpoint.finish();
+        }
+        drop(slot);
+    }
+    drop(repo);
+
+    let report = job.report();
+    if let Some(events) = &state.events {
+        events
+            .publish(events::Event::JobFinished {
+                job_id: job.id.clone(),
+                repo_path: job.repo_path.clone(),
+                state: report.state,
+                summary: report.summary.clone(),
+            })
+            .await;
+    }
+
+    // Checked again, as a job resumed from a checkpoint may predate a config change
+    let own_callback = job.callback_url.clone().filter(|url| {
+        let allowed = webhook::is_allowed_callback(url, &config.callback_url_prefixes);
+        if !allowed {
+            tracing::warn!(job_id = %job.id, "Not notifying {}: no longer under an allowed prefix", url);
+        }
+        allowed
+    });
+    let callback = own_callback.or_else(|| config.job_callbacks.get(&job.repo_path).cloned());
+    if let Some(url) = callback {
+        state.webhooks.notify(&url, &report).await;
+    }
+}
+
+async fn job_status(
+    State(state): State<AppState>,
+    caller: Option<Extension<auth::Caller>>,
+    Path(job_id): Path<String>,
+) -> Result<Json<jobs::JobReport>, StatusCode> {
+    let job = state.jobs.get(&job_id).ok_or(StatusCode::NOT_FOUND)?;
+    auth::authorize_repo(caller.as_deref(), &job.repo_path)?;
+    Ok(Json(job.report()))
+}
+
+async fn list_repos(State(state): State<AppState>) -> Json<indexing::RepoList> {
+    Json(indexing::RepoList {
+        repos: state.registry.list(),
+        success: true,
+    })
+}
+
+/// Drops a repository's vectors from the configured store, its cached parses,
+/// and its registry entry.
+async fn delete_repo(
+    State(state): State<AppState>,
+    Path(repo_path): Path<String>,
+) -> Result<Json<indexing::RepoDeletion>, StatusCode> {
+    let lock = state.jobs.repo_lock(&repo_path);
+    let _repo = lock.lock().await;
+    if let Some(sink) = &state.sink {
+        if let Err(e) = sink.delete_repo(&repo_path).await {
+            tracing::error!("Failed to delete repository vectors: {}", e);
+            return Err(StatusCode::BAD_GATEWAY);
+        }
+    }
+
+    state.parser.purge_cache(Some(&repo_path));
+    if let Some(shared) = state.parser.shared_cache() {
+        if let Err(e) = shared.purge(Some(&repo_path)).await {
+            tracing::warn!("Failed to purge the shared cache for {}: {}", repo_path, e);
+        }
+    }
+    if let Some(events) = &state.events {
+        events.forget_repo(&repo_path);
+        events
+            .publish(events::Event::RepoDeleted {
+                repo_path: repo_path.clone(),
+            })
+            .await;
+    }
+
+    Ok(Json(indexing::RepoDeletion {
+        sink: state.sink.as_ref().map(|sink| sink.name().to_string()),
+        was_registered: state.registry.remove(&repo_path),
+        repo_path,
+        success: true,
+    }))
+}
+
+async fn cache_stats(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let mut caches = vec![serde_json::json!(state.parser.cache_stats())];
+    if let Some(shared) = state.parser.shared_cache() {
+        caches.push(serde_json::json!(shared.stats()));
+    }
+    Json(serde_json::json!({
+        "caches": caches,
+        "success": true,
+    }))
+}
+
+/// Empties the caches for one repository (`?repo=`) or entirely.
+async fn purge_cache(State(state): State<AppState>, Query(query): Query<CachePurgeQuery>) -> Json<serde_json::Value> {
+    let purged = state.parser.purge_cache(query.repo.as_deref());
+    tracing::info!("Purged {} cached parse(s) for {}", purged, query.repo.as_deref().unwrap_or("all repositories"));
+    let shared_purged = match state.parser.shared_cache() {
+        Some(shared) => match shared.purge(query.repo.as_deref()).await {
+            Ok(count) => Some(count),
+            Err(e) => {
+                tracing::error!("Failed to purge the shared cache: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    Json(serde_json::json!({
+        "purged": purged,
+        "shared_purged": shared_purged,
+        "success": state.parser.shared_cache().is_none() || shared_purged.is_some(),
+    }))
+}
+
+/// Loads every grammar and parses the given hot files into the cache, so the
+/// first requests after a deploy skip the cold start.
+async fn warmup(State(state): State<AppState>, payload: Option<Json<WarmupRequest>>) -> Json<WarmupResponse> {
+    let started = Instant::now();
+    let Json(payload) = payload.unwrap_or_default();
+
+    let grammar_errors = state.parser.broken_grammars();
+    let grammars_loaded = state.parser.languages().len();
+
+    let roots = state.config.current().repo_roots.clone();
+    let mut files_parsed = 0;
+    let mut files_failed = Vec::new();
+    for file in payload.files {
+        if jail::check(&roots, &file, None).is_err() {
+            files_failed.push(file);
+            continue;
+        }
+        match state.parser.preload(&file).await {
+            Ok(()) => files_parsed += 1,
+            Err(e) => {
+                tracing::warn!("Skipping warm-up of {}: {}", file, e);
+                files_failed.push(file);
+            }
+        }
+    }
+
+    Json(WarmupResponse {
+        grammars_loaded,
+        success: grammar_errors.is_empty() && files_failed.is_empty(),
+        grammar_errors,
+        files_parsed,
+        files_failed,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+async fn key_usage(State(state): State<AppState>) -> Json<auth::UsageList> {
+    Json(auth::UsageList {
+        keys: state.usage.list(),
+        success: true,
+    })
+}
+
+/// Re-reads `INDEXER_CONFIG` without restarting, keeping the parse cache warm.
+async fn reload_config(State(state): State<AppState>) -> Result<Json<config::ReloadSummary>, StatusCode> {
+    match state.config.reload(&state.parser) {
+        Ok(summary) => {
+            tracing::info!("Configuration reloaded");
+            Ok(Json(summary))
+        }
+        Err(e) => {
+            tracing::error!("Failed to reload configuration, keeping the current one: {:#}", e);
+            Err(StatusCode::UNPROCESSABLE_ENTITY)
+        }
+    }
+}
+
+/// Reloads the configuration whenever the process receives SIGHUP.
+async fn reload_on_sighup(config: Arc<ConfigHandle>, parser: Arc<ParserService>) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            tracing::warn!("Cannot listen for SIGHUP, reload via /admin/config/reload only: {}", e);
+            return;
+        }
+    };
+    while hangups.recv().await.is_some() {
+        match config.reload(&parser) {
+            Ok(_) => tracing::info!("Configuration reloaded on SIGHUP"),
+            Err(e) => tracing::error!("Failed to reload configuration, keeping the current one: {:#}", e),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crate::telemetry;
+use axum::{
+    body::{Body, HttpBody},
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::any::Any;
+use tracing::Span;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+fn request_id(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+}
+
+/// Span wrapping every request, so each event logged while serving it carries
+/// the request id.
+pub fn request_span(request: &Request) -> Span {
+    tracing::info_span!(
+        "request",
+        request_id = request_id(request).unwrap_or("-"),
+        method = %request.method(),
+        uri = %request.uri().path(),
+    )
+}
+
+/// Gives bare error statuses a JSON body naming the request id, so callers can
+/// quote it when reporting a failure.
+pub async fn error_body(request: Request, next: Next) -> Response {
+    let request_id = request_id(&request).map(str::to_string);
+    let response = next.run(request).await;
+
+    let status = response.status();
+    let is_error = status.is_client_error() || status.is_server_error();
+    if !is_error || response.body().size_hint().exact() != Some(0) {
+        return response;
+    }
+
+    let body = serde_json::json!({
+        "success": false,
+        "error": status.canonical_reason().unwrap_or("Error"),
+        "request_id": request_id,
+    });
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    Response::from_parts(parts, Body::from(body.to_string()))
+}
+
+/// Turns a panic while serving a request into a bare 500 (which `error_body`
+/// then fills in), logging the panic message and counting it.
+pub fn panic_response(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let message = panic
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| panic.downcast_ref::<&str>().copied())
+        .unwrap_or("unknown panic");
+    telemetry::record_panic();
+    tracing::error!("Request handler panicked: {}", message);
+    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+}
@@ -0,0 +1,222 @@
+use crate::symbol::CodeSymbol;
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Page size for repository-wide listings when the caller gives no `limit`.
+pub const DEFAULT_PAGE_SIZE: usize = 1000;
+/// Largest page any listing returns, whatever `limit` asks for.
+pub const MAX_PAGE_SIZE: usize = 10_000;
+
+/// Order of a symbol listing (`sort=`). Ties are broken by position.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolSort {
+    /// File, then line.
+    #[default]
+    Position,
+    Name,
+    /// Symbol type (`function`, `struct`, ...).
+    Kind,
+    /// Line span.
+    Size,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+enum Primary {
+    None,
+    Text(String),
+    Number(i32),
+}
+
+/// Where a page ended. The cursor holds the last symbol's sort key rather
+/// than an offset, so edits to files before the cursor do not shift or
+/// repeat later pages.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct SortKey {
+    primary: Primary,
+    file_path: String,
+    line_start: i32,
+    id: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    sort: SymbolSort,
+    order: SortOrder,
+    after: SortKey,
+}
+
+fn key(symbol: &CodeSymbol, sort: SymbolSort) -> SortKey {
+    let primary = match sort {
+        SymbolSort::Position => Primary::None,
+        SymbolSort::Name => Primary::Text(symbol.symbol_name.to_lowercase()),
+        SymbolSort::Kind => Primary::Text(symbol.symbol_type.clone()),
+        SymbolSort::Size => Primary::Number(symbol.line_end - symbol.line_start + 1),
+    };
+    SortKey {
+        primary,
+        file_path: symbol.file_path.clone(),
+        line_start: symbol.line_start,
+        id: symbol.id.clone(),
+    }
+}
+
+fn compare(a: &SortKey, b: &SortKey, order: SortOrder) -> Ordering {
+    match order {
+        SortOrder::Asc => a.cmp(b),
+        SortOrder::Desc => b.cmp(a),
+    }
+}
+
+fn encode(cursor: &Cursor) -> String {
+    let json = serde_json::to_vec(cursor).unwrap_or_default();
+    json.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode(cursor: &str) -> Option<Cursor> {
+    if cursor.len() % 2 == 1 {
+        return None;
+    }
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(cursor.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Sorts `symbols` by `sort`, ties broken by file, line and id.
+pub fn sort(symbols: &mut [CodeSymbol], sort: SymbolSort, order: SortOrder) {
+    symbols.sort_by_cached_key(|symbol| key(symbol, sort));
+    if order == SortOrder::Desc {
+        symbols.reverse();
+    }
+}
+
+/// The symbols after `cursor` (all of them when `None`) in `sort` order, at
+/// most `limit` (capped at [`MAX_PAGE_SIZE`]), and the cursor for the next
+/// page when more remain. 400 for a cursor this service did not hand out,
+/// or one from a listing sorted by another key or in the other direction.
+pub fn paginate(
+    mut symbols: Vec<CodeSymbol>,
+    by: SymbolSort,
+    order: SortOrder,
+    limit: Option<usize>,
+    cursor: Option<&str>,
+) -> Result<(Vec<CodeSymbol>, Option<String>), StatusCode> {
+    sort(&mut symbols, by, order);
+    if let Some(cursor) = cursor {
+        let Some(cursor) = decode(cursor).filter(|cursor| cursor.sort == by && cursor.order == order) else {
+            tracing::warn!("Rejected malformed or mismatched pagination cursor");
+            return Err(StatusCode::BAD_REQUEST);
+        };
+        let start = symbols.partition_point(|symbol| compare(&key(symbol, by), &cursor.after, order) != Ordering::Greater);
+        symbols.drain(..start);
+    }
+
+    let limit = limit.unwrap_or(usize::MAX).clamp(1, MAX_PAGE_SIZE);
+    if symbols.len() <= limit {
+        return Ok((symbols, None));
+    }
+    symbols.truncate(limit);
+    let next = symbols.last().map(|symbol| {
+        encode(&Cursor {
+            sort: by,
+            order,
+            after: key(symbol, by),
+        })
+    });
+    Ok((symbols, next))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(name: &str, file_path: &str, line_start: i32) -> CodeSymbol {
+        CodeSymbol {
+            id: format!("{}:{}:{}", file_path, name, line_start),
+            symbol_name: name.to_string(),
+            symbol_type: "function".to_string(),
+            file_path: file_path.to_string(),
+            line_start,
+            line_end: line_start + 2,
+            signature: None,
+            dependencies: Vec::new(),
+            exported: false,
+            visibility: None,
+            metrics: None,
+            token_count: None,
+            enrichment: None,
+            qualified_name: None,
+            supertypes: Vec::new(),
+            reference_count: None,
+            owners: Vec::new(),
+            external: false,
+        }
+    }
+
+    fn symbols() -> Vec<CodeSymbol> {
+        vec![
+            symbol("delta", "b.rs", 1),
+            symbol("alpha", "a.rs", 10),
+            symbol("charlie", "a.rs", 1),
+            symbol("bravo", "b.rs", 20),
+            symbol("echo", "c.rs", 5),
+        ]
+    }
+
+    fn names(symbols: &[CodeSymbol]) -> Vec<&str> {
+        symbols.iter().map(|symbol| symbol.symbol_name.as_str()).collect()
+    }
+
+    /// Walks every page of a listing, `limit` symbols at a time.
+    fn walk(by: SymbolSort, order: SortOrder, limit: usize) -> Vec<String> {
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = paginate(symbols(), by, order, Some(limit), cursor.as_deref()).unwrap();
+            seen.extend(page.into_iter().map(|symbol| symbol.symbol_name));
+            match next {
+                Some(next) => cursor = Some(next),
+                None => return seen,
+            }
+        }
+    }
+
+    #[test]
+    fn pages_cover_the_listing_once_in_either_order() {
+        assert_eq!(walk(SymbolSort::Name, SortOrder::Asc, 2), ["alpha", "bravo", "charlie", "delta", "echo"]);
+        assert_eq!(walk(SymbolSort::Name, SortOrder::Desc, 2), ["echo", "delta", "charlie", "bravo", "alpha"]);
+        assert_eq!(walk(SymbolSort::Position, SortOrder::Desc, 3), ["echo", "bravo", "delta", "alpha", "charlie"]);
+    }
+
+    #[test]
+    fn last_page_has_no_cursor() {
+        let (page, next) = paginate(symbols(), SymbolSort::Name, SortOrder::Desc, Some(5), None).unwrap();
+        assert_eq!(names(&page), ["echo", "delta", "charlie", "bravo", "alpha"]);
+        assert!(next.is_none());
+    }
+
+    #[test]
+    fn mismatched_cursor_is_rejected() {
+        let (_, next) = paginate(symbols(), SymbolSort::Name, SortOrder::Asc, Some(2), None).unwrap();
+        let next = next.unwrap();
+        for (by, order) in [(SymbolSort::Position, SortOrder::Asc), (SymbolSort::Name, SortOrder::Desc)] {
+            let rejected = paginate(symbols(), by, order, Some(2), Some(&next)).err();
+            assert_eq!(rejected, Some(StatusCode::BAD_REQUEST));
+        }
+        for garbage in ["zz", "abc", "7b7d"] {
+            let rejected = paginate(symbols(), SymbolSort::Name, SortOrder::Asc, Some(2), Some(garbage)).err();
+            assert_eq!(rejected, Some(StatusCode::BAD_REQUEST));
+        }
+    }
+}
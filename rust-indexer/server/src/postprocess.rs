@@ -0,0 +1,174 @@
+use crate::symbol::CodeSymbol;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// Which symbols a `classify` or `filter` stage applies to. Every condition
+/// given must hold; an empty match selects every symbol.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SymbolMatch {
+    /// `symbol_type` values.
+    #[serde(default)]
+    pub kinds: Vec<String>,
+    pub name_prefix: Option<String>,
+    pub name_suffix: Option<String>,
+    pub path_contains: Option<String>,
+}
+
+impl SymbolMatch {
+    fn matches(&self, symbol: &CodeSymbol) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(&symbol.symbol_type))
+            && self.name_prefix.iter().all(|prefix| symbol.symbol_name.starts_with(prefix.as_str()))
+            && self.name_suffix.iter().all(|suffix| symbol.symbol_name.ends_with(suffix.as_str()))
+            && self.path_contains.iter().all(|part| symbol.file_path.contains(part.as_str()))
+    }
+}
+
+/// One post-processing stage, tagged by `stage`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum Stage {
+    /// Strips the first matching prefix and suffix from symbol names
+    /// (`_private` to `private`, `ParserImpl` to `Parser`).
+    NormalizeNames {
+        #[serde(default)]
+        strip_prefixes: Vec<String>,
+        #[serde(default)]
+        strip_suffixes: Vec<String>,
+    },
+    /// Replaces a leading `from` in file paths, and in the ids built from
+    /// them, with `to`.
+    RewritePaths { from: String, to: String },
+    /// Sets `symbol_type` on the symbols `matching` selects.
+    Classify {
+        symbol_type: String,
+        #[serde(default)]
+        matching: SymbolMatch,
+    },
+    /// Drops the symbols `exclude` selects.
+    Filter { exclude: SymbolMatch },
+    /// Posts `{"symbols": [...]}` to `url`, which answers with the
+    /// transformed `{"symbols": [...]}`.
+    Http {
+        name: String,
+        url: String,
+        #[serde(default = "default_timeout_secs")]
+        timeout_secs: u64,
+    },
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+/// Post-processing applied to extracted symbols before they are returned or
+/// stored, run in configuration order.
+#[derive(Default)]
+pub struct PostProcessor {
+    stages: Vec<Stage>,
+    http: reqwest::Client,
+}
+
+impl PostProcessor {
+    /// Reads stages from `SYMBOL_POSTPROCESSORS`, a JSON array of stages.
+    pub fn from_env() -> Result<Self> {
+        let stages = match std::env::var("SYMBOL_POSTPROCESSORS") {
+            Ok(raw) if !raw.trim().is_empty() => {
+                serde_json::from_str(&raw).context("SYMBOL_POSTPROCESSORS must be a JSON array of stages")?
+            }
+            _ => Vec::new(),
+        };
+        Ok(Self {
+            stages,
+            http: reqwest::Client::new(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Runs every stage over `symbols`. A failing HTTP stage is logged and
+    /// skipped, leaving the symbols as the previous stage left them.
+    pub async fn apply(&self, symbols: &mut Vec<CodeSymbol>) {
+        for stage in &self.stages {
+            match stage {
+                Stage::NormalizeNames { strip_prefixes, strip_suffixes } => {
+                    for symbol in symbols.iter_mut() {
+                        normalize_name(symbol, strip_prefixes, strip_suffixes);
+                    }
+                }
+                Stage::RewritePaths { from, to } => {
+                    for symbol in symbols.iter_mut() {
+                        rewrite_path(symbol, from, to);
+                    }
+                }
+                Stage::Classify { symbol_type, matching } => {
+                    for symbol in symbols.iter_mut().filter(|symbol| matching.matches(symbol)) {
+                        symbol.symbol_type = symbol_type.clone();
+                    }
+                }
+                Stage::Filter { exclude } => symbols.retain(|symbol| !exclude.matches(symbol)),
+                Stage::Http { name, url, timeout_secs } => match self.call(url, *timeout_secs, symbols).await {
+                    Ok(transformed) => *symbols = transformed,
+                    Err(e) => tracing::warn!("Post-processor {} failed: {}", name, e),
+                },
+            }
+        }
+    }
+
+    async fn call(&self, url: &str, timeout_secs: u64, symbols: &[CodeSymbol]) -> Result<Vec<CodeSymbol>> {
+        #[derive(Deserialize)]
+        struct Transformed {
+            symbols: Vec<CodeSymbol>,
+        }
+
+        let response = self
+            .http
+            .post(url)
+            .timeout(Duration::from_secs(timeout_secs))
+            .json(&json!({ "symbols": symbols }))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.json::<Transformed>().await?.symbols)
+    }
+}
+
+fn normalize_name(symbol: &mut CodeSymbol, strip_prefixes: &[String], strip_suffixes: &[String]) {
+    let mut name = symbol.symbol_name.as_str();
+    if let Some(stripped) = strip_prefixes.iter().find_map(|prefix| name.strip_prefix(prefix.as_str())) {
+        name = stripped;
+    }
+    if let Some(stripped) = strip_suffixes.iter().find_map(|suffix| name.strip_suffix(suffix.as_str())) {
+        name = stripped;
+    }
+    // A name that is nothing but the prefix or suffix is left alone
+    if name.is_empty() || name == symbol.symbol_name {
+        return;
+    }
+
+    let name = name.to_string();
+    if let Some(qualified) = &mut symbol.qualified_name {
+        if let Some(outer) = qualified.strip_suffix(&symbol.symbol_name) {
+            *qualified = format!("{}{}", outer, name);
+        }
+    }
+    symbol.symbol_name = name;
+}
+
+fn rewrite_path(symbol: &mut CodeSymbol, from: &str, to: &str) {
+    let Some(rest) = symbol.file_path.strip_prefix(from) else {
+        return;
+    };
+    let rewritten = format!("{}{}", to, rest);
+    if let Some(id_rest) = symbol.id.strip_prefix(&symbol.file_path) {
+        symbol.id = format!("{}{}", rewritten, id_rest);
+    }
+    symbol.file_path = rewritten;
+}
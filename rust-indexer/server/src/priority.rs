@@ -0,0 +1,209 @@
+use crate::jobs::JobRegistry;
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Interactive grants per batch grant while both classes are waiting.
+pub const DEFAULT_INTERACTIVE_WEIGHT: usize = 4;
+pub const DEFAULT_RETRY_AFTER_SECS: u64 = 5;
+
+pub const QUEUE_DEPTH_HEADER: &str = "x-queue-depth";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkClass {
+    /// Single-file requests an editor is waiting on.
+    Interactive,
+    /// Files of repository-wide index runs.
+    Batch,
+}
+
+#[derive(Default)]
+struct Queues {
+    available: usize,
+    interactive: VecDeque<oneshot::Sender<WorkPermit>>,
+    batch: VecDeque<oneshot::Sender<WorkPermit>>,
+    /// Interactive grants since the last batch grant.
+    streak: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkQueueStats {
+    pub slots: usize,
+    pub available: usize,
+    pub interactive_waiting: usize,
+    pub batch_waiting: usize,
+}
+
+/// A fixed number of work slots shared by both classes. While both have
+/// waiters, interactive work gets `interactive_weight` slots for every batch
+/// slot, so a monorepo re-index cannot starve editor requests (nor the
+/// reverse).
+pub struct WorkQueue {
+    slots: usize,
+    interactive_weight: usize,
+    queues: Mutex<Queues>,
+}
+
+/// Holds a work slot; dropping it hands the slot to the next waiter.
+pub struct WorkPermit {
+    queue: Option<Arc<WorkQueue>>,
+}
+
+impl Drop for WorkPermit {
+    fn drop(&mut self) {
+        if let Some(queue) = self.queue.take() {
+            queue.release();
+        }
+    }
+}
+
+impl WorkQueue {
+    pub fn new(slots: usize, interactive_weight: usize) -> Arc<Self> {
+        Arc::new(Self {
+            slots: slots.max(1),
+            interactive_weight: interactive_weight.max(1),
+            queues: Mutex::new(Queues {
+                available: slots.max(1),
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// `WORK_SLOTS` (default: available parallelism) and `INTERACTIVE_WEIGHT`.
+    pub fn from_env() -> Arc<Self> {
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse().ok());
+        let slots = env("WORK_SLOTS")
+            .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4));
+        Self::new(slots, env("INTERACTIVE_WEIGHT").unwrap_or(DEFAULT_INTERACTIVE_WEIGHT))
+    }
+
+    pub async fn acquire(self: &Arc<Self>, class: WorkClass) -> WorkPermit {
+        let receiver = {
+            let mut queues = self.queues.lock().unwrap();
+            if queues.available > 0 && queues.interactive.is_empty() && queues.batch.is_empty() {
+                queues.available -= 1;
+                return WorkPermit { queue: Some(self.clone()) };
+            }
+            let (sender, receiver) = oneshot::channel();
+            match class {
+                WorkClass::Interactive => queues.interactive.push_back(sender),
+                WorkClass::Batch => queues.batch.push_back(sender),
+            }
+            receiver
+        };
+        receiver.await.expect("work queue never drops a waiter's sender")
+    }
+
+    fn release(self: &Arc<Self>) {
+        let mut queues = self.queues.lock().unwrap();
+        queues.available += 1;
+        while queues.available > 0 {
+            let prefer_interactive = match (queues.interactive.is_empty(), queues.batch.is_empty()) {
+                (true, true) => return,
+                (false, true) => true,
+                (true, false) => false,
+                (false, false) => queues.streak < self.interactive_weight,
+            };
+            let sender = match prefer_interactive {
+                true => {
+                    queues.streak += 1;
+                    queues.interactive.pop_front()
+                }
+                false => {
+                    queues.streak = 0;
+                    queues.batch.pop_front()
+                }
+            };
+            let Some(sender) = sender else {
+                return;
+            };
+            queues.available -= 1;
+            // A waiter that gave up hands the permit straight back; it is
+            // defused rather than dropped, since its Drop would take the lock
+            if let Err(mut permit) = sender.send(WorkPermit { queue: Some(self.clone()) }) {
+                permit.queue = None;
+                queues.available += 1;
+            }
+        }
+    }
+
+    /// Requests of either class waiting for a slot.
+    pub fn waiting(&self) -> usize {
+        let queues = self.queues.lock().unwrap();
+        queues.interactive.len() + queues.batch.len()
+    }
+
+    pub fn stats(&self) -> WorkQueueStats {
+        let queues = self.queues.lock().unwrap();
+        WorkQueueStats {
+            slots: self.slots,
+            available: queues.available,
+            interactive_waiting: queues.interactive.len(),
+            batch_waiting: queues.batch.len(),
+        }
+    }
+}
+
+/// Runs the request in an interactive work slot.
+pub async fn interactive(State(queue): State<Arc<WorkQueue>>, request: Request, next: Next) -> Response {
+    let _permit = queue.acquire(WorkClass::Interactive).await;
+    next.run(request).await
+}
+
+/// Turns batch requests away while the work queue and queued index jobs
+/// together are `max_depth` deep, instead of piling up more work.
+pub struct Backpressure {
+    work: Arc<WorkQueue>,
+    jobs: Arc<JobRegistry>,
+    max_depth: usize,
+    retry_after_secs: u64,
+}
+
+impl Backpressure {
+    /// `MAX_QUEUE_DEPTH` and `QUEUE_RETRY_AFTER_SECS`; `None` when no depth is set.
+    pub fn from_env(work: Arc<WorkQueue>, jobs: Arc<JobRegistry>) -> Option<Arc<Self>> {
+        let max_depth = std::env::var("MAX_QUEUE_DEPTH")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|&depth| depth > 0)?;
+        Some(Arc::new(Self {
+            work,
+            jobs,
+            max_depth,
+            retry_after_secs: std::env::var("QUEUE_RETRY_AFTER_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_RETRY_AFTER_SECS),
+        }))
+    }
+
+    pub fn depth(&self) -> usize {
+        self.work.waiting() + self.jobs.queued()
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+}
+
+/// Answers 429 with `Retry-After` and `X-Queue-Depth` while the queue is full.
+pub async fn backpressure(State(limit): State<Arc<Backpressure>>, request: Request, next: Next) -> Response {
+    let depth = limit.depth();
+    if depth >= limit.max_depth {
+        tracing::warn!(depth, max_depth = limit.max_depth, "Queue full, rejecting {}", request.uri().path());
+        let headers = [
+            (header::RETRY_AFTER, limit.retry_after_secs.to_string()),
+            (header::HeaderName::from_static(QUEUE_DEPTH_HEADER), depth.to_string()),
+        ];
+        return (StatusCode::TOO_MANY_REQUESTS, headers).into_response();
+    }
+    next.run(request).await
+}
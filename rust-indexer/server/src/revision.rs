@@ -0,0 +1,79 @@
+use crate::error::SourceError;
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// A repository's tree at a git ref, exported to a temporary directory.
+/// Call `remove` when done with it.
+pub struct Snapshot {
+    pub path: PathBuf,
+}
+
+impl Snapshot {
+    /// Exports `git_ref` of the repository at `repo_path` with `git archive`,
+    /// which only reads the object store, so read-only checkouts work.
+    pub async fn export(repo_path: &str, git_ref: &str) -> Result<Self> {
+        // A ref starting with `-` would be taken for an option
+        if git_ref.is_empty() || git_ref.starts_with('-') {
+            return Err(SourceError::UnknownRevision(git_ref.to_string()).into());
+        }
+        let verified = Command::new("git")
+            .arg("-C")
+            .arg(repo_path)
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("{}^{{tree}}", git_ref))
+            .output()
+            .await
+            .context("Failed to run git")?;
+        if !verified.status.success() {
+            return Err(SourceError::UnknownRevision(git_ref.to_string()).into());
+        }
+
+        let path = std::env::temp_dir().join(format!("sherlock-snapshot-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&path).await?;
+        let snapshot = Self { path };
+        if let Err(e) = snapshot.extract(repo_path, git_ref).await {
+            snapshot.remove().await;
+            return Err(e);
+        }
+        Ok(snapshot)
+    }
+
+    async fn extract(&self, repo_path: &str, git_ref: &str) -> Result<()> {
+        let archive = self.path.with_extension("tar");
+        let archived = Command::new("git")
+            .arg("-C")
+            .arg(repo_path)
+            .args(["archive", "--format=tar", "-o"])
+            .arg(&archive)
+            .arg(git_ref)
+            .output()
+            .await
+            .context("Failed to run git")?;
+        if !archived.status.success() {
+            bail!("git archive failed: {}", String::from_utf8_lossy(&archived.stderr).trim());
+        }
+
+        let unpacked = Command::new("tar").arg("-xf").arg(&archive).arg("-C").arg(&self.path).output().await;
+        let _ = tokio::fs::remove_file(&archive).await;
+        let unpacked = unpacked.context("Failed to run tar")?;
+        if !unpacked.status.success() {
+            bail!("tar failed: {}", String::from_utf8_lossy(&unpacked.stderr).trim());
+        }
+        Ok(())
+    }
+
+    /// Where `file_path`, inside the snapshot, lives in the repository.
+    pub fn repo_file_path(&self, file_path: &str, repo_path: &str) -> String {
+        match Path::new(file_path).strip_prefix(&self.path) {
+            Ok(relative) => Path::new(repo_path).join(relative).to_string_lossy().into_owned(),
+            Err(_) => file_path.to_string(),
+        }
+    }
+
+    pub async fn remove(self) {
+        if let Err(e) = tokio::fs::remove_dir_all(&self.path).await {
+            tracing::warn!("Failed to remove snapshot {}: {}", self.path.display(), e);
+        }
+    }
+}
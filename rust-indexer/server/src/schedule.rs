@@ -0,0 +1,143 @@
+use crate::config::IndexerConfig;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Mutex, RwLock};
+
+/// Parses a cron expression with a leading seconds field, e.g. `0 0 */6 * * *`.
+pub fn parse(expr: &str) -> Result<Schedule> {
+    Schedule::from_str(expr).with_context(|| format!("Invalid cron expression {:?}", expr))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleRequest {
+    pub cron: String,
+}
+
+/// Where a schedule was defined; the admin API overrides the config file.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleSource {
+    Config,
+    Api,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduleInfo {
+    pub repo_path: String,
+    pub cron: String,
+    pub source: ScheduleSource,
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_job_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduleList {
+    pub schedules: Vec<ScheduleInfo>,
+    pub success: bool,
+}
+
+struct Planned {
+    cron: String,
+    next_run: Option<DateTime<Utc>>,
+    last_run: Option<DateTime<Utc>>,
+    last_job_id: Option<String>,
+}
+
+/// Periodic re-index schedules per repository, from `[schedules]` in the
+/// config file and the admin API.
+#[derive(Default)]
+pub struct Scheduler {
+    api: RwLock<HashMap<String, String>>,
+    planned: Mutex<HashMap<String, Planned>>,
+}
+
+impl Scheduler {
+    pub fn set(&self, repo_path: &str, cron: &str) -> Result<()> {
+        parse(cron)?;
+        self.api.write().unwrap().insert(repo_path.to_string(), cron.to_string());
+        Ok(())
+    }
+
+    /// Drops an API-defined schedule; config schedules stay until the file changes.
+    pub fn remove(&self, repo_path: &str) -> bool {
+        self.api.write().unwrap().remove(repo_path).is_some()
+    }
+
+    fn effective(&self, config: &IndexerConfig) -> HashMap<String, (String, ScheduleSource)> {
+        let mut schedules: HashMap<String, (String, ScheduleSource)> = config
+            .schedules
+            .iter()
+            .map(|(repo, cron)| (repo.clone(), (cron.clone(), ScheduleSource::Config)))
+            .collect();
+        for (repo, cron) in self.api.read().unwrap().iter() {
+            schedules.insert(repo.clone(), (cron.clone(), ScheduleSource::Api));
+        }
+        schedules
+    }
+
+    /// Repositories whose next run has come, advancing their schedules. A new
+    /// or changed schedule first runs at its next occurrence, not immediately.
+    pub fn due(&self, config: &IndexerConfig, now: DateTime<Utc>) -> Vec<String> {
+        let schedules = self.effective(config);
+        let mut planned = self.planned.lock().unwrap();
+        planned.retain(|repo, _| schedules.contains_key(repo));
+
+        let mut due = Vec::new();
+        for (repo, (cron, _)) in schedules {
+            let Ok(schedule) = parse(&cron) else {
+                continue;
+            };
+            let entry = planned.entry(repo.clone()).or_insert_with(|| Planned {
+                cron: cron.clone(),
+                next_run: None,
+                last_run: None,
+                last_job_id: None,
+            });
+            if entry.cron != cron || entry.next_run.is_none() {
+                entry.cron = cron;
+                entry.next_run = schedule.after(&now).next();
+                continue;
+            }
+            if entry.next_run.is_some_and(|next| next <= now) {
+                entry.last_run = Some(now);
+                entry.next_run = schedule.after(&now).next();
+                due.push(repo);
+            }
+        }
+        due
+    }
+
+    pub fn record_job(&self, repo_path: &str, job_id: &str) {
+        if let Some(entry) = self.planned.lock().unwrap().get_mut(repo_path) {
+            entry.last_job_id = Some(job_id.to_string());
+        }
+    }
+
+    pub fn list(&self, config: &IndexerConfig) -> Vec<ScheduleInfo> {
+        let planned = self.planned.lock().unwrap();
+        let mut schedules: Vec<ScheduleInfo> = self
+            .effective(config)
+            .into_iter()
+            .map(|(repo_path, (cron, source))| {
+                let entry = planned.get(&repo_path).filter(|entry| entry.cron == cron);
+                ScheduleInfo {
+                    next_run: entry
+                        .and_then(|entry| entry.next_run)
+                        .or_else(|| parse(&cron).ok().and_then(|s| s.upcoming(Utc).next())),
+                    last_run: entry.and_then(|entry| entry.last_run),
+                    last_job_id: entry.and_then(|entry| entry.last_job_id.clone()),
+                    repo_path,
+                    cron,
+                    source,
+                }
+            })
+            .collect();
+        schedules.sort_by(|a, b| a.repo_path.cmp(&b.repo_path));
+        schedules
+    }
+}
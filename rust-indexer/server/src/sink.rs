@@ -0,0 +1,423 @@
+use crate::symbol::{CodeChunk, CodeSymbol};
+use anyhow::{Context, Result};
+use serde_json::json;
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+/// A chunk embedding ready to be written, with the symbols it covers.
+#[derive(Debug, Clone)]
+pub struct VectorRecord {
+    pub repo_path: String,
+    /// Tenant of the key that indexed it, so searches can be partitioned.
+    pub tenant: Option<String>,
+    /// Owners of the chunk's file under the repository's CODEOWNERS.
+    pub owners: Vec<String>,
+    /// The chunk's file is generated code, indexed because
+    /// `include_generated` is set or it was indexed on its own.
+    pub generated: bool,
+    /// The chunk's file is vendored or third-party code.
+    pub external: bool,
+    pub chunk: CodeChunk,
+    pub vector: Vec<f32>,
+    pub symbols: Vec<CodeSymbol>,
+}
+
+impl VectorRecord {
+    /// Keyed by tenant, file and content so unchanged chunks keep their
+    /// vector across re-indexes even when surrounding code shifts their
+    /// position, and tenants indexing the same file never share a point.
+    pub fn key(&self) -> String {
+        match &self.tenant {
+            Some(tenant) => format!("{}|{}#{}", tenant, self.chunk.file_path, self.chunk.hash),
+            None => format!("{}#{}", self.chunk.file_path, self.chunk.hash),
+        }
+    }
+
+    fn point_id(&self) -> Uuid {
+        point_id(&self.key())
+    }
+
+    fn payload(&self) -> serde_json::Value {
+        json!({
+            "key": self.key(),
+            "repo_path": self.repo_path,
+            "tenant": self.tenant,
+            "owners": self.owners,
+            "generated": self.generated,
+            "external": self.external,
+            "chunk_id": self.chunk.id,
+            "file_path": self.chunk.file_path,
+            "line_start": self.chunk.line_start,
+            "line_end": self.chunk.line_end,
+            "hash": self.chunk.hash,
+            "content": self.chunk.content,
+            "language": self.chunk.language,
+            "symbol": self.chunk.symbol,
+            "imports": self.chunk.imports,
+            "enrichment": self.chunk.enrichment,
+            "symbols": self.symbols.iter().map(|s| json!({
+                "id": s.id,
+                "symbol_name": s.symbol_name,
+                "symbol_type": s.symbol_type,
+                "line_start": s.line_start,
+                "line_end": s.line_end,
+                "signature": s.signature,
+                "exported": s.exported,
+                "reference_count": s.reference_count,
+                "enrichment": s.enrichment,
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn point_id(key: &str) -> Uuid {
+    Uuid::new_v5(&Uuid::NAMESPACE_URL, key.as_bytes())
+}
+
+/// Where chunk embeddings are persisted.
+pub enum VectorSink {
+    Qdrant(QdrantSink),
+    Pgvector(PgvectorSink),
+}
+
+impl VectorSink {
+    /// Reads `VECTOR_STORE` (`qdrant` or `pgvector`) and its settings; `None` when unset.
+    pub async fn from_env() -> Result<Option<Self>> {
+        match std::env::var("VECTOR_STORE").as_deref() {
+            Ok("qdrant") => Ok(Some(VectorSink::Qdrant(QdrantSink::from_env()?))),
+            Ok("pgvector") => Ok(Some(VectorSink::Pgvector(PgvectorSink::connect_from_env().await?))),
+            Ok("") | Err(_) => Ok(None),
+            Ok(other) => Err(anyhow::anyhow!("Unknown VECTOR_STORE: {}", other)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VectorSink::Qdrant(_) => "qdrant",
+            VectorSink::Pgvector(_) => "pgvector",
+        }
+    }
+
+    /// Checks that the store answers, without touching the collection or table.
+    pub async fn ping(&self) -> Result<()> {
+        match self {
+            VectorSink::Qdrant(sink) => sink.ping().await,
+            VectorSink::Pgvector(sink) => sink.ping().await,
+        }
+    }
+
+    pub async fn upsert(&self, records: &[VectorRecord]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        match self {
+            VectorSink::Qdrant(sink) => sink.upsert(records).await,
+            VectorSink::Pgvector(sink) => sink.upsert(records).await,
+        }
+    }
+
+    /// Removes vectors `tenant` stored for `file_path` whose keys are not in
+    /// `keep`, i.e. chunks that disappeared since the previous index of the file.
+    pub async fn delete_stale(&self, tenant: Option<&str>, file_path: &str, keep: &[String]) -> Result<()> {
+        let scope = Scope::Tenant(tenant);
+        match self {
+            VectorSink::Qdrant(sink) => sink.delete_stale(scope, "file_path", file_path, keep, &[]).await,
+            VectorSink::Pgvector(sink) => sink.delete_stale(scope, "file_path", file_path, keep, &[]).await,
+        }
+    }
+
+    /// Removes every vector stored for `repo_path`, whichever tenant indexed it.
+    pub async fn delete_repo(&self, repo_path: &str) -> Result<()> {
+        match self {
+            VectorSink::Qdrant(sink) => sink.delete_stale(Scope::AllTenants, "repo_path", repo_path, &[], &[]).await,
+            VectorSink::Pgvector(sink) => sink.delete_stale(Scope::AllTenants, "repo_path", repo_path, &[], &[]).await,
+        }
+    }
+
+    /// Removes vectors `tenant` stored for `repo_path` whose keys are not in
+    /// `keep`, which also drops every chunk of files deleted from the
+    /// repository. Vectors of `skip_files`, which a run failed to index, are
+    /// left as they were.
+    pub async fn delete_stale_repo(
+        &self,
+        tenant: Option<&str>,
+        repo_path: &str,
+        keep: &[String],
+        skip_files: &[String],
+    ) -> Result<()> {
+        let scope = Scope::Tenant(tenant);
+        match self {
+            VectorSink::Qdrant(sink) => sink.delete_stale(scope, "repo_path", repo_path, keep, skip_files).await,
+            VectorSink::Pgvector(sink) => sink.delete_stale(scope, "repo_path", repo_path, keep, skip_files).await,
+        }
+    }
+}
+
+/// Whose vectors a deletion may touch.
+#[derive(Clone, Copy)]
+enum Scope<'a> {
+    /// Only those indexed by the tenant, or without one for `None`.
+    Tenant(Option<&'a str>),
+    AllTenants,
+}
+
+pub struct QdrantSink {
+    url: String,
+    collection: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+    collection_ready: OnceCell<()>,
+}
+
+impl QdrantSink {
+    fn from_env() -> Result<Self> {
+        let url = std::env::var("QDRANT_URL").context("QDRANT_URL must be set when VECTOR_STORE=qdrant")?;
+        let collection = std::env::var("QDRANT_COLLECTION").unwrap_or_else(|_| "sherlock_chunks".to_string());
+        let api_key = std::env::var("QDRANT_API_KEY").ok().filter(|k| !k.is_empty());
+        Ok(Self::new(&url, &collection, api_key))
+    }
+
+    pub fn new(url: &str, collection: &str, api_key: Option<String>) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            collection: collection.to_string(),
+            api_key,
+            http: reqwest::Client::new(),
+            collection_ready: OnceCell::new(),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let request = self.http.request(method, format!("{}/collections/{}{}", self.url, self.collection, path));
+        self.authorize(request)
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.api_key {
+            Some(key) => request.header("api-key", key),
+            None => request,
+        }
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.authorize(self.http.get(format!("{}/collections", self.url)))
+            .send()
+            .await?
+            .error_for_status()
+            .context("Qdrant is not healthy")?;
+        Ok(())
+    }
+
+    /// Creates the collection on first use, sized to the first vector seen.
+    async fn ensure_collection(&self, dimensions: usize) -> Result<()> {
+        self.collection_ready
+            .get_or_try_init(|| async {
+                let existing = self.request(reqwest::Method::GET, "").send().await?;
+                if existing.status().is_success() {
+                    return Ok(());
+                }
+                self.request(reqwest::Method::PUT, "")
+                    .json(&json!({ "vectors": { "size": dimensions, "distance": "Cosine" } }))
+                    .send()
+                    .await?
+                    .error_for_status()
+                    .context("Failed to create Qdrant collection")?;
+                Ok::<(), anyhow::Error>(())
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn upsert(&self, records: &[VectorRecord]) -> Result<()> {
+        self.ensure_collection(records[0].vector.len()).await?;
+
+        let points: Vec<_> = records
+            .iter()
+            .map(|r| json!({ "id": r.point_id(), "vector": r.vector, "payload": r.payload() }))
+            .collect();
+
+        self.request(reqwest::Method::PUT, "/points?wait=true")
+            .json(&json!({ "points": points }))
+            .send()
+            .await?
+            .error_for_status()
+            .context("Qdrant upsert failed")?;
+        Ok(())
+    }
+
+    async fn delete_stale(
+        &self,
+        scope: Scope<'_>,
+        field: &str,
+        value: &str,
+        keep: &[String],
+        skip_files: &[String],
+    ) -> Result<()> {
+        if self.collection_ready.get().is_none() {
+            let existing = self.request(reqwest::Method::GET, "").send().await?;
+            if !existing.status().is_success() {
+                return Ok(());
+            }
+        }
+
+        let keep_ids: Vec<Uuid> = keep.iter().map(|k| point_id(k)).collect();
+        let mut must = vec![json!({ "key": field, "match": { "value": value } })];
+        match scope {
+            Scope::Tenant(Some(tenant)) => must.push(json!({ "key": "tenant", "match": { "value": tenant } })),
+            Scope::Tenant(None) => must.push(json!({ "is_null": { "key": "tenant" } })),
+            Scope::AllTenants => {}
+        }
+        let mut must_not = vec![json!({ "has_id": keep_ids })];
+        if !skip_files.is_empty() {
+            must_not.push(json!({ "key": "file_path", "match": { "any": skip_files } }));
+        }
+        self.request(reqwest::Method::POST, "/points/delete?wait=true")
+            .json(&json!({
+                "filter": {
+                    "must": must,
+                    "must_not": must_not,
+                }
+            }))
+            .send()
+            .await?
+            .error_for_status()
+            .context("Qdrant delete failed")?;
+        Ok(())
+    }
+}
+
+pub struct PgvectorSink {
+    client: tokio_postgres::Client,
+    table: String,
+    table_ready: OnceCell<()>,
+}
+
+impl PgvectorSink {
+    async fn connect_from_env() -> Result<Self> {
+        let url = std::env::var("PGVECTOR_URL").context("PGVECTOR_URL must be set when VECTOR_STORE=pgvector")?;
+        let table = std::env::var("PGVECTOR_TABLE").unwrap_or_else(|_| "sherlock_chunks".to_string());
+        if !table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(anyhow::anyhow!("PGVECTOR_TABLE must be a plain identifier"));
+        }
+
+        let (client, connection) = tokio_postgres::connect(&url, tokio_postgres::NoTls)
+            .await
+            .context("Failed to connect to pgvector database")?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::error!("pgvector connection error: {}", e);
+            }
+        });
+
+        Ok(Self {
+            client,
+            table,
+            table_ready: OnceCell::new(),
+        })
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.client
+            .simple_query("SELECT 1")
+            .await
+            .context("pgvector connection is not usable")?;
+        Ok(())
+    }
+
+    async fn ensure_table(&self, dimensions: usize) -> Result<()> {
+        self.table_ready
+            .get_or_try_init(|| async {
+                self.client
+                    .batch_execute(&format!(
+                        "CREATE EXTENSION IF NOT EXISTS vector;
+                         CREATE TABLE IF NOT EXISTS {table} (
+                             key TEXT PRIMARY KEY,
+                             repo_path TEXT NOT NULL,
+                             file_path TEXT NOT NULL,
+                             embedding vector({dimensions}) NOT NULL,
+                             metadata JSONB NOT NULL
+                         );
+                         CREATE INDEX IF NOT EXISTS {table}_file_path_idx ON {table} (file_path);
+                         CREATE INDEX IF NOT EXISTS {table}_repo_path_idx ON {table} (repo_path);",
+                        table = self.table,
+                        dimensions = dimensions,
+                    ))
+                    .await
+                    .context("Failed to create pgvector table")?;
+                Ok::<(), anyhow::Error>(())
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn upsert(&self, records: &[VectorRecord]) -> Result<()> {
+        self.ensure_table(records[0].vector.len()).await?;
+
+        let statement = format!(
+            "INSERT INTO {} (key, repo_path, file_path, embedding, metadata)
+             VALUES ($1, $2, $3, $4::text::vector, $5::text::jsonb)
+             ON CONFLICT (key) DO UPDATE SET
+                 repo_path = EXCLUDED.repo_path,
+                 file_path = EXCLUDED.file_path,
+                 embedding = EXCLUDED.embedding,
+                 metadata = EXCLUDED.metadata",
+            self.table
+        );
+        for record in records {
+            let vector = format!(
+                "[{}]",
+                record.vector.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(",")
+            );
+            self.client
+                .execute(
+                    &statement,
+                    &[
+                        &record.key(),
+                        &record.repo_path,
+                        &record.chunk.file_path,
+                        &vector,
+                        &record.payload().to_string(),
+                    ],
+                )
+                .await
+                .context("pgvector upsert failed")?;
+        }
+        Ok(())
+    }
+
+    async fn delete_stale(
+        &self,
+        scope: Scope<'_>,
+        field: &str,
+        value: &str,
+        keep: &[String],
+        skip_files: &[String],
+    ) -> Result<()> {
+        let exists = self
+            .client
+            .query_one("SELECT to_regclass($1) IS NOT NULL", &[&self.table])
+            .await?
+            .get::<_, bool>(0);
+        if !exists {
+            return Ok(());
+        }
+
+        let statement = format!(
+            "DELETE FROM {} WHERE {} = $1 AND NOT (key = ANY($2)) AND NOT (file_path = ANY($3))",
+            self.table, field
+        );
+        match scope {
+            Scope::Tenant(tenant) => {
+                self.client
+                    .execute(
+                        &format!("{} AND metadata->>'tenant' IS NOT DISTINCT FROM $4", statement),
+                        &[&value, &keep, &skip_files, &tenant],
+                    )
+                    .await
+            }
+            Scope::AllTenants => self.client.execute(&statement, &[&value, &keep, &skip_files]).await,
+        }
+        .context("pgvector delete failed")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use crate::parser::ParserService;
+use crate::symbol::CodeSymbol;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// An exported symbol as it appears in an API surface report.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ApiSymbol {
+    pub name: String,
+    pub qualified_name: String,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    pub file_path: String,
+    pub line_start: i32,
+    pub line_end: i32,
+}
+
+/// The exported symbols of one package or module.
+#[derive(Debug, Serialize)]
+pub struct ApiModule {
+    /// Package directory (Go, Java) or module path without extension,
+    /// relative to the repository; `.` for the repository root.
+    pub module: String,
+    pub language: String,
+    pub symbols: Vec<ApiSymbol>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiSurface {
+    pub modules: Vec<ApiModule>,
+    pub total_symbols: usize,
+    pub success: bool,
+}
+
+/// Exported symbols of a repository grouped by module, both in path order
+/// and symbols in source order. Vendored and third-party code is not part of
+/// the repository's surface and is left out. Files of languages whose extraction does not
+/// tell exported symbols apart (see `LanguageFeatures::visibility`) are
+/// reported by their default, e.g. every Java declaration.
+pub fn api_surface(parser: &ParserService, symbols: &[CodeSymbol], repo_path: &str) -> ApiSurface {
+    let mut modules: BTreeMap<(String, String), Vec<ApiSymbol>> = BTreeMap::new();
+    for symbol in symbols.iter().filter(|symbol| symbol.exported && !symbol.external) {
+        let relative = Path::new(&symbol.file_path).strip_prefix(repo_path).unwrap_or(Path::new(&symbol.file_path));
+        let Some(language) = relative
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| parser.language_for_extension(&ext.to_lowercase()))
+        else {
+            continue;
+        };
+        let module = module_of(relative, &language);
+        modules.entry((module, language)).or_default().push(ApiSymbol {
+            name: symbol.symbol_name.clone(),
+            qualified_name: symbol.qualified_name.clone().unwrap_or_else(|| symbol.symbol_name.clone()),
+            kind: symbol.symbol_type.clone(),
+            signature: symbol.signature.clone(),
+            file_path: symbol.file_path.clone(),
+            line_start: symbol.line_start,
+            line_end: symbol.line_end,
+        });
+    }
+
+    let modules: Vec<ApiModule> = modules
+        .into_iter()
+        .map(|((module, language), mut symbols)| {
+            symbols.sort_by(|a, b| a.file_path.cmp(&b.file_path).then_with(|| a.line_start.cmp(&b.line_start)));
+            ApiModule { module, language, symbols }
+        })
+        .collect();
+    ApiSurface {
+        total_symbols: modules.iter().map(|module| module.symbols.len()).sum(),
+        modules,
+        success: true,
+    }
+}
+
+/// Go and Java packages are directories; elsewhere each file is a module,
+/// with `mod.rs`, `lib.rs`, `main.rs`, `__init__.py` and `index.*` standing
+/// for their directory.
+fn module_of(relative: &Path, language: &str) -> String {
+    let stem = relative.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    let directory_module = matches!(language, "go" | "java") || matches!(stem, "mod" | "lib" | "main" | "__init__" | "index");
+    let module = if directory_module {
+        relative.parent().map(Path::to_path_buf).unwrap_or_default()
+    } else {
+        relative.with_extension("")
+    };
+    let module = module.to_string_lossy().replace('\\', "/");
+    if module.is_empty() {
+        ".".to_string()
+    } else {
+        module
+    }
+}
+
+/// An exported symbol whose signature changed between two revisions.
+#[derive(Debug, Serialize)]
+pub struct ApiChange {
+    pub module: String,
+    pub before: ApiSymbol,
+    pub after: ApiSymbol,
+}
+
+/// An exported symbol only one of two revisions has.
+#[derive(Debug, Serialize)]
+pub struct ApiEntry {
+    pub module: String,
+    #[serde(flatten)]
+    pub symbol: ApiSymbol,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiDiff {
+    pub base: String,
+    pub head: String,
+    pub added: Vec<ApiEntry>,
+    pub removed: Vec<ApiEntry>,
+    pub changed: Vec<ApiChange>,
+    /// Whether anything was removed or changed. Any change to a signature
+    /// counts, since compatible ones (a new defaulted parameter) cannot be
+    /// told apart from the declaration line alone.
+    pub breaking: bool,
+    /// Semantic version bump the diff calls for: `major` for breaking
+    /// changes, `minor` for additions only, `patch` for neither.
+    pub bump: &'static str,
+    pub success: bool,
+}
+
+/// Differences between the API surfaces of two revisions. Symbols match by
+/// module, qualified name and kind; overloads that share all three are
+/// compared by signature.
+pub fn diff(base: ApiSurface, head: ApiSurface, base_ref: &str, head_ref: &str) -> ApiDiff {
+    let base = by_key(base);
+    let mut head = by_key(head);
+    let (mut added, mut removed, mut changed) = (Vec::new(), Vec::new(), Vec::new());
+
+    for (key, mut before) in base {
+        let mut after = head.remove(&key).unwrap_or_default();
+        // Declarations present on both sides unchanged are not reported
+        let signatures: BTreeSet<_> = after.iter().map(normalized_signature).collect();
+        before.retain(|symbol| !signatures.contains(&normalized_signature(symbol)));
+        let signatures: BTreeSet<_> = before.iter().map(normalized_signature).collect();
+        after.retain(|symbol| !signatures.contains(&normalized_signature(symbol)));
+
+        let module = key.0;
+        if before.len() == 1 && after.len() == 1 {
+            changed.push(ApiChange {
+                module,
+                before: before.remove(0),
+                after: after.remove(0),
+            });
+            continue;
+        }
+        removed.extend(before.into_iter().map(|symbol| ApiEntry { module: module.clone(), symbol }));
+        added.extend(after.into_iter().map(|symbol| ApiEntry { module: module.clone(), symbol }));
+    }
+    for ((module, _, _), after) in head {
+        added.extend(after.into_iter().map(|symbol| ApiEntry { module: module.clone(), symbol }));
+    }
+
+    let breaking = !removed.is_empty() || !changed.is_empty();
+    let bump = if breaking {
+        "major"
+    } else if !added.is_empty() {
+        "minor"
+    } else {
+        "patch"
+    };
+    ApiDiff {
+        base: base_ref.to_string(),
+        head: head_ref.to_string(),
+        added,
+        removed,
+        changed,
+        breaking,
+        bump,
+        success: true,
+    }
+}
+
+fn by_key(surface: ApiSurface) -> BTreeMap<(String, String, String), Vec<ApiSymbol>> {
+    let mut symbols: BTreeMap<(String, String, String), Vec<ApiSymbol>> = BTreeMap::new();
+    for module in surface.modules {
+        for symbol in module.symbols {
+            let key = (module.module.clone(), symbol.qualified_name.clone(), symbol.kind.clone());
+            symbols.entry(key).or_default().push(symbol);
+        }
+    }
+    symbols
+}
+
+fn normalized_signature(symbol: &ApiSymbol) -> String {
+    symbol.signature.as_deref().unwrap_or_default().split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,488 @@
+use crate::chunker::ChunkSettings;
+use crate::context::CardFormat;
+use crate::export::ExportKind;
+use crate::literals::{Literal, LiteralFilter, LiteralKind};
+use crate::metrics::MetricKind;
+use crate::page::{SortOrder, SymbolSort};
+use crate::query_pack::{QueryMatch, QueryPackInfo};
+use crate::routes::Route;
+use serde::{Deserialize, Serialize};
+
+pub use sherlock_indexer_core::symbol::*;
+
+#[derive(Debug, Deserialize)]
+pub struct ExtractRequest {
+    pub start_line: Option<i32>,
+    pub end_line: Option<i32>,
+    #[serde(default)]
+    pub include_metrics: bool,
+    #[serde(default)]
+    pub include_halstead: bool,
+    #[serde(default)]
+    pub include_tokens: bool,
+    pub tokenizer: Option<String>,
+    /// Runs the configured symbol enrichment hooks.
+    #[serde(default)]
+    pub enrich: bool,
+    /// Parses the file as this language instead of detecting it from the path.
+    pub language: Option<String>,
+    /// Comma-separated symbol types to keep (`function,method`).
+    pub kinds: Option<String>,
+    /// Keeps only exported (public) symbols.
+    #[serde(default)]
+    pub exported_only: bool,
+    /// Keeps only symbols spanning at least this many lines.
+    pub min_lines: Option<i32>,
+    /// Result order; source order when neither this nor paging is requested.
+    pub sort: Option<SymbolSort>,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// Page size; all symbols when neither this nor `cursor` is given.
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page.
+    pub cursor: Option<String>,
+}
+
+impl ExtractRequest {
+    pub fn filter(&self) -> SymbolFilter {
+        SymbolFilter {
+            kinds: self
+                .kinds
+                .iter()
+                .flat_map(|kinds| kinds.split(','))
+                .map(|kind| kind.trim().to_lowercase())
+                .filter(|kind| !kind.is_empty())
+                .collect(),
+            exported_only: self.exported_only,
+            min_lines: self.min_lines,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsResponse {
+    pub file_path: String,
+    pub language: Option<String>,
+    pub has_errors: bool,
+    pub diagnostics: Vec<Diagnostic>,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AstQuery {
+    pub start_line: Option<i32>,
+    pub end_line: Option<i32>,
+    /// Parses the file as this language instead of detecting it from the path.
+    pub language: Option<String>,
+    /// `sexp` for a plain-text dump, like `Accept: text/x-sexp`.
+    pub format: Option<String>,
+    /// Levels below the starting node to include.
+    pub max_depth: Option<usize>,
+    /// Comma-separated node kinds to keep (`function_item,call_expression`).
+    pub kinds: Option<String>,
+    /// Adds the source text of leaf nodes.
+    #[serde(default)]
+    pub include_text: bool,
+}
+
+impl AstQuery {
+    pub fn filter(&self) -> AstFilter {
+        AstFilter {
+            max_depth: self.max_depth,
+            kinds: self
+                .kinds
+                .iter()
+                .flat_map(|kinds| kinds.split(','))
+                .map(|kind| kind.trim().to_string())
+                .filter(|kind| !kind.is_empty())
+                .collect(),
+            include_text: self.include_text,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AstResponse {
+    pub file_path: String,
+    pub language: String,
+    /// The starting node, or its matching descendants when `kinds` filters it out.
+    pub nodes: Vec<AstNode>,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HighlightResponse {
+    pub file_path: String,
+    pub language: String,
+    pub spans: Vec<HighlightSpan>,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HighlightQuery {
+    pub start_line: Option<i32>,
+    pub end_line: Option<i32>,
+    /// Parses the file as this language instead of detecting it from the path.
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DefinitionQuery {
+    pub repo: String,
+    /// A symbol name, optionally with its enclosing path (`Parser::new`).
+    pub name: String,
+    /// Only symbols of this `symbol_type`.
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DefinitionResponse {
+    pub name: String,
+    /// Best candidates first.
+    pub definitions: Vec<CodeSymbol>,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryPacksResponse {
+    pub packs: Vec<QueryPackInfo>,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryPackQuery {
+    /// Name of the pack, its `.scm` file name without the extension.
+    pub pack: String,
+    pub start_line: Option<i32>,
+    pub end_line: Option<i32>,
+    /// Parses the file as this language instead of detecting it from the path.
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryPackResponse {
+    pub file_path: String,
+    pub language: String,
+    pub pack: String,
+    pub matches: Vec<QueryMatch>,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LiteralQuery {
+    /// `string` or `constant`; both when absent.
+    pub kind: Option<LiteralKind>,
+    /// Keeps literals whose value or constant name contains this text, or
+    /// matches it whole when it has `*` wildcards; case-insensitive.
+    pub pattern: Option<String>,
+    /// Parses the file as this language instead of detecting it from the
+    /// path; single files only.
+    pub language: Option<String>,
+}
+
+impl LiteralQuery {
+    pub fn filter(&self) -> LiteralFilter {
+        LiteralFilter {
+            kind: self.kind,
+            pattern: self.pattern.clone().filter(|pattern| !pattern.is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileLiteralsResponse {
+    pub file_path: String,
+    pub language: String,
+    pub literals: Vec<Literal>,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LiteralsResponse {
+    pub literals: Vec<Literal>,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RouteQuery {
+    /// Keeps routes accepting this HTTP method, including `ANY` ones.
+    pub method: Option<String>,
+    /// Keeps routes of one framework: `flask`, `fastapi`, `spring`,
+    /// `express` or `axum`.
+    pub framework: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutesResponse {
+    pub routes: Vec<Route>,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SelectionQuery {
+    /// One-based line of the cursor.
+    pub line: i32,
+    /// Zero-based byte column of the cursor.
+    #[serde(default)]
+    pub column: usize,
+    /// Parses the file as this language instead of detecting it from the path.
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelectionResponse {
+    pub file_path: String,
+    pub language: String,
+    /// Innermost first, each strictly larger than the one before.
+    pub ranges: Vec<SelectionRange>,
+    pub success: bool,
+}
+
+/// What `GET`/`HEAD /meta/...` reports about a file, without parsing it.
+#[derive(Debug, Serialize)]
+pub struct FileMeta {
+    pub file_path: String,
+    pub size: u64,
+    pub language: Option<String>,
+    /// Same value `/hash` returns for the whole file; absent for binary or
+    /// oversized files.
+    pub hash: Option<String>,
+    pub binary_file: bool,
+    /// Why the file counts as generated, which leaves it out of repository
+    /// runs by default; absent when it does not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated: Option<&'static str>,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExtractResponse {
+    pub symbols: Vec<CodeSymbol>,
+    /// The file has syntax errors, so `symbols` may be incomplete.
+    pub has_errors: bool,
+    pub error_count: usize,
+    pub error_ranges: Vec<ErrorRange>,
+    /// See `CodeChunk::lossy_decoded`.
+    pub lossy_decoded: bool,
+    /// The file holds binary content and was not parsed.
+    pub binary_file: bool,
+    /// Pass as `cursor` to fetch the next page; absent on the last one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    pub success: bool,
+}
+
+/// `GET /symbols/:repo_path`: every symbol in a repository, a page at a time.
+#[derive(Debug, Deserialize)]
+pub struct SymbolListQuery {
+    #[serde(default)]
+    pub sort: SymbolSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+    /// Keeps only symbols in vendored or third-party code when true, only
+    /// first-party ones when false; both when absent.
+    pub external: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RankingQuery {
+    #[serde(default)]
+    pub metric: MetricKind,
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub include_halstead: bool,
+}
+
+/// `most` puts the most referenced symbols first, `least` the least.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageOrder {
+    #[default]
+    Most,
+    Least,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    #[serde(default)]
+    pub order: UsageOrder,
+    pub limit: Option<usize>,
+    /// Only exported symbols, e.g. to find unused public API.
+    #[serde(default)]
+    pub exported_only: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChunkRequest {
+    /// Overrides the configured settings for the file's language.
+    #[serde(flatten)]
+    pub settings: ChunkSettings,
+    #[serde(default)]
+    pub include_tokens: bool,
+    /// Sends each chunk to the configured embedding endpoint.
+    #[serde(default)]
+    pub embed: bool,
+    /// Runs the configured chunk enrichment hooks.
+    #[serde(default)]
+    pub enrich: bool,
+    /// Parses the file as this language instead of detecting it from the path.
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct LanguageQuery {
+    /// Parses the file as this language instead of detecting it from the path.
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChunkDiffRequest {
+    #[serde(flatten)]
+    pub chunking: ChunkRequest,
+    #[serde(default)]
+    pub previous: Vec<ChunkManifestEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChunkDiffResponse {
+    pub changed: Vec<CodeChunk>,
+    /// Tombstones: previous entries with no matching chunk any more.
+    pub removed: Vec<ChunkManifestEntry>,
+    pub unchanged: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChunkResponse {
+    pub chunks: Vec<CodeChunk>,
+    /// The file holds binary content and was not chunked.
+    pub binary_file: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContextPackRequest {
+    pub repo_path: String,
+    pub symbol_id: Option<String>,
+    #[serde(default)]
+    pub symbol_ids: Vec<String>,
+    pub token_budget: usize,
+    pub tokenizer: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContextQuery {
+    pub depth: Option<usize>,
+    /// Repository the symbol is in; required.
+    pub repo_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HierarchyQuery {
+    /// Repository the symbol is in; required.
+    pub repo_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImplementationsQuery {
+    /// Repository the symbol is in; required.
+    pub repo_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TraitQuery {
+    /// Only implementations of this trait, matched by name.
+    #[serde(rename = "trait")]
+    pub trait_name: Option<String>,
+    /// Only implementations for this type.
+    #[serde(rename = "type")]
+    pub type_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiDiffQuery {
+    /// Git ref of the earlier revision.
+    pub base: String,
+    /// Git ref of the later revision; defaults to the working tree.
+    pub head: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OwnerQuery {
+    /// A user (`@octocat`), team (`@org/team`) or email as written in
+    /// CODEOWNERS, matched case-insensitively.
+    pub owner: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OwnedSymbols {
+    pub owner: String,
+    /// Source files the owner owns, relative to the repository.
+    pub files: Vec<String>,
+    pub symbols: Vec<CodeSymbol>,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileOwners {
+    pub file_path: String,
+    /// The CODEOWNERS pattern that decides the owners; absent when no rule
+    /// matches.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    pub owners: Vec<String>,
+    pub success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CardQuery {
+    #[serde(default)]
+    pub format: CardFormat,
+    /// Repository the symbol is in; required.
+    pub repo_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub kind: ExportKind,
+    /// Emit identical chunks once, listing every location they occur at.
+    #[serde(default)]
+    pub dedup: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CachePurgeQuery {
+    /// Limits the purge to one repository; everything is purged when absent.
+    pub repo: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct WarmupRequest {
+    /// Files (`repo/path`) to parse into the cache.
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WarmupResponse {
+    pub grammars_loaded: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub grammar_errors: Vec<String>,
+    pub files_parsed: usize,
+    /// Files that could not be read or parsed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files_failed: Vec<String>,
+    pub duration_ms: u64,
+    pub success: bool,
+}
@@ -0,0 +1,18 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Requests whose handler panicked and were answered with a 500.
+static PANICS: AtomicU64 = AtomicU64::new(0);
+
+pub fn record_panic() {
+    PANICS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Process-wide counters in the Prometheus text exposition format.
+pub fn render() -> String {
+    format!(
+        "# HELP sherlock_indexer_panics_total Requests whose handler panicked.\n\
+         # TYPE sherlock_indexer_panics_total counter\n\
+         sherlock_indexer_panics_total {}\n",
+        PANICS.load(Ordering::Relaxed)
+    )
+}
@@ -0,0 +1,43 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use sherlock_indexer_core::timing::{self, CallStats};
+use std::sync::Arc;
+use std::time::Instant;
+
+pub const DURATION_HEADER: &str = "x-duration-ms";
+pub const BYTES_PARSED_HEADER: &str = "x-bytes-parsed";
+pub const CACHE_HEADER: &str = "x-parse-cache";
+pub const LANGUAGE_HEADER: &str = "x-language";
+
+fn set(response: &mut Response, name: &'static str, value: impl ToString) {
+    if let Ok(value) = HeaderValue::from_str(&value.to_string()) {
+        response.headers_mut().insert(HeaderName::from_static(name), value);
+    }
+}
+
+/// Reports how each call went in response headers, whatever the endpoint:
+/// `X-Duration-Ms` (also as `Server-Timing`) always, and for calls that
+/// parsed source, `X-Bytes-Parsed`, `X-Parse-Cache` (`hit` when nothing had
+/// to be read from disk, else `miss`) and `X-Language`.
+pub async fn annotate(request: Request, next: Next) -> Response {
+    let call = Arc::new(CallStats::default());
+    let started = Instant::now();
+    let mut response = timing::scope(call.clone(), next.run(request)).await;
+
+    let duration_ms = started.elapsed().as_millis();
+    set(&mut response, DURATION_HEADER, duration_ms);
+    set(&mut response, "server-timing", format!("total;dur={}", duration_ms));
+
+    if call.hits() + call.misses() > 0 {
+        set(&mut response, BYTES_PARSED_HEADER, call.bytes_parsed());
+        set(&mut response, CACHE_HEADER, if call.misses() == 0 { "hit" } else { "miss" });
+    }
+    if let Some(language) = call.language() {
+        set(&mut response, LANGUAGE_HEADER, language);
+    }
+    response
+}
@@ -0,0 +1,83 @@
+use crate::hierarchy::base_name;
+use crate::symbol::CodeSymbol;
+use serde::Serialize;
+
+/// One Rust `impl Trait for Type` block.
+#[derive(Debug, Serialize)]
+pub struct TraitImpl {
+    /// The trait as written (`serde::Serialize`, `From<u8>`).
+    #[serde(rename = "trait")]
+    pub trait_name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// Id of the `impl` symbol.
+    pub impl_id: String,
+    pub file_path: String,
+    pub line_start: i32,
+    pub line_end: i32,
+    /// Methods the block provides; those left to the trait's defaults are
+    /// not listed.
+    pub methods: Vec<String>,
+    /// Id of the trait's declaration, when it is in the repository.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trait_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraitImplsResponse {
+    pub implementations: Vec<TraitImpl>,
+    pub success: bool,
+}
+
+/// Trait implementations among a repository's symbols, optionally only
+/// those of `trait_name` and/or for `type_name`. Both match by name, so
+/// `Serialize` finds `impl serde::Serialize for ...` blocks.
+pub fn implementations(symbols: &[CodeSymbol], trait_name: Option<&str>, type_name: Option<&str>) -> Vec<TraitImpl> {
+    let mut implementations: Vec<TraitImpl> = symbols
+        .iter()
+        .filter(|symbol| symbol.symbol_type == "impl")
+        .filter_map(|symbol| Some((symbol, symbol.supertypes.first()?)))
+        .filter(|(symbol, written)| {
+            trait_name.iter().all(|name| base_name(name) == base_name(written))
+                && type_name.iter().all(|name| *name == symbol.symbol_name)
+        })
+        .map(|(symbol, written)| TraitImpl {
+            trait_name: written.clone(),
+            type_name: symbol.symbol_name.clone(),
+            impl_id: symbol.id.clone(),
+            file_path: symbol.file_path.clone(),
+            line_start: symbol.line_start,
+            line_end: symbol.line_end,
+            methods: methods(symbols, symbol),
+            trait_id: symbols
+                .iter()
+                .find(|candidate| candidate.symbol_type == "trait" && candidate.symbol_name == base_name(written))
+                .map(|candidate| candidate.id.clone()),
+        })
+        .collect();
+    implementations.sort_by(|a, b| {
+        a.trait_name
+            .cmp(&b.trait_name)
+            .then_with(|| a.type_name.cmp(&b.type_name))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+            .then_with(|| a.line_start.cmp(&b.line_start))
+    });
+    implementations
+}
+
+/// Functions declared directly in an `impl` block, leaving out functions
+/// nested in their bodies.
+fn methods(symbols: &[CodeSymbol], block: &CodeSymbol) -> Vec<String> {
+    let within = |symbol: &CodeSymbol, outer: &CodeSymbol| {
+        symbol.file_path == outer.file_path && symbol.line_start >= outer.line_start && symbol.line_end <= outer.line_end
+    };
+    let functions: Vec<&CodeSymbol> = symbols
+        .iter()
+        .filter(|symbol| symbol.symbol_type == "function" && within(symbol, block))
+        .collect();
+    functions
+        .iter()
+        .filter(|function| !functions.iter().any(|outer| outer.id != function.id && within(function, outer)))
+        .map(|function| function.symbol_name.clone())
+        .collect()
+}
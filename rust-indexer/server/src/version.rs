@@ -0,0 +1,48 @@
+use axum::{
+    extract::Request,
+    http::{HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+pub const VERSION_HEADER: &str = "x-api-version";
+/// Version legacy unprefixed routes answer as.
+pub const CURRENT: u32 = 1;
+/// Versions this build serves, each under `/v{n}`; unprefixed routes are
+/// legacy aliases for v1. Within a version, changes are additive only: new
+/// endpoints, new optional request fields and new response fields. Renamed
+/// or retyped fields, or a restructured `CodeSymbol`, ship as the next
+/// version under its own prefix while the older ones stay served.
+pub const SUPPORTED: &[u32] = &[1];
+
+/// Version named by a `/v{n}/` path prefix.
+fn path_version(path: &str) -> Option<u32> {
+    let rest = path.strip_prefix("/v")?;
+    let (number, _) = rest.split_once('/').unwrap_or((rest, ""));
+    number.parse().ok()
+}
+
+/// Checks the optional `X-Api-Version` request header against the version
+/// the path resolves to (its prefix, or [`CURRENT`] for legacy routes): a
+/// client written against another version gets 406 rather than a response
+/// shape it cannot read. The served version is stamped on every response.
+pub async fn negotiate(request: Request, next: Next) -> Result<Response, StatusCode> {
+    let served = path_version(request.uri().path()).unwrap_or(CURRENT);
+    if let Some(requested) = request.headers().get(VERSION_HEADER) {
+        let requested = requested
+            .to_str()
+            .ok()
+            .and_then(|value| value.trim().trim_start_matches(['v', 'V']).parse::<u32>().ok())
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        if requested != served {
+            tracing::warn!(requested, served, "Rejected request for an API version this route does not serve");
+            return Err(StatusCode::NOT_ACCEPTABLE);
+        }
+    }
+
+    let mut response = next.run(request).await;
+    if SUPPORTED.contains(&served) {
+        response.headers_mut().insert(VERSION_HEADER, HeaderValue::from(served));
+    }
+    Ok(response)
+}
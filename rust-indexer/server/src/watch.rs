@@ -0,0 +1,143 @@
+use crate::chunker;
+use crate::encoding;
+use crate::error::SourceError;
+use crate::parser::{ExtractOptions, ParserService};
+use crate::repo;
+use crate::symbol::CodeSymbol;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tokio::time::MissedTickBehavior;
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// The symbol's source text differs; moving it alone does not count.
+    Changed,
+}
+
+/// One line of `watch` output.
+#[derive(Debug, Serialize)]
+pub struct ChangeEvent<'a> {
+    pub event: ChangeKind,
+    pub file_path: &'a str,
+    pub symbol: &'a CodeSymbol,
+}
+
+/// Symbols of a file keyed by kind, name and occurrence, with a hash of
+/// each one's source text.
+type Symbols = HashMap<String, (CodeSymbol, String)>;
+
+struct WatchedFile {
+    modified: Option<SystemTime>,
+    len: u64,
+    symbols: Symbols,
+}
+
+/// Polls `dir` every `interval` and prints an NDJSON event for each symbol
+/// added, removed or changed since the previous poll. The first scan only
+/// records the starting state. Runs until the process is stopped.
+pub async fn run(parser: &ParserService, dir: &str, interval: Duration) -> Result<()> {
+    let root = Path::new(dir);
+    if !root.is_dir() {
+        return Err(SourceError::NotADirectory.into());
+    }
+
+    let mut files: HashMap<PathBuf, WatchedFile> = HashMap::new();
+    let mut ticks = tokio::time::interval(interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut baseline = true;
+    loop {
+        ticks.tick().await;
+
+        let mut seen = HashSet::new();
+        for path in repo::source_files(root, parser) {
+            let Ok(metadata) = tokio::fs::metadata(&path).await else {
+                continue;
+            };
+            seen.insert(path.clone());
+            let (modified, len) = (metadata.modified().ok(), metadata.len());
+            if files.get(&path).is_some_and(|file| file.modified == modified && file.len == len) {
+                continue;
+            }
+
+            // Unreadable files are remembered without symbols so they are not retried every poll
+            let symbols = snapshot(parser, &path).await.unwrap_or_else(|e| {
+                tracing::warn!("Skipping {}: {:#}", path.display(), e);
+                Symbols::new()
+            });
+            let previous = files.insert(path.clone(), WatchedFile { modified, len, symbols });
+            if !baseline {
+                let before = previous.map(|file| file.symbols).unwrap_or_default();
+                print_changes(&path, &before, &files[&path].symbols)?;
+            }
+        }
+
+        let deleted: Vec<PathBuf> = files.keys().filter(|path| !seen.contains(*path)).cloned().collect();
+        for path in deleted {
+            if let Some(file) = files.remove(&path) {
+                print_changes(&path, &file.symbols, &Symbols::new())?;
+            }
+        }
+        baseline = false;
+    }
+}
+
+async fn snapshot(parser: &ParserService, path: &Path) -> Result<Symbols> {
+    let file_path = path.to_str().context("Path is not valid UTF-8")?;
+    let symbols = parser.extract_symbols(file_path, &ExtractOptions::default()).await?;
+    let source = encoding::read(file_path).await?;
+    let lines: Vec<&str> = source.text.lines().collect();
+
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    Ok(symbols
+        .into_iter()
+        .map(|symbol| {
+            // Overloads share a name, so the nth one in the file is keyed apart
+            let name = symbol.qualified_name.as_deref().unwrap_or(&symbol.symbol_name);
+            let key = format!("{}:{}", symbol.symbol_type, name);
+            let occurrence = occurrences.entry(key.clone()).or_default();
+            *occurrence += 1;
+
+            let start = (symbol.line_start.max(1) as usize - 1).min(lines.len());
+            let end = (symbol.line_end.max(0) as usize).clamp(start, lines.len());
+            let hash = chunker::content_hash(&lines[start..end].join("\n"));
+            (format!("{}#{}", key, occurrence), (symbol, hash))
+        })
+        .collect())
+}
+
+/// Prints the differences between two snapshots of a file, in line order.
+fn print_changes(path: &Path, before: &Symbols, after: &Symbols) -> Result<()> {
+    let mut changes: Vec<(ChangeKind, &CodeSymbol)> = Vec::new();
+    for (key, (symbol, hash)) in after {
+        match before.get(key) {
+            None => changes.push((ChangeKind::Added, symbol)),
+            Some((_, previous)) if previous != hash => changes.push((ChangeKind::Changed, symbol)),
+            Some(_) => {}
+        }
+    }
+    for (key, (symbol, _)) in before {
+        if !after.contains_key(key) {
+            changes.push((ChangeKind::Removed, symbol));
+        }
+    }
+    changes.sort_by_key(|(_, symbol)| (symbol.line_start, symbol.line_end));
+
+    let file_path = path.to_string_lossy();
+    for (event, symbol) in changes {
+        let line = serde_json::to_string(&ChangeEvent {
+            event,
+            file_path: &file_path,
+            symbol,
+        })?;
+        println!("{}", line);
+    }
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use crate::jobs::{JobReport, JobState};
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MAX_ATTEMPTS: u32 = 3;
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub const SIGNATURE_HEADER: &str = "x-sherlock-signature";
+pub const EVENT_HEADER: &str = "x-sherlock-event";
+pub const TIMESTAMP_HEADER: &str = "x-sherlock-timestamp";
+
+/// Whether a job may name `url` as its callback: it must have the scheme,
+/// host and port of one of `allowed` and a path under that one's path, so a
+/// caller cannot make the service post to hosts of its choosing.
+pub fn is_allowed_callback(url: &str, allowed: &[String]) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    allowed.iter().filter_map(|prefix| reqwest::Url::parse(prefix).ok()).any(|prefix| {
+        url.scheme() == prefix.scheme()
+            && url.host_str() == prefix.host_str()
+            && url.port_or_known_default() == prefix.port_or_known_default()
+            && url.username().is_empty()
+            && (url.path() == prefix.path().trim_end_matches('/')
+                || url.path().starts_with(&format!("{}/", prefix.path().trim_end_matches('/'))))
+    })
+}
+
+/// Posts finished job reports to callback URLs. With `JOB_WEBHOOK_SECRET` set,
+/// each delivery carries its Unix time in `X-Sherlock-Timestamp` and is signed
+/// as `sha256=<hex HMAC-SHA256 of "<timestamp>.<body>">` in
+/// `X-Sherlock-Signature`, so receivers can refuse replayed reports.
+pub struct JobWebhooks {
+    http: reqwest::Client,
+    secret: Option<String>,
+}
+
+impl JobWebhooks {
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            http: reqwest::Client::builder().timeout(TIMEOUT).build()?,
+            secret: std::env::var("JOB_WEBHOOK_SECRET").ok().filter(|s| !s.is_empty()),
+        })
+    }
+
+    fn sign(&self, timestamp: u64, body: &[u8]) -> Option<String> {
+        let secret = self.secret.as_ref()?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+        mac.update(format!("{}.", timestamp).as_bytes());
+        mac.update(body);
+        let digest: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+        Some(format!("sha256={}", digest))
+    }
+
+    /// Delivers `report` to `url`, retrying with backoff; failures are only logged.
+    pub async fn notify(&self, url: &str, report: &JobReport) {
+        let event = match report.state {
+            JobState::Failed => "job.failed",
+            JobState::Superseded => "job.superseded",
+            _ => "job.completed",
+        };
+        let body = match serde_json::to_vec(report) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::error!("Failed to serialize job report: {}", e);
+                return;
+            }
+        };
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        let signature = self.sign(timestamp, &body);
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            let mut request = self
+                .http
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(EVENT_HEADER, event)
+                .header(TIMESTAMP_HEADER, timestamp)
+                .body(body.clone());
+            if let Some(signature) = &signature {
+                request = request.header(SIGNATURE_HEADER, signature);
+            }
+            match request.send().await.and_then(|r| r.error_for_status()) {
+                Ok(_) => return,
+                Err(e) if attempt < MAX_ATTEMPTS => {
+                    tracing::warn!("Job webhook to {} failed (attempt {}): {}", url, attempt, e);
+                    tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+                }
+                Err(e) => tracing::error!("Giving up on job webhook to {}: {}", url, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_callbacks_under_an_allowed_prefix_pass() {
+        let allowed = vec!["https://hooks.example.com/indexer/".to_string()];
+        assert!(is_allowed_callback("https://hooks.example.com/indexer/jobs?id=1", &allowed));
+        assert!(is_allowed_callback("https://hooks.example.com:443/indexer", &allowed));
+        for url in [
+            "http://hooks.example.com/indexer/jobs",
+            "https://hooks.example.com.evil.com/indexer/jobs",
+            "https://hooks.example.com/indexerx",
+            "https://hooks.example.com:8443/indexer/jobs",
+            "https://user@hooks.example.com/indexer/jobs",
+            "http://169.254.169.254/latest/meta-data",
+            "not a url",
+        ] {
+            assert!(!is_allowed_callback(url, &allowed), "allowed {}", url);
+        }
+        assert!(!is_allowed_callback("https://hooks.example.com/indexer/jobs", &[]));
+    }
+
+    #[test]
+    fn signature_covers_the_timestamp() {
+        let webhooks = JobWebhooks {
+            http: reqwest::Client::new(),
+            secret: Some("secret".to_string()),
+        };
+        assert_ne!(webhooks.sign(1, b"{}"), webhooks.sign(2, b"{}"));
+    }
+}
@@ -0,0 +1,60 @@
+use sherlock_indexer_core::{ExtractOptions, ParserService};
+use wasm_bindgen::prelude::*;
+
+/// Symbol extraction for source text held by the page, using the same
+/// grammars and extraction rules as the indexer service.
+#[wasm_bindgen]
+pub struct Extractor {
+    parser: ParserService,
+}
+
+#[wasm_bindgen]
+impl Extractor {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Extractor {
+        // Nothing is read from disk, so there is nothing to cache
+        Extractor {
+            parser: ParserService::with_cache_capacity(0),
+        }
+    }
+
+    /// Names of the built-in languages.
+    pub fn languages(&self) -> Vec<String> {
+        self.parser.languages()
+    }
+
+    /// Language `path` would be parsed as, or `undefined` when unsupported.
+    #[wasm_bindgen(js_name = detectLanguage)]
+    pub fn detect_language(path: &str) -> Option<String> {
+        ParserService::detect_language(path)
+    }
+
+    /// Symbols defined in `source`, shaped like the service's `symbols`.
+    /// `path` picks the language unless `language` is given, and is what
+    /// symbol ids and `file_path` are built from.
+    #[wasm_bindgen(js_name = extractSymbols)]
+    pub fn extract_symbols(
+        &self,
+        source: &str,
+        path: &str,
+        language: Option<String>,
+        include_metrics: Option<bool>,
+    ) -> Result<JsValue, JsError> {
+        let options = ExtractOptions {
+            include_metrics: include_metrics.unwrap_or(false),
+            language,
+            ..ExtractOptions::default()
+        };
+        let symbols = self
+            .parser
+            .extract_from_source(source, path, &options)
+            .map_err(|e| JsError::new(&format!("{:#}", e)))?;
+        Ok(serde_wasm_bindgen::to_value(&symbols)?)
+    }
+}
+
+impl Default for Extractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}